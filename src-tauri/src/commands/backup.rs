@@ -0,0 +1,46 @@
+use crate::db::backup::{self, BackupInfo};
+use crate::state::AppState;
+use serde::Serialize;
+use tauri::State;
+
+#[derive(Debug, Serialize)]
+pub struct IntegrityStatus {
+    /// Startup `integrity_check` findings or migration error; empty when the database was healthy
+    pub problems: Vec<String>,
+    pub backups: Vec<BackupInfo>,
+}
+
+#[tauri::command]
+pub async fn db_list_backups(state: State<'_, AppState>) -> Result<Vec<BackupInfo>, String> {
+    backup::list_backups(&state.app_dir.join(backup::BACKUP_DIR))
+}
+
+#[tauri::command]
+pub async fn db_create_backup(state: State<'_, AppState>) -> Result<BackupInfo, String> {
+    // A backup of a damaged file could rotate the last good one away
+    if !state.integrity_problems.is_empty() {
+        return Err("The database is damaged; restore a backup instead".to_string());
+    }
+    let retention = backup::load_retention(&state.db).await;
+    backup::create_backup(&state.db, &state.app_dir.join(backup::BACKUP_DIR), retention).await
+}
+
+/// Replace the database with `file_name` and restart the app to load it.
+#[tauri::command]
+pub async fn db_restore_backup(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    file_name: String,
+) -> Result<(), String> {
+    backup::schedule_restore(&state.app_dir, &file_name)?;
+    state.db.close().await;
+    app.restart();
+}
+
+#[tauri::command]
+pub async fn db_integrity_status(state: State<'_, AppState>) -> Result<IntegrityStatus, String> {
+    Ok(IntegrityStatus {
+        problems: state.integrity_problems.clone(),
+        backups: backup::list_backups(&state.app_dir.join(backup::BACKUP_DIR))?,
+    })
+}
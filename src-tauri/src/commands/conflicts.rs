@@ -0,0 +1,22 @@
+use crate::conflicts::{self, Resolution, WorklogConflict};
+use crate::state::AppState;
+use tauri::State;
+
+#[tauri::command]
+pub async fn conflict_list(state: State<'_, AppState>) -> Result<Vec<WorklogConflict>, String> {
+    conflicts::list_conflicts(&state.db)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn conflict_resolve(
+    state: State<'_, AppState>,
+    worklog_id: i64,
+    resolution: Resolution,
+) -> Result<(), String> {
+    if conflicts::resolve(&state.db, worklog_id, resolution).await? {
+        state.sync.wake();
+    }
+    Ok(())
+}
@@ -1,31 +1,80 @@
-use crate::jira::client::{extract_adf_text, JiraClient};
-use crate::jira::types::{JiraIssue, JiraUser};
-use crate::state::AppState;
+use crate::conflicts::{self, ImportMerge, WorklogFields};
+use crate::jira::auth::OAuthAuth;
+use crate::jira::error::JiraError;
+use crate::jira::client::{comment_text, JiraClient, LOCAL_ID_PROPERTY};
+use crate::jira::oauth::{self, OAuthApp, OAuthConnection, PendingAuthorization};
+use crate::jira::types::{JiraFlavor, JiraIssue, JiraUser, JiraWorklogEntry};
+use crate::rounding;
+use crate::state::{AppState, JiraConfig};
+use crate::sync::{self, SyncError, SyncOperation, UnsyncedWorklog, UNSYNCED_COLUMNS};
+use crate::sync_events::{self, EventOperation, SyncEvent};
+use crate::tray_menu;
 use chrono::{DateTime, Local, NaiveDate};
 use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
 use std::collections::HashMap;
+use std::sync::Arc;
 use tauri::State;
+use tauri_plugin_opener::OpenerExt;
 
 /// Convert any date string to Jira format: "2021-01-17T12:34:00.000+0000"
-fn format_for_jira(date_str: &str) -> Result<String, String> {
+pub(crate) fn format_for_jira(date_str: &str) -> Result<String, String> {
     let dt = DateTime::parse_from_rfc3339(date_str)
         .map_err(|e| format!("Invalid date '{}': {}", date_str, e))?;
     Ok(dt.format("%Y-%m-%dT%H:%M:%S%.3f%z").to_string())
 }
 
-fn get_client(state: &AppState) -> Result<JiraClient, String> {
+/// Upper bound on issues pulled by a worklog JQL search. Hitting it is reported
+/// to the caller instead of silently dropping issues.
+const MAX_WORKLOG_SEARCH_ISSUES: usize = 1000;
+
+pub(crate) fn get_client(state: &AppState) -> Result<JiraClient, String> {
     let config = state
         .jira_config
         .lock()
         .unwrap_or_else(|e| e.into_inner())
         .clone()
         .ok_or_else(|| "Jira not configured. Go to Settings to set up connection.".to_string())?;
-    Ok(JiraClient::new(
-        state.http_client.clone(),
-        &config.base_url,
-        &config.email,
-        &config.api_token,
-    ))
+    let client = match &config.oauth {
+        Some(connection) => JiraClient::with_auth(
+            state.http_client.clone(),
+            &connection.api_base_url(),
+            JiraFlavor::Cloud,
+            Arc::new(OAuthAuth::new(
+                state.http_client.clone(),
+                connection.app.clone(),
+                state.oauth_tokens.clone(),
+            )),
+        ),
+        None => JiraClient::new(
+            state.http_client.clone(),
+            &config.base_url,
+            config.flavor,
+            &config.email,
+            &config.api_token,
+        ),
+    };
+    Ok(rate_limited(state, client))
+}
+
+/// `client` under the configured retry policy, sharing the app's rate limiter.
+fn rate_limited(state: &AppState, client: JiraClient) -> JiraClient {
+    let policy = state
+        .jira_retry_policy
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone();
+    client.with_retry(policy, state.jira_rate_limiter.clone())
+}
+
+#[tauri::command]
+pub async fn jira_detect_flavor(
+    state: State<'_, AppState>,
+    base_url: String,
+) -> Result<JiraFlavor, String> {
+    JiraClient::detect_flavor(&state.http_client, &base_url)
+        .await
+        .map_err(|e| format!("Could not detect Jira deployment type: {}", e))
 }
 
 #[tauri::command]
@@ -34,9 +83,132 @@ pub async fn jira_test_connection(
     base_url: String,
     email: String,
     api_token: String,
+    flavor: Option<JiraFlavor>,
 ) -> Result<JiraUser, String> {
-    let client = JiraClient::new(state.http_client.clone(), &base_url, &email, &api_token);
-    client.get_myself().await
+    let flavor = match flavor {
+        Some(f) => f,
+        None => JiraClient::detect_flavor(&state.http_client, &base_url)
+            .await
+            .map_err(|e| format!("Could not detect Jira deployment type: {}", e))?,
+    };
+    let client = JiraClient::new(state.http_client.clone(), &base_url, flavor, &email, &api_token);
+    rate_limited(&state, client).get_myself().await.map_err(|e| e.to_string())
+}
+
+/// How long the browser consent step may take before the loopback listener gives up.
+const OAUTH_CONSENT_TIMEOUT_SECS: u64 = 300;
+
+/// Connect through OAuth 2.0 (3LO): open the Atlassian consent page in the browser,
+/// receive the code on a loopback redirect, and store the tokens. `site_url` picks
+/// the site when the account can access several.
+#[tauri::command]
+pub async fn jira_oauth_connect(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    client_id: String,
+    client_secret: Option<String>,
+    redirect_port: u16,
+    site_url: Option<String>,
+) -> Result<JiraUser, String> {
+    let oauth_app = OAuthApp {
+        client_id,
+        client_secret: client_secret.filter(|s| !s.is_empty()),
+        redirect_port,
+        token_url: OAuthApp::default_token_url(),
+    };
+
+    let pending = PendingAuthorization::begin(&oauth_app).await?;
+    app.opener()
+        .open_url(&pending.authorize_url, None::<&str>)
+        .map_err(|e| format!("Failed to open browser: {}", e))?;
+    let tokens = pending
+        .complete(
+            &state.http_client,
+            &oauth_app,
+            std::time::Duration::from_secs(OAUTH_CONSENT_TIMEOUT_SECS),
+        )
+        .await?;
+
+    let resources = oauth::accessible_resources(&state.http_client, &tokens.access_token)
+        .await
+        .map_err(|e| e.to_string())?;
+    let wanted = site_url.map(|u| u.trim_end_matches('/').to_string());
+    let site = resources
+        .into_iter()
+        .find(|r| wanted.as_deref().is_none_or(|w| r.url.trim_end_matches('/') == w))
+        .ok_or("The authorized account has no access to that Jira site")?;
+
+    state
+        .oauth_tokens
+        .set(Some(tokens))
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // The app carries the client secret, so it lives with the tokens
+    let app_json = serde_json::to_string(&oauth_app).map_err(|e| e.to_string())?;
+    state
+        .secrets
+        .set("jira_oauth_app", &app_json)
+        .map_err(|e| e.to_string())?;
+    let pairs = [
+        ("jira_auth_method", "oauth"),
+        ("jira_base_url", site.url.as_str()),
+        ("jira_flavor", JiraFlavor::Cloud.as_str()),
+        ("jira_oauth_cloud_id", site.id.as_str()),
+    ];
+    for (key, value) in pairs {
+        sqlx::query(
+            "INSERT INTO settings (key, value) VALUES (?1, ?2) ON CONFLICT(key) DO UPDATE SET value = ?2",
+        )
+        .bind(key)
+        .bind(value)
+        .execute(&state.db)
+        .await
+        .map_err(|e| e.to_string())?;
+    }
+
+    *state.jira_config.lock().unwrap_or_else(|e| e.into_inner()) = Some(JiraConfig {
+        base_url: site.url,
+        flavor: JiraFlavor::Cloud,
+        email: String::new(),
+        api_token: String::new(),
+        oauth: Some(OAuthConnection {
+            app: oauth_app,
+            cloud_id: site.id,
+        }),
+    });
+    *state
+        .cached_account_id
+        .lock()
+        .unwrap_or_else(|e| e.into_inner()) = None;
+
+    let client = get_client(&state)?;
+    client.get_myself().await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn jira_oauth_disconnect(state: State<'_, AppState>) -> Result<(), String> {
+    state
+        .oauth_tokens
+        .set(None)
+        .await
+        .map_err(|e| e.to_string())?;
+    state
+        .secrets
+        .delete("jira_oauth_app")
+        .map_err(|e| e.to_string())?;
+    sqlx::query(
+        "DELETE FROM settings WHERE key IN ('jira_auth_method', 'jira_oauth_cloud_id')",
+    )
+    .execute(&state.db)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let mut config = state.jira_config.lock().unwrap_or_else(|e| e.into_inner());
+    if config.as_ref().is_some_and(|c| c.oauth.is_some()) {
+        *config = None;
+    }
+    Ok(())
 }
 
 #[tauri::command]
@@ -44,7 +216,7 @@ pub async fn jira_get_myself(
     state: State<'_, AppState>,
 ) -> Result<JiraUser, String> {
     let client = get_client(&state)?;
-    client.get_myself().await
+    client.get_myself().await.map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -55,25 +227,14 @@ pub async fn jira_search_issues(
 ) -> Result<Vec<JiraIssue>, String> {
     let client = get_client(&state)?;
     let issues = client
-        .search_issues(&jql, max_results.unwrap_or(50))
-        .await?;
+        .search_all_issues(&jql, max_results.unwrap_or(50) as usize)
+        .await
+        .map_err(|e| e.to_string())?
+        .issues;
 
     // Cache issues locally
     for issue in &issues {
-        if let Err(e) = sqlx::query(
-            "INSERT INTO issues (issue_key, summary, project_key, status, issue_type, updated_at) \
-             VALUES (?1, ?2, ?3, ?4, ?5, datetime('now')) \
-             ON CONFLICT(issue_key) DO UPDATE SET \
-             summary = ?2, project_key = ?3, status = ?4, issue_type = ?5, updated_at = datetime('now')",
-        )
-        .bind(&issue.issue_key)
-        .bind(&issue.summary)
-        .bind(&issue.project_key)
-        .bind(&issue.status)
-        .bind(&issue.issue_type)
-        .execute(&state.db)
-        .await
-        {
+        if let Err(e) = cache_issue(&state.db, issue).await {
             eprintln!("Failed to cache issue {}: {}", issue.issue_key, e);
         }
     }
@@ -81,7 +242,7 @@ pub async fn jira_search_issues(
     Ok(issues)
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct PushSummary {
     pub total: u32,
     pub success: u32,
@@ -96,123 +257,269 @@ pub async fn jira_push_worklog(
 ) -> Result<(), String> {
     let client = get_client(&state)?;
 
-    let row: Option<(String, String, i64, String, String)> = sqlx::query_as(
-        "SELECT issue_key, started_at, duration_seconds, description, sync_status FROM worklogs WHERE id = ?1",
-    )
+    let sync_status: Option<String> =
+        sqlx::query_scalar("SELECT sync_status FROM worklogs WHERE id = ?1")
+            .bind(worklog_id)
+            .fetch_optional(&state.db)
+            .await
+            .map_err(|e| e.to_string())?;
+    if sync_status.ok_or("Worklog not found")? == "synced" {
+        return Err("Worklog already synced".to_string());
+    }
+
+    let wl: UnsyncedWorklog = sqlx::query_as(&format!(
+        "SELECT {} FROM worklogs WHERE id = ?1",
+        UNSYNCED_COLUMNS
+    ))
     .bind(worklog_id)
-    .fetch_optional(&state.db)
+    .fetch_one(&state.db)
     .await
     .map_err(|e| e.to_string())?;
 
-    let (issue_key, started_at, duration, description, sync_status) =
-        row.ok_or("Worklog not found")?;
+    sync::push_unsynced_worklog(&state.db, &client, &wl)
+        .await
+        .map_err(|e| e.to_string())
+}
 
-    if sync_status == "synced" {
-        return Err("Worklog already synced".to_string());
+#[tauri::command]
+pub async fn jira_push_all_pending(
+    state: State<'_, AppState>,
+    date: String,
+) -> Result<PushSummary, String> {
+    let report = jira_push_pending(state, Some(date.clone()), Some(date), false).await?;
+    Ok(report.summary.unwrap_or_default())
+}
+
+/// One pending worklog exactly as it would be sent to Jira, and whatever would
+/// stop it from being pushed.
+#[derive(Debug, Serialize)]
+pub struct PushPreviewItem {
+    pub worklog_id: i64,
+    pub issue_key: String,
+    /// Start time in Jira's format
+    pub started: String,
+    pub duration_seconds: i64,
+    /// The comment as sent: ADF on Cloud, plain text on Data Center
+    pub comment: Option<serde_json::Value>,
+    pub problems: Vec<String>,
+    /// A worklog already in Jira on the same issue, by us, with the same start and
+    /// duration. Only checked in a dry run; resolve it with `jira_link_worklog`.
+    pub duplicate_of: Option<String>,
+    /// The duplicate carries this worklog's local id: an earlier push got through
+    pub from_earlier_push: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PushReport {
+    pub dry_run: bool,
+    pub items: Vec<PushPreviewItem>,
+    /// What happened when pushing; `None` for a dry run
+    pub summary: Option<PushSummary>,
+}
+
+/// Push pending worklogs whose local date is in `date_from..=date_to`; a missing
+/// bound leaves that side open, so no dates pushes everything pending. Each
+/// worklog is validated first and only sent if nothing is wrong with it. Only a
+/// dry run looks the issues up in Jira, to flag missing issues and duplicates.
+#[tauri::command]
+pub async fn jira_push_pending(
+    state: State<'_, AppState>,
+    date_from: Option<String>,
+    date_to: Option<String>,
+    dry_run: bool,
+) -> Result<PushReport, String> {
+    let parse = |date: &Option<String>| {
+        date.as_deref()
+            .map(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").map_err(|e| format!("Invalid date '{}': {}", d, e)))
+            .transpose()
+    };
+    let (first, last) = (parse(&date_from)?, parse(&date_to)?);
+    if let (Some(first), Some(last)) = (first, last) {
+        if last < first {
+            return Err("The end date is before the start date".to_string());
+        }
     }
 
-    // Jira expects: "2021-01-17T12:34:00.000+0000"
-    let started_jira = format_for_jira(&started_at)?;
+    let client = get_client(&state)?;
+    let my_account_id = current_account_id(&state, &client).await?;
+    push_pending(&state.db, &client, &my_account_id, first, last, dry_run).await
+}
 
-    match client
-        .add_worklog(&issue_key, duration, &started_jira, &description)
-        .await
-    {
-        Ok(resp) => {
-            sqlx::query(
-                "UPDATE worklogs SET sync_status = 'synced', jira_worklog_id = ?1, sync_error = NULL, updated_at = datetime('now') WHERE id = ?2",
-            )
-            .bind(&resp.id)
-            .bind(worklog_id)
-            .execute(&state.db)
-            .await
-            .map_err(|e| e.to_string())?;
-            Ok(())
+async fn push_pending(
+    db: &SqlitePool,
+    client: &JiraClient,
+    my_account_id: &str,
+    first: Option<NaiveDate>,
+    last: Option<NaiveDate>,
+    dry_run: bool,
+) -> Result<PushReport, String> {
+    let mut rows = pending_in_range(db, first, last).await?;
+    // Preview the durations the push will really send
+    let policy = rounding::load(db).await.map_err(|e| e.to_string())?;
+    for wl in rows.iter_mut() {
+        wl.duration_seconds = sync::push_duration(&policy, wl.duration_seconds, wl.raw_duration_seconds);
+    }
+    let items = plan_push(client, my_account_id, &rows, dry_run).await;
+    if dry_run {
+        return Ok(PushReport { dry_run, items, summary: None });
+    }
+
+    let mut summary = PushSummary {
+        total: items.len() as u32,
+        ..Default::default()
+    };
+    // Pacing and 429 handling live in the client's shared rate limiter
+    for (wl, item) in rows.iter().zip(&items) {
+        if !item.problems.is_empty() {
+            summary.errors.push(format!("{}: {}", wl.issue_key, item.problems.join("; ")));
+            continue;
         }
-        Err(e) => {
-            sqlx::query(
-                "UPDATE worklogs SET sync_status = 'error', sync_error = ?1, updated_at = datetime('now') WHERE id = ?2",
-            )
-            .bind(&e)
-            .bind(worklog_id)
-            .execute(&state.db)
-            .await
-            .map_err(|e| e.to_string())?;
-            Err(e)
+        match sync::push_unsynced_worklog(db, client, wl).await {
+            Ok(()) => summary.success += 1,
+            Err(e) => summary.errors.push(format!("{}: {}", wl.issue_key, e)),
         }
     }
+    summary.failed = summary.total - summary.success;
+
+    Ok(PushReport {
+        dry_run,
+        items,
+        summary: Some(summary),
+    })
 }
 
-#[tauri::command]
-pub async fn jira_push_all_pending(
-    state: State<'_, AppState>,
-    date: String,
-) -> Result<PushSummary, String> {
-    let push_date_from = format!("{}T00:00:00", date);
-    let push_date_to = format!("{}T23:59:59", date);
-    let rows: Vec<(i64, String, String, i64, String)> = sqlx::query_as(
-        "SELECT id, issue_key, started_at, duration_seconds, description \
-         FROM worklogs WHERE sync_status = 'pending' AND started_at >= ?1 AND started_at <= ?2",
-    )
-    .bind(&push_date_from)
-    .bind(&push_date_to)
-    .fetch_all(&state.db)
+/// Pending worklogs by local date. `started_at` carries its own offset, so the
+/// range is checked on the parsed instant rather than by comparing strings.
+async fn pending_in_range(
+    db: &SqlitePool,
+    first: Option<NaiveDate>,
+    last: Option<NaiveDate>,
+) -> Result<Vec<UnsyncedWorklog>, String> {
+    let rows: Vec<UnsyncedWorklog> = sqlx::query_as(&format!(
+        "SELECT {} FROM worklogs WHERE sync_status = 'pending' ORDER BY started_at",
+        UNSYNCED_COLUMNS
+    ))
+    .fetch_all(db)
     .await
     .map_err(|e| e.to_string())?;
+    if first.is_none() && last.is_none() {
+        return Ok(rows);
+    }
 
-    let total = rows.len() as u32;
-    let mut success = 0u32;
-    let mut errors = Vec::new();
+    let mut in_range: Vec<(DateTime<Local>, UnsyncedWorklog)> = rows
+        .into_iter()
+        .filter_map(|wl| {
+            let started = DateTime::parse_from_rfc3339(&wl.started_at).ok()?.with_timezone(&Local);
+            let date = started.date_naive();
+            let after_first = first.is_none_or(|f| date >= f);
+            let before_last = last.is_none_or(|l| date <= l);
+            (after_first && before_last).then_some((started, wl))
+        })
+        .collect();
+    in_range.sort_by_key(|(started, _)| *started);
+    Ok(in_range.into_iter().map(|(_, wl)| wl).collect())
+}
 
-    let client = get_client(&state)?;
+/// Build what would be sent for each row and check it. With `check_remote` it is
+/// also checked against Jira: the issue must exist, and a matching worklog must
+/// not be there already. Issues and their worklogs are fetched once per issue, and
+/// a failed lookup only becomes a problem of that issue's rows.
+async fn plan_push(
+    client: &JiraClient,
+    my_account_id: &str,
+    rows: &[UnsyncedWorklog],
+    check_remote: bool,
+) -> Vec<PushPreviewItem> {
+    let now = chrono::Utc::now();
+    let mut earliest: HashMap<&str, i64> = HashMap::new();
+    for wl in rows.iter().filter(|_| check_remote) {
+        if let Ok(started) = DateTime::parse_from_rfc3339(&wl.started_at) {
+            let ms = started.timestamp_millis();
+            earliest
+                .entry(wl.issue_key.as_str())
+                .and_modify(|e| *e = (*e).min(ms))
+                .or_insert(ms);
+        }
+    }
 
-    for (i, (id, issue_key, started_at, duration, description)) in rows.into_iter().enumerate() {
-        // Rate limit: pause between requests to avoid Jira API throttling
-        if i > 0 {
-            tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+    // The issue's worklogs, or why they could not be checked
+    let mut remote: HashMap<&str, Result<Vec<JiraWorklogEntry>, String>> = HashMap::new();
+    for wl in rows.iter().filter(|_| check_remote) {
+        let key = wl.issue_key.as_str();
+        if remote.contains_key(key) {
+            continue;
         }
+        let worklogs = match client.get_issue(key).await {
+            Ok(_) => {
+                // Jira's startedAfter is exclusive
+                let after = earliest.get(key).map(|ms| ms - 1);
+                client
+                    .get_worklogs_with_properties(key, after)
+                    .await
+                    .map(|list| list.worklogs)
+                    .map_err(|e| format!("Could not check for duplicates in Jira: {}", e))
+            }
+            Err(JiraError::NotFound(_)) => Err(format!("Issue {} does not exist in Jira", key)),
+            Err(e) => Err(format!("Could not look up issue {} in Jira: {}", key, e)),
+        };
+        remote.insert(key, worklogs);
+    }
 
-        let started_jira = format_for_jira(&started_at)?;
+    let mut items = Vec::with_capacity(rows.len());
+    for wl in rows {
+        let mut problems = Vec::new();
+        let started = DateTime::parse_from_rfc3339(&wl.started_at);
+        match &started {
+            Ok(dt) if *dt > now => problems.push("Starts in the future".to_string()),
+            Ok(_) => {}
+            Err(_) => problems.push(format!("Invalid start time '{}'", wl.started_at)),
+        }
+        if wl.duration_seconds <= 0 {
+            problems.push("Duration must be greater than zero".to_string());
+        }
 
-        match client
-            .add_worklog(&issue_key, duration, &started_jira, &description)
-            .await
-        {
-            Ok(resp) => {
-                if let Err(db_err) = sqlx::query(
-                    "UPDATE worklogs SET sync_status = 'synced', jira_worklog_id = ?1, sync_error = NULL, updated_at = datetime('now') WHERE id = ?2",
-                )
-                .bind(&resp.id)
-                .bind(id)
-                .execute(&state.db)
-                .await
-                {
-                    eprintln!("Failed to update worklog {} after push: {}", id, db_err);
-                }
-                success += 1;
-            }
-            Err(e) => {
-                if let Err(db_err) = sqlx::query(
-                    "UPDATE worklogs SET sync_status = 'error', sync_error = ?1, updated_at = datetime('now') WHERE id = ?2",
-                )
-                .bind(&e)
-                .bind(id)
-                .execute(&state.db)
-                .await
-                {
-                    eprintln!("Failed to record sync error for worklog {}: {}", id, db_err);
+        let mut duplicate_of = None;
+        let mut from_earlier_push = false;
+        match remote.get(wl.issue_key.as_str()) {
+            Some(Err(e)) => problems.push(e.clone()),
+            Some(Ok(worklogs)) => {
+                let tagged = worklogs.iter().find(|w| {
+                    w.properties
+                        .iter()
+                        .any(|p| p.key == LOCAL_ID_PROPERTY && p.value["id"] == wl.local_uuid.as_str())
+                });
+                if let Some(w) = tagged {
+                    duplicate_of = Some(w.id.clone());
+                    from_earlier_push = true;
+                } else if let Ok(started) = &started {
+                    duplicate_of = worklogs
+                        .iter()
+                        .find(|w| {
+                            w.author.account_id == my_account_id
+                                && w.time_spent_seconds == wl.duration_seconds
+                                && DateTime::parse_from_str(&w.started, "%Y-%m-%dT%H:%M:%S%.3f%z")
+                                    .is_ok_and(|s| s == *started)
+                        })
+                        .map(|w| w.id.clone());
                 }
-                errors.push(format!("{}: {}", issue_key, e));
             }
+            None => {}
         }
-    }
 
-    Ok(PushSummary {
-        total,
-        success,
-        failed: total - success,
-        errors,
-    })
+        items.push(PushPreviewItem {
+            worklog_id: wl.id,
+            issue_key: wl.issue_key.clone(),
+            started: started
+                .map(|dt| dt.format("%Y-%m-%dT%H:%M:%S%.3f%z").to_string())
+                .unwrap_or_default(),
+            duration_seconds: wl.duration_seconds,
+            comment: client.worklog_comment(&wl.description),
+            problems,
+            duplicate_of,
+            from_earlier_push,
+        });
+    }
+    items
 }
 
 #[tauri::command]
@@ -225,48 +532,111 @@ pub async fn jira_update_worklog(
 ) -> Result<(), String> {
     let client = get_client(&state)?;
 
-    let row: Option<(String, Option<String>, String, i64, String, String)> = sqlx::query_as(
-        "SELECT issue_key, jira_worklog_id, sync_status, duration_seconds, description, started_at FROM worklogs WHERE id = ?1",
+    let row: Option<(String, Option<String>, String)> = sqlx::query_as(
+        "SELECT issue_key, jira_worklog_id, sync_status FROM worklogs WHERE id = ?1",
     )
     .bind(worklog_id)
     .fetch_optional(&state.db)
     .await
     .map_err(|e| e.to_string())?;
 
-    let (issue_key, jira_wl_id, sync_status, cur_duration, cur_description, cur_started_at) =
-        row.ok_or("Worklog not found")?;
+    let (issue_key, jira_wl_id, sync_status) = row.ok_or("Worklog not found")?;
 
-    if sync_status != "synced" {
+    if sync_status != "synced" && sync_status != "modified" {
         return Err("Worklog is not synced to Jira".to_string());
     }
 
     let jira_id = jira_wl_id.ok_or("Worklog has no Jira worklog ID")?;
 
-    let final_duration = duration_seconds.unwrap_or(cur_duration);
-    let final_description = description.unwrap_or(cur_description);
-    let final_started_at = started_at.unwrap_or(cur_started_at);
-
-    let started_jira = format_for_jira(&final_started_at)?;
-
-    // Push to Jira first — if it fails, nothing changes locally
-    let resp = client
-        .update_worklog(&issue_key, &jira_id, final_duration, &started_jira, &final_description)
-        .await?;
-
-    // Update locally on success
+    let rounded = match duration_seconds {
+        Some(raw) => Some(rounding::load(&state.db).await.map_err(|e| e.to_string())?.apply(raw)),
+        None => None,
+    };
+    // Keep the edit locally first, queued for the sync worker in case pushing it
+    // fails; pushing merges with anything changed in Jira since the last sync
+    let mut tx = state.db.begin().await.map_err(|e| e.to_string())?;
     sqlx::query(
-        "UPDATE worklogs SET duration_seconds = ?1, description = ?2, started_at = ?3, jira_worklog_id = ?4, jira_updated_at = datetime('now'), updated_at = datetime('now') WHERE id = ?5",
+        "UPDATE worklogs SET duration_seconds = COALESCE(?1, duration_seconds), \
+         raw_duration_seconds = COALESCE(?2, raw_duration_seconds), description = COALESCE(?3, description), \
+         started_at = COALESCE(?4, started_at), sync_status = 'modified', sync_error = NULL, updated_at = datetime('now') \
+         WHERE id = ?5",
     )
-    .bind(final_duration)
-    .bind(&final_description)
-    .bind(&final_started_at)
-    .bind(&resp.id)
+    .bind(rounded)
+    .bind(duration_seconds)
+    .bind(&description)
+    .bind(&started_at)
     .bind(worklog_id)
-    .execute(&state.db)
+    .execute(&mut *tx)
     .await
     .map_err(|e| e.to_string())?;
+    sync::enqueue(&mut *tx, worklog_id, SyncOperation::Update, &issue_key, Some(&jira_id))
+        .await
+        .map_err(|e| e.to_string())?;
+    tx.commit().await.map_err(|e| e.to_string())?;
 
-    Ok(())
+    let result = sync::push_update(&state.db, &client, worklog_id, Some(&jira_id)).await;
+    settle_queued(&state, worklog_id, result).await
+}
+
+/// Finish a command that pushed its queued edit or delete right away. Like the
+/// worker, the outbox entry stays for a transient failure and goes otherwise.
+async fn settle_queued(state: &AppState, worklog_id: i64, result: Result<(), SyncError>) -> Result<(), String> {
+    match result {
+        // Jira unreachable right now: the sync worker replays it
+        Err(SyncError::Jira(e)) if e.is_transient() => {
+            state.sync.wake();
+            Ok(())
+        }
+        result => {
+            sync::remove_entry(&state.db, worklog_id).await.map_err(|e| e.to_string())?;
+            result.map_err(|e| e.to_string())
+        }
+    }
+}
+
+/// Resolve a pending worklog that a push preview found already in Jira, either as
+/// `duplicate_of` or from an earlier push, by linking it to that Jira worklog
+/// instead of creating another.
+#[tauri::command]
+pub async fn jira_link_worklog(
+    state: State<'_, AppState>,
+    worklog_id: i64,
+    jira_worklog_id: String,
+) -> Result<(), String> {
+    let client = get_client(&state)?;
+    let my_account_id = current_account_id(&state, &client).await?;
+    link_pending_worklog(&state.db, &client, &my_account_id, worklog_id, &jira_worklog_id).await
+}
+
+async fn link_pending_worklog(
+    db: &SqlitePool,
+    client: &JiraClient,
+    my_account_id: &str,
+    worklog_id: i64,
+    jira_worklog_id: &str,
+) -> Result<(), String> {
+    let row: Option<(String, String)> =
+        sqlx::query_as("SELECT issue_key, sync_status FROM worklogs WHERE id = ?1")
+            .bind(worklog_id)
+            .fetch_optional(db)
+            .await
+            .map_err(|e| e.to_string())?;
+    let (issue_key, sync_status) = row.ok_or("Worklog not found")?;
+    if sync_status != "pending" {
+        return Err("Only pending worklogs can be linked to a Jira worklog".to_string());
+    }
+
+    let entry = client
+        .get_worklog(&issue_key, jira_worklog_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    if entry.author.account_id != my_account_id {
+        return Err(format!("Jira worklog {} was logged by someone else", jira_worklog_id));
+    }
+    let started = DateTime::parse_from_str(&entry.started, "%Y-%m-%dT%H:%M:%S%.3f%z")
+        .map_err(|_| format!("Jira worklog {} has an unrecognised start time: {}", entry.id, entry.started))?;
+    let started_rfc3339 = started.with_timezone(&Local).to_rfc3339();
+    link_worklog(db, worklog_id, &issue_key, &entry, &started_rfc3339, "Linked to a worklog already in Jira").await
 }
 
 #[tauri::command]
@@ -284,22 +654,27 @@ pub async fn jira_delete_worklog(
 
     let (issue_key, jira_worklog_id, sync_status) = row.ok_or("Worklog not found")?;
 
-    if sync_status != "synced" {
+    if sync_status != "synced" && sync_status != "modified" {
         return Err("Worklog is not synced to Jira".to_string());
     }
 
     let jira_id = jira_worklog_id.ok_or("Worklog has no Jira worklog ID")?;
 
     let client = get_client(&state)?;
-    client.delete_worklog(&issue_key, &jira_id).await?;
-
-    sqlx::query("DELETE FROM worklogs WHERE id = ?1")
+    // Kept as `pending_delete` until Jira has confirmed the delete
+    let mut tx = state.db.begin().await.map_err(|e| e.to_string())?;
+    sqlx::query("UPDATE worklogs SET sync_status = 'pending_delete', updated_at = datetime('now') WHERE id = ?1")
         .bind(worklog_id)
-        .execute(&state.db)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    sync::enqueue(&mut *tx, worklog_id, SyncOperation::Delete, &issue_key, Some(&jira_id))
         .await
         .map_err(|e| e.to_string())?;
+    tx.commit().await.map_err(|e| e.to_string())?;
 
-    Ok(())
+    let result = sync::push_delete(&state.db, &client, worklog_id, &issue_key, &jira_id).await;
+    settle_queued(&state, worklog_id, result).await
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -308,100 +683,427 @@ pub struct ImportSummary {
     pub updated: u32,
     pub deleted: u32,
     pub skipped: u32,
+    /// Worklogs changed both here and in Jira, left for the user to resolve
+    pub conflicts: u32,
     pub issues_checked: u32,
     pub warnings: Vec<String>,
+    /// The same counts per local date: every date of a range import, or only
+    /// the dates an incremental import touched
+    pub days: Vec<DayImportSummary>,
 }
 
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct DayImportSummary {
+    pub date: String,
+    pub imported: u32,
+    pub updated: u32,
+    pub deleted: u32,
+    pub skipped: u32,
+    pub conflicts: u32,
+}
+
+/// Longest range a single import may cover.
+const MAX_IMPORT_DAYS: i64 = 92;
+
 #[tauri::command]
 pub async fn jira_import_worklogs(
     state: State<'_, AppState>,
-    date: String,
+    date_from: String,
+    date_to: String,
 ) -> Result<ImportSummary, String> {
     let client = get_client(&state)?;
+    let my_account_id = current_account_id(&state, &client).await?;
 
-    let my_account_id = {
-        let cached = state
-            .cached_account_id
-            .lock()
-            .unwrap_or_else(|e| e.into_inner())
-            .clone();
-        if let Some(id) = cached {
-            id
-        } else {
-            let user = client.get_myself().await?;
-            let id = user.account_id;
-            *state
-                .cached_account_id
-                .lock()
-                .unwrap_or_else(|e| e.into_inner()) = Some(id.clone());
-            id
+    let parse = |date: &str| {
+        NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|e| format!("Invalid date '{}': {}", date, e))
+    };
+    let first = parse(&date_from)?;
+    let last = parse(&date_to)?;
+    if last < first {
+        return Err("The end date is before the start date".to_string());
+    }
+    if (last - first).num_days() >= MAX_IMPORT_DAYS {
+        return Err(format!("Import at most {} days at a time", MAX_IMPORT_DAYS));
+    }
+
+    import_worklogs_for_range(&state.db, &client, &my_account_id, first, last)
+        .await
+        .inspect(|_| tray_menu::invalidate(&state))
+}
+
+async fn current_account_id(state: &AppState, client: &JiraClient) -> Result<String, String> {
+    let cached = state
+        .cached_account_id
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone();
+    if let Some(id) = cached {
+        return Ok(id);
+    }
+    let user = client.get_myself().await.map_err(|e| e.to_string())?;
+    *state
+        .cached_account_id
+        .lock()
+        .unwrap_or_else(|e| e.into_inner()) = Some(user.account_id.clone());
+    Ok(user.account_id)
+}
+
+/// Settings keys holding the `since` watermark of each Jira change feed.
+const WORKLOG_UPDATED_SINCE_KEY: &str = "jira_worklog_updated_since";
+const WORKLOG_DELETED_SINCE_KEY: &str = "jira_worklog_deleted_since";
+/// How far back the first incremental import looks; older worklogs come from a range import.
+const INCREMENTAL_FIRST_RUN_DAYS: i64 = 30;
+/// Worklogs fetched in full per incremental import; the rest wait for the next one.
+const MAX_FEED_WORKLOGS: usize = 5000;
+
+/// Pull only the worklogs that changed in Jira since the last call, using the
+/// `/worklog/updated` and `/worklog/deleted` feeds instead of a JQL scan. The
+/// first call has nothing to continue from and runs a range import instead.
+#[tauri::command]
+pub async fn jira_import_worklog_changes(state: State<'_, AppState>) -> Result<ImportSummary, String> {
+    let client = get_client(&state)?;
+    let my_account_id = current_account_id(&state, &client).await?;
+    import_worklog_changes(&state.db, &client, &my_account_id)
+        .await
+        .inspect(|_| tray_menu::invalidate(&state))
+}
+
+async fn import_worklog_changes(
+    db: &SqlitePool,
+    client: &JiraClient,
+    my_account_id: &str,
+) -> Result<ImportSummary, String> {
+    let Some(updated_since) = load_watermark(db, WORKLOG_UPDATED_SINCE_KEY).await? else {
+        return import_first_run(db, client, my_account_id).await;
+    };
+    let deleted_since = load_watermark(db, WORKLOG_DELETED_SINCE_KEY).await?.unwrap_or(updated_since);
+
+    let mut days: std::collections::BTreeMap<NaiveDate, DayImportSummary> = std::collections::BTreeMap::new();
+    let mut warnings: Vec<String> = Vec::new();
+    let mut issue_keys: HashMap<String, String> = HashMap::new();
+
+    // The feed lists everyone's worklogs without their author, so every listed
+    // worklog has to be fetched to find ours; a busy site is worked off in batches
+    let changed = client
+        .worklogs_updated_since(updated_since)
+        .await
+        .map_err(|e| e.to_string())?;
+    let mut updated_until = changed.until;
+    let mut batch = changed.values.as_slice();
+    if batch.len() > MAX_FEED_WORKLOGS {
+        batch = &batch[..MAX_FEED_WORKLOGS];
+        // Resume just before the last one fetched, in case others share its time
+        let last = batch[MAX_FEED_WORKLOGS - 1].updated_time;
+        updated_until = if last - 1 > updated_since { last - 1 } else { last };
+        warnings.push(format!(
+            "{} worklogs changed in Jira; the first {} were checked and the rest follow on the next import",
+            changed.values.len(),
+            MAX_FEED_WORKLOGS
+        ));
+    }
+    let ids: Vec<i64> = batch.iter().map(|c| c.worklog_id).collect();
+    let entries = client.get_worklogs_by_ids(&ids).await.map_err(|e| e.to_string())?;
+
+    // Anything skipped on error stays behind the watermark and is retried next time
+    let mut complete = true;
+    for entry in entries.iter().filter(|e| e.author.account_id == my_account_id) {
+        let Ok(parsed) = DateTime::parse_from_str(&entry.started, "%Y-%m-%dT%H:%M:%S%.3f%z") else {
+            warnings.push(format!("Worklog {} has an unrecognised start time: {}", entry.id, entry.started));
+            continue;
+        };
+        let issue_key = match resolve_issue_key(db, client, &mut issue_keys, entry).await {
+            Ok(key) => key,
+            Err(e) => {
+                warnings.push(format!("Failed to look up the issue of worklog {}: {}", entry.id, e));
+                complete = false;
+                continue;
+            }
+        };
+
+        let existing: Option<(i64, Option<String>, String)> = sqlx::query_as(
+            "SELECT id, jira_updated_at, sync_status FROM worklogs WHERE jira_worklog_id = ?1",
+        )
+        .bind(&entry.id)
+        .fetch_optional(db)
+        .await
+        .map_err(|e| e.to_string())?;
+        let started_rfc3339 = parsed.with_timezone(&Local).to_rfc3339();
+        let day = days
+            .entry(parsed.with_timezone(&Local).date_naive())
+            .or_insert_with_key(|d| DayImportSummary::new(*d));
+
+        if existing.is_none() && link_pushed_worklog(db, entry, &started_rfc3339).await? {
+            day.record(Applied::Updated);
+            continue;
         }
+        let applied = apply_remote_worklog(db, existing.as_ref(), &issue_key, entry, started_rfc3339).await?;
+        day.record(applied);
+    }
+    if complete {
+        save_watermark(db, WORKLOG_UPDATED_SINCE_KEY, updated_until).await?;
+    }
+
+    let deleted = client
+        .worklogs_deleted_since(deleted_since)
+        .await
+        .map_err(|e| e.to_string())?;
+    for change in &deleted.values {
+        let jira_id = change.worklog_id.to_string();
+        let local: Option<(i64, String, String, String)> = sqlx::query_as(
+            "SELECT id, issue_key, started_at, sync_status FROM worklogs WHERE jira_worklog_id = ?1",
+        )
+        .bind(&jira_id)
+        .fetch_optional(db)
+        .await
+        .map_err(|e| e.to_string())?;
+        let Some((id, issue_key, started_at, sync_status)) = local else {
+            continue;
+        };
+        let target = (id, issue_key.as_str(), jira_id.as_str());
+        let applied = apply_remote_delete(db, target, &sync_status, "Listed in Jira's deleted worklog feed").await?;
+        if let Ok(dt) = DateTime::parse_from_rfc3339(&started_at) {
+            days.entry(dt.with_timezone(&Local).date_naive())
+                .or_insert_with_key(|d| DayImportSummary::new(*d))
+                .record(applied);
+        }
+    }
+    save_watermark(db, WORKLOG_DELETED_SINCE_KEY, deleted.until).await?;
+
+    let issues_checked = issue_keys.len() as u32;
+    Ok(ImportSummary::from_days(days.into_values().collect(), issues_checked, warnings))
+}
+
+/// Without a watermark the feeds would list the whole site's worklogs, so the
+/// first import covers the last days with a range import of our own worklogs and
+/// starts both feeds from when it began.
+async fn import_first_run(db: &SqlitePool, client: &JiraClient, my_account_id: &str) -> Result<ImportSummary, String> {
+    let started = chrono::Utc::now();
+    let last = started.with_timezone(&Local).date_naive();
+    let first = last - chrono::Duration::days(INCREMENTAL_FIRST_RUN_DAYS);
+    let summary = import_worklogs_for_range(db, client, my_account_id, first, last).await?;
+    // Only a clean import may be built on
+    if summary.warnings.is_empty() {
+        save_watermark(db, WORKLOG_UPDATED_SINCE_KEY, started.timestamp_millis()).await?;
+        save_watermark(db, WORKLOG_DELETED_SINCE_KEY, started.timestamp_millis()).await?;
+    }
+    Ok(summary)
+}
+
+async fn load_watermark(db: &SqlitePool, key: &str) -> Result<Option<i64>, String> {
+    let value: Option<String> = sqlx::query_scalar("SELECT value FROM settings WHERE key = ?1")
+        .bind(key)
+        .fetch_optional(db)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(value.and_then(|v| v.parse().ok()))
+}
+
+async fn save_watermark(db: &SqlitePool, key: &str, since: i64) -> Result<(), String> {
+    sqlx::query(
+        "INSERT INTO settings (key, value) VALUES (?1, ?2) ON CONFLICT(key) DO UPDATE SET value = ?2",
+    )
+    .bind(key)
+    .bind(since.to_string())
+    .execute(db)
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Store or refresh an issue in the local cache, so worklogs can show its summary.
+async fn cache_issue(db: &SqlitePool, issue: &JiraIssue) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO issues (issue_key, summary, project_key, status, issue_type, updated_at) \
+         VALUES (?1, ?2, ?3, ?4, ?5, datetime('now')) \
+         ON CONFLICT(issue_key) DO UPDATE SET \
+         summary = ?2, project_key = ?3, status = ?4, issue_type = ?5, updated_at = datetime('now')",
+    )
+    .bind(&issue.issue_key)
+    .bind(&issue.summary)
+    .bind(&issue.project_key)
+    .bind(&issue.status)
+    .bind(&issue.issue_type)
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// `/worklog/list` only gives the issue id. Known ids are cached per run; new
+/// issues are fetched once and stored so the worklog list can show their summary.
+async fn resolve_issue_key(
+    db: &SqlitePool,
+    client: &JiraClient,
+    cache: &mut HashMap<String, String>,
+    entry: &JiraWorklogEntry,
+) -> Result<String, String> {
+    let issue_id = entry.issue_id.as_deref().ok_or("Jira did not say which issue it belongs to")?;
+    if let Some(key) = cache.get(issue_id) {
+        return Ok(key.clone());
+    }
+    let issue = client.get_issue(issue_id).await.map_err(|e| e.to_string())?;
+    cache_issue(db, &issue).await.map_err(|e| e.to_string())?;
+    cache.insert(issue_id.to_string(), issue.issue_key.clone());
+    Ok(issue.issue_key)
+}
+
+/// A worklog we created whose response never arrived carries our local UUID.
+/// Link it to its pending row instead of importing a duplicate; local edits made
+/// since are kept and pushed as an update.
+async fn link_pushed_worklog(
+    db: &SqlitePool,
+    entry: &JiraWorklogEntry,
+    started_rfc3339: &str,
+) -> Result<bool, String> {
+    let Some(local_id) = entry
+        .properties
+        .iter()
+        .find(|p| p.key == LOCAL_ID_PROPERTY)
+        .and_then(|p| p.value["id"].as_str())
+    else {
+        return Ok(false);
+    };
+    let row: Option<(i64, String)> = sqlx::query_as(
+        "SELECT id, issue_key FROM worklogs WHERE local_uuid = ?1 AND jira_worklog_id IS NULL",
+    )
+    .bind(local_id)
+    .fetch_optional(db)
+    .await
+    .map_err(|e| e.to_string())?;
+    let Some((id, issue_key)) = row else {
+        return Ok(false);
     };
+    link_worklog(db, id, &issue_key, entry, started_rfc3339, "Found in Jira tagged with this worklog's local id").await?;
+    Ok(true)
+}
 
-    let target_date = NaiveDate::parse_from_str(&date, "%Y-%m-%d")
-        .map_err(|e| format!("Invalid date '{}': {}", date, e))?;
+/// Point local row `id` at the Jira worklog `entry`, taking Jira's version as the
+/// base so local edits made since are pushed as an update.
+pub(crate) async fn link_worklog(
+    db: &SqlitePool,
+    id: i64,
+    issue_key: &str,
+    entry: &JiraWorklogEntry,
+    started_rfc3339: &str,
+    reason: &str,
+) -> Result<(), String> {
+    let description = entry.comment.as_ref().map(comment_text).unwrap_or_default();
+    sqlx::query(
+        "UPDATE worklogs SET jira_worklog_id = ?1, jira_updated_at = ?2, push_attempted_at = NULL, sync_error = NULL, \
+         base_started_at = ?3, base_duration_seconds = ?4, base_description = ?5, \
+         sync_status = 'modified', updated_at = datetime('now') WHERE id = ?6",
+    )
+    .bind(&entry.id)
+    .bind(&entry.updated)
+    .bind(started_rfc3339)
+    .bind(entry.time_spent_seconds)
+    .bind(&description)
+    .bind(id)
+    .execute(db)
+    .await
+    .map_err(|e| e.to_string())?;
+    // Nothing to push when the row still matches what was sent
+    let row = conflicts::load_row(db, id).await.map_err(|e| e.to_string())?;
+    if let Some(row) = row.filter(|r| r.base().is_some_and(|base| base.same_as(&r.local()))) {
+        conflicts::apply_synced(db, id, &row.local(), Some(&entry.updated))
+            .await
+            .map_err(|e| e.to_string())?;
+    } else {
+        sync::enqueue(db, id, SyncOperation::Update, issue_key, Some(&entry.id))
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    SyncEvent::new(EventOperation::Link, id)
+        .issue(issue_key)
+        .jira_id(Some(&entry.id))
+        .request(reason)
+        .succeeded(db)
+        .await
+        .map_err(|e| e.to_string())
+}
 
+/// Import the current user's worklogs for the local dates `first..=last`: insert
+/// new ones, update ones changed in Jira, and delete synced rows that no longer
+/// exist in Jira. One search and one worklog fetch per issue covers the whole range.
+async fn import_worklogs_for_range(
+    db: &SqlitePool,
+    client: &JiraClient,
+    my_account_id: &str,
+    first: NaiveDate,
+    last: NaiveDate,
+) -> Result<ImportSummary, String> {
     // Search ±1 day to cover timezone differences between Jira account TZ and local TZ.
     // JQL worklogDate uses the Jira account timezone, which may differ from the user's
     // system timezone. Precise local-date filtering happens later in Rust.
-    let day_before = target_date - chrono::Duration::days(1);
-    let day_after = target_date + chrono::Duration::days(1);
+    let day_before = first - chrono::Duration::days(1);
+    let day_after = last + chrono::Duration::days(1);
     let jql = format!(
         "worklogDate >= \"{}\" AND worklogDate <= \"{}\" AND worklogAuthor = currentUser()",
         day_before, day_after
     );
     let mut warnings: Vec<String> = Vec::new();
 
-    let jql_result = client.search_issues(&jql, 50).await;
+    let jql_result = client
+        .search_all_issues(&jql, MAX_WORKLOG_SEARCH_ISSUES)
+        .await;
     let jql_succeeded = jql_result.is_ok();
-    if let Err(ref e) = jql_result {
-        warnings.push(format!("JQL search failed: {}", e));
+    let search = match jql_result {
+        Ok(search) => search,
+        Err(e) => {
+            warnings.push(format!("JQL search failed: {}", e));
+            Default::default()
+        }
+    };
+    // A truncated search means some issues were never fetched, so their local
+    // worklogs must not be treated as deleted in Jira.
+    let search_complete = !search.truncated;
+    if search.truncated {
+        warnings.push(format!(
+            "More than {} issues have worklogs in this range; only the first {} were imported and deletion detection was skipped",
+            MAX_WORKLOG_SEARCH_ISSUES, MAX_WORKLOG_SEARCH_ISSUES
+        ));
     }
-    let issues = jql_result.unwrap_or_default();
+    let issues = search.issues;
 
     // Cache issue summaries so worklogs LEFT JOIN can resolve them
     for issue in &issues {
-        let _ = sqlx::query(
-            "INSERT INTO issues (issue_key, summary, project_key, status, issue_type, updated_at) \
-             VALUES (?1, ?2, ?3, ?4, ?5, datetime('now')) \
-             ON CONFLICT(issue_key) DO UPDATE SET \
-             summary = ?2, project_key = ?3, status = ?4, issue_type = ?5, updated_at = datetime('now')",
-        )
-        .bind(&issue.issue_key)
-        .bind(&issue.summary)
-        .bind(&issue.project_key)
-        .bind(&issue.status)
-        .bind(&issue.issue_type)
-        .execute(&state.db)
-        .await;
+        let _ = cache_issue(db, issue).await;
     }
 
     let issue_keys: Vec<String> = issues.into_iter().map(|i| i.issue_key).collect();
 
-    // Only fetch worklogs started after (target_date - 2 days) to reduce payload
+    // Only fetch worklogs started after the day before the range to reduce payload
     let started_after_epoch = day_before
         .and_hms_opt(0, 0, 0)
         .map(|dt| dt.and_utc().timestamp_millis());
 
-    // Load existing synced worklogs for the target date range (not ALL worklogs)
-    let date_from = format!("{}T00:00:00", day_before);
-    let date_to = format!("{}T23:59:59", day_after);
-    let existing_rows: Vec<(String, Option<String>)> = sqlx::query_as(
-        "SELECT jira_worklog_id, jira_updated_at FROM worklogs \
-         WHERE jira_worklog_id IS NOT NULL AND started_at >= ?1 AND started_at <= ?2",
+    // Load existing synced worklogs for the target date range (not ALL worklogs).
+    // `started_at` carries its own offset, so the padded range is compared as instants.
+    let range_from = day_before.and_hms_opt(0, 0, 0).map(|dt| dt.and_utc().timestamp());
+    let range_until = (day_after + chrono::Duration::days(1))
+        .and_hms_opt(0, 0, 0)
+        .map(|dt| dt.and_utc().timestamp());
+    let existing_rows: Vec<(String, i64, Option<String>, String)> = sqlx::query_as(
+        "SELECT jira_worklog_id, id, jira_updated_at, sync_status FROM worklogs \
+         WHERE jira_worklog_id IS NOT NULL \
+         AND CAST(strftime('%s', started_at) AS INTEGER) >= ?1 \
+         AND CAST(strftime('%s', started_at) AS INTEGER) < ?2",
     )
-    .bind(&date_from)
-    .bind(&date_to)
-    .fetch_all(&state.db)
+    .bind(range_from)
+    .bind(range_until)
+    .fetch_all(db)
     .await
     .map_err(|e| e.to_string())?;
 
-    let existing_map: std::collections::HashMap<String, Option<String>> =
-        existing_rows.into_iter().collect();
+    let existing_map: std::collections::HashMap<String, (i64, Option<String>, String)> = existing_rows
+        .into_iter()
+        .map(|(jira_id, id, jira_updated_at, status)| (jira_id, (id, jira_updated_at, status)))
+        .collect();
 
-    let mut imported = 0u32;
-    let mut updated = 0u32;
-    let mut skipped = 0u32;
+    let mut days: std::collections::BTreeMap<NaiveDate, DayImportSummary> = first
+        .iter_days()
+        .take_while(|d| *d <= last)
+        .map(|d| (d, DayImportSummary::new(d)))
+        .collect();
     let issues_checked = issue_keys.len() as u32;
     let mut seen_jira_ids: std::collections::HashSet<String> = std::collections::HashSet::new();
     let mut failed_issues: std::collections::HashSet<String> = std::collections::HashSet::new();
@@ -413,7 +1115,7 @@ pub async fn jira_import_worklogs(
         .map(|key| {
             let client = client.clone();
             async move {
-                let result = client.get_worklogs(&key, started_after_epoch).await;
+                let result = client.get_worklogs_with_properties(&key, started_after_epoch).await;
                 (key, result)
             }
         })
@@ -445,105 +1147,239 @@ pub async fn jira_import_worklogs(
                 Err(_) => continue,
             };
             let local_date = parsed.with_timezone(&Local).date_naive();
-            if local_date != target_date {
+            let Some(day) = days.get_mut(&local_date) else {
                 continue;
-            }
+            };
 
             seen_jira_ids.insert(entry.id.clone());
 
             let started_rfc3339 = parsed.with_timezone(&Local).to_rfc3339();
-            let description = entry
-                .comment
-                .as_ref()
-                .map(extract_adf_text)
-                .unwrap_or_default();
-
-            match existing_map.get(&entry.id) {
-                None => {
-                    // New worklog — insert (OR IGNORE to handle concurrent imports)
-                    sqlx::query(
-                        "INSERT OR IGNORE INTO worklogs (issue_key, started_at, duration_seconds, description, sync_status, jira_worklog_id, jira_updated_at) \
-                         VALUES (?1, ?2, ?3, ?4, 'synced', ?5, ?6)",
-                    )
-                    .bind(issue_key)
-                    .bind(&started_rfc3339)
-                    .bind(entry.time_spent_seconds)
-                    .bind(&description)
-                    .bind(&entry.id)
-                    .bind(&entry.updated)
-                    .execute(&state.db)
-                    .await
-                    .map_err(|e| e.to_string())?;
-                    imported += 1;
-                }
-                Some(local_updated) => {
-                    if local_updated.as_deref() != Some(&entry.updated) {
-                        // Changed in Jira — update locally
-                        sqlx::query(
-                            "UPDATE worklogs SET started_at = ?1, duration_seconds = ?2, description = ?3, jira_updated_at = ?4, updated_at = datetime('now') \
-                             WHERE jira_worklog_id = ?5",
-                        )
-                        .bind(&started_rfc3339)
-                        .bind(entry.time_spent_seconds)
-                        .bind(&description)
-                        .bind(&entry.updated)
-                        .bind(&entry.id)
-                        .execute(&state.db)
-                        .await
-                        .map_err(|e| e.to_string())?;
-                        updated += 1;
-                    } else {
-                        skipped += 1;
-                    }
-                }
+            let existing = existing_map.get(&entry.id);
+            // A push whose answer was lost left the row pending without a Jira id
+            if existing.is_none() && link_pushed_worklog(db, entry, &started_rfc3339).await? {
+                day.record(Applied::Updated);
+                continue;
             }
+            let applied = apply_remote_worklog(db, existing, issue_key, entry, started_rfc3339).await?;
+            day.record(applied);
         }
     }
 
     // Detect worklogs deleted from Jira.
-    // Skip deletion entirely if JQL search failed (can't trust results).
+    // Skip deletion entirely if JQL search failed or was truncated (can't trust results).
     // Skip worklogs from issues where get_worklogs API call failed.
-    // All other synced worklogs for target_date not seen in Jira → deleted in Jira.
-    let mut deleted = 0u32;
-    if jql_succeeded {
-        let local_synced: Vec<(i64, String, String, String)> = sqlx::query_as(
-            "SELECT id, issue_key, jira_worklog_id, started_at FROM worklogs \
-             WHERE sync_status = 'synced' AND jira_worklog_id IS NOT NULL \
-             AND started_at >= ?1 AND started_at <= ?2",
+    // All other synced worklogs in the range not seen in Jira → deleted in Jira.
+    if jql_succeeded && search_complete {
+        let local_synced: Vec<(i64, String, String, String, String)> = sqlx::query_as(
+            "SELECT id, issue_key, jira_worklog_id, started_at, sync_status FROM worklogs \
+             WHERE sync_status IN ('synced', 'modified', 'pending_delete') AND jira_worklog_id IS NOT NULL \
+             AND CAST(strftime('%s', started_at) AS INTEGER) >= ?1 \
+             AND CAST(strftime('%s', started_at) AS INTEGER) < ?2",
         )
-        .bind(&date_from)
-        .bind(&date_to)
-        .fetch_all(&state.db)
+        .bind(range_from)
+        .bind(range_until)
+        .fetch_all(db)
         .await
         .map_err(|e| e.to_string())?;
 
-        for (id, issue_key, jira_id, started_at) in local_synced {
+        for (id, issue_key, jira_id, started_at, sync_status) in local_synced {
             // Don't delete from issues where get_worklogs failed
             if failed_issues.contains(&issue_key) {
                 continue;
             }
             if let Ok(dt) = DateTime::parse_from_rfc3339(&started_at) {
                 let local_date = dt.with_timezone(&Local).date_naive();
-                if local_date == target_date && !seen_jira_ids.contains(&jira_id) {
-                    sqlx::query("DELETE FROM worklogs WHERE id = ?1")
-                        .bind(id)
-                        .execute(&state.db)
-                        .await
-                        .map_err(|e| e.to_string())?;
-                    deleted += 1;
+                let Some(day) = days.get_mut(&local_date) else {
+                    continue;
+                };
+                if !seen_jira_ids.contains(&jira_id) {
+                    let reason = format!("Missing from {}'s worklogs in a range import", issue_key);
+                    let target = (id, issue_key.as_str(), jira_id.as_str());
+                    day.record(apply_remote_delete(db, target, &sync_status, &reason).await?);
                 }
             }
         }
     }
 
-    Ok(ImportSummary {
-        imported,
-        updated,
-        deleted,
-        skipped,
-        issues_checked,
-        warnings,
-    })
+    Ok(ImportSummary::from_days(days.into_values().collect(), issues_checked, warnings))
+}
+
+/// What an import did with one worklog.
+enum Applied {
+    Imported,
+    Updated,
+    Deleted,
+    Skipped,
+    Conflict,
+}
+
+impl DayImportSummary {
+    fn new(date: NaiveDate) -> Self {
+        Self {
+            date: date.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn record(&mut self, applied: Applied) {
+        match applied {
+            Applied::Imported => self.imported += 1,
+            Applied::Updated => self.updated += 1,
+            Applied::Deleted => self.deleted += 1,
+            Applied::Skipped => self.skipped += 1,
+            Applied::Conflict => self.conflicts += 1,
+        }
+    }
+}
+
+impl ImportSummary {
+    fn from_days(days: Vec<DayImportSummary>, issues_checked: u32, warnings: Vec<String>) -> Self {
+        Self {
+            imported: days.iter().map(|d| d.imported).sum(),
+            updated: days.iter().map(|d| d.updated).sum(),
+            deleted: days.iter().map(|d| d.deleted).sum(),
+            skipped: days.iter().map(|d| d.skipped).sum(),
+            conflicts: days.iter().map(|d| d.conflicts).sum(),
+            issues_checked,
+            warnings,
+            days,
+        }
+    }
+}
+
+/// Bring one of the user's Jira worklogs into the local table. `existing` is the
+/// local row already holding it: `(id, jira_updated_at, sync_status)`.
+async fn apply_remote_worklog(
+    db: &SqlitePool,
+    existing: Option<&(i64, Option<String>, String)>,
+    issue_key: &str,
+    entry: &JiraWorklogEntry,
+    started_rfc3339: String,
+) -> Result<Applied, String> {
+    let description = entry
+        .comment
+        .as_ref()
+        .map(comment_text)
+        .unwrap_or_default();
+
+    let event = |id: i64, operation: EventOperation| {
+        SyncEvent::new(operation, id)
+            .issue(issue_key)
+            .jira_id(Some(&entry.id))
+    };
+    match existing {
+        None => {
+            // New worklog — insert (OR IGNORE to handle concurrent imports)
+            let inserted = sqlx::query(
+                "INSERT OR IGNORE INTO worklogs (issue_key, started_at, duration_seconds, description, sync_status, jira_worklog_id, jira_updated_at, \
+                 base_started_at, base_duration_seconds, base_description) \
+                 VALUES (?1, ?2, ?3, ?4, 'synced', ?5, ?6, ?2, ?3, ?4)",
+            )
+            .bind(issue_key)
+            .bind(&started_rfc3339)
+            .bind(entry.time_spent_seconds)
+            .bind(&description)
+            .bind(&entry.id)
+            .bind(&entry.updated)
+            .execute(db)
+            .await
+            .map_err(|e| e.to_string())?;
+            if inserted.rows_affected() == 0 {
+                return Ok(Applied::Skipped);
+            }
+            let fields = WorklogFields {
+                started_at: started_rfc3339,
+                duration_seconds: entry.time_spent_seconds,
+                description,
+            };
+            event(inserted.last_insert_rowid(), EventOperation::ImportCreate)
+                .request(sync_events::describe(&fields))
+                .succeeded(db)
+                .await
+                .map_err(|e| e.to_string())?;
+            Ok(Applied::Imported)
+        }
+        Some((_, local_updated, status))
+            if status == "conflict" || local_updated.as_deref() == Some(&entry.updated) =>
+        {
+            Ok(Applied::Skipped)
+        }
+        Some((id, _, _)) => {
+            // Changed in Jira — merge with any local edits against the last-synced snapshot
+            let row = conflicts::load_row(db, *id).await.map_err(|e| e.to_string())?;
+            let Some(row) = row else {
+                return Ok(Applied::Skipped);
+            };
+            let remote = WorklogFields {
+                started_at: started_rfc3339,
+                duration_seconds: entry.time_spent_seconds,
+                description,
+            };
+            let merged = conflicts::merge_remote_change(db, *id, &row, &remote, &entry.updated)
+                .await
+                .map_err(|e| e.to_string())?;
+            let event = event(*id, EventOperation::ImportUpdate).request(sync_events::describe(&remote));
+            let recorded = match merged {
+                ImportMerge::Updated => event.succeeded(db).await,
+                ImportMerge::Kept => event.skipped(db, "Local changes kept and queued for Jira").await,
+                ImportMerge::Conflict => event.conflicted(db, "Changed both here and in Jira").await,
+            };
+            recorded.map_err(|e| e.to_string())?;
+            Ok(match merged {
+                ImportMerge::Updated => Applied::Updated,
+                ImportMerge::Kept => Applied::Skipped,
+                ImportMerge::Conflict => Applied::Conflict,
+            })
+        }
+    }
+}
+
+/// Reflect a worklog deleted in Jira on the local row that held it.
+/// `target` is `(id, issue_key, jira_worklog_id)`; `reason` says why the import
+/// believes it is gone and is kept in the sync history.
+async fn apply_remote_delete(
+    db: &SqlitePool,
+    target: (i64, &str, &str),
+    sync_status: &str,
+    reason: &str,
+) -> Result<Applied, String> {
+    let (id, issue_key, jira_id) = target;
+    let event = SyncEvent::new(EventOperation::ImportDelete, id)
+        .issue(issue_key)
+        .jira_id(Some(jira_id))
+        .request(reason);
+    match sync_status {
+        "conflict" => {
+            event
+                .skipped(db, "Worklog already has an unresolved conflict")
+                .await
+                .map_err(|e| e.to_string())?;
+            return Ok(Applied::Skipped);
+        }
+        "modified" => {
+            // Edited here, deleted there: don't throw the edit away
+            let conflict_reason = "Deleted in Jira since the last sync";
+            conflicts::record_conflict(db, id, false, None, conflict_reason)
+                .await
+                .map_err(|e| e.to_string())?;
+            event.conflicted(db, conflict_reason).await.map_err(|e| e.to_string())?;
+            return Ok(Applied::Conflict);
+        }
+        _ => {}
+    }
+    // A queued delete has nothing left to do
+    sqlx::query("DELETE FROM sync_outbox WHERE worklog_id = ?1")
+        .bind(id)
+        .execute(db)
+        .await
+        .map_err(|e| e.to_string())?;
+    sqlx::query("DELETE FROM worklogs WHERE id = ?1")
+        .bind(id)
+        .execute(db)
+        .await
+        .map_err(|e| e.to_string())?;
+    event.succeeded(db).await.map_err(|e| e.to_string())?;
+    Ok(Applied::Deleted)
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -557,13 +1393,20 @@ pub struct ExternalWorklog {
     pub author_name: String,
 }
 
+#[derive(Debug, Serialize)]
+pub struct UserWorklogs {
+    pub worklogs: Vec<ExternalWorklog>,
+    /// The issue search hit its limit, so worklogs on the remaining issues are missing
+    pub truncated: bool,
+}
+
 #[tauri::command]
 pub async fn jira_search_users(
     state: State<'_, AppState>,
     query: String,
 ) -> Result<Vec<JiraUser>, String> {
     let client = get_client(&state)?;
-    client.search_users(&query, 10).await
+    client.search_users(&query, 10).await.map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -573,7 +1416,7 @@ pub async fn jira_fetch_user_worklogs(
     author_name: String,
     date_from: String,
     date_to: String,
-) -> Result<Vec<ExternalWorklog>, String> {
+) -> Result<UserWorklogs, String> {
     let client = get_client(&state)?;
 
     let from = NaiveDate::parse_from_str(&date_from, "%Y-%m-%d")
@@ -590,7 +1433,12 @@ pub async fn jira_fetch_user_worklogs(
         day_before, day_after, account_id
     );
 
-    let issues = client.search_issues(&jql, 50).await?;
+    let search = client
+        .search_all_issues(&jql, MAX_WORKLOG_SEARCH_ISSUES)
+        .await
+        .map_err(|e| e.to_string())?;
+    let truncated = search.truncated;
+    let issues = search.issues;
 
     let summary_map: HashMap<String, String> = issues
         .iter()
@@ -609,7 +1457,7 @@ pub async fn jira_fetch_user_worklogs(
         .map(|key| {
             let client = client.clone();
             async move {
-                let result = client.get_worklogs(&key, started_after_epoch).await;
+                let result = client.get_worklogs_with_properties(&key, started_after_epoch).await;
                 (key, result)
             }
         })
@@ -642,7 +1490,7 @@ pub async fn jira_fetch_user_worklogs(
             let description = entry
                 .comment
                 .as_ref()
-                .map(extract_adf_text)
+                .map(comment_text)
                 .unwrap_or_default();
             result.push(ExternalWorklog {
                 id: entry.id,
@@ -656,7 +1504,10 @@ pub async fn jira_fetch_user_worklogs(
         }
     }
 
-    Ok(result)
+    Ok(UserWorklogs {
+        worklogs: result,
+        truncated,
+    })
 }
 
 #[tauri::command]
@@ -699,7 +1550,11 @@ pub async fn jira_fetch_issue_worklogs(
         .collect::<Vec<_>>()
         .join(", ");
     let jql = format!("key in ({})", keys_str);
-    let issues = client.search_issues(&jql, issue_keys.len() as u32).await?;
+    let issues = client
+        .search_all_issues(&jql, issue_keys.len())
+        .await
+        .map_err(|e| e.to_string())?
+        .issues;
 
     let summary_map: HashMap<String, String> = issues
         .iter()
@@ -717,7 +1572,7 @@ pub async fn jira_fetch_issue_worklogs(
         .map(|key| {
             let client = client.clone();
             async move {
-                let result = client.get_worklogs(&key, started_after_epoch).await;
+                let result = client.get_worklogs_with_properties(&key, started_after_epoch).await;
                 (key, result)
             }
         })
@@ -747,7 +1602,7 @@ pub async fn jira_fetch_issue_worklogs(
             let description = entry
                 .comment
                 .as_ref()
-                .map(extract_adf_text)
+                .map(comment_text)
                 .unwrap_or_default();
             let author_name = entry
                 .author
@@ -767,3 +1622,439 @@ pub async fn jira_fetch_issue_worklogs(
 
     Ok(result)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jira::fake_server::{test_client, FakeResponse, FakeServer};
+    use crate::jira::retry::RetryPolicy;
+    use chrono::TimeZone;
+
+    fn jira_started(date: NaiveDate, hour: u32) -> DateTime<Local> {
+        Local
+            .from_local_datetime(&date.and_hms_opt(hour, 0, 0).unwrap())
+            .unwrap()
+    }
+
+    /// A worklog of ours as Jira lists it.
+    fn jira_worklog(id: &str, started: DateTime<Local>, seconds: i64) -> serde_json::Value {
+        serde_json::json!({
+            "id": id,
+            "started": started.format("%Y-%m-%dT%H:%M:%S%.3f%z").to_string(),
+            "updated": "2024-03-04T18:00:00.000+0000",
+            "timeSpentSeconds": seconds,
+            "author": { "accountId": "me" }
+        })
+    }
+
+    /// One page holding all of `worklogs`.
+    fn worklog_page(worklogs: &[serde_json::Value]) -> FakeResponse {
+        FakeResponse::json(
+            200,
+            serde_json::json!({ "startAt": 0, "total": worklogs.len(), "worklogs": worklogs }),
+        )
+    }
+
+    /// A JQL search finding SUP-1 only.
+    fn sup_1_search() -> FakeResponse {
+        FakeResponse::json(
+            200,
+            serde_json::json!({
+                "issues": [{ "key": "SUP-1", "fields": { "summary": "Support" } }],
+                "isLast": true
+            }),
+        )
+    }
+
+    #[tokio::test]
+    async fn import_reads_every_worklog_page() {
+        let target_date = NaiveDate::from_ymd_opt(2024, 3, 4).unwrap();
+        let worklogs: Vec<serde_json::Value> = (1..=5)
+            .map(|i| jira_worklog(&format!("{}", 100 + i), jira_started(target_date, 8 + i), 600 * i64::from(i)))
+            .collect();
+
+        let server = FakeServer::start(move |req| {
+            if req.path == "/rest/api/3/search/jql" {
+                return sup_1_search();
+            }
+            // Serve two worklogs per page regardless of the requested maxResults
+            let start: usize = req.query.get("startAt").and_then(|s| s.parse().ok()).unwrap_or(0);
+            let page: Vec<_> = worklogs.iter().skip(start).take(2).cloned().collect();
+            FakeResponse::json(
+                200,
+                serde_json::json!({
+                    "startAt": start,
+                    "maxResults": 2,
+                    "total": worklogs.len(),
+                    "worklogs": page
+                }),
+            )
+        })
+        .await;
+
+        let db = crate::db::test_pool().await;
+        // Already imported earlier; it lives on the last page
+        sqlx::query(
+            "INSERT INTO worklogs (issue_key, started_at, duration_seconds, sync_status, jira_worklog_id, jira_updated_at) \
+             VALUES ('SUP-1', ?1, 3000, 'synced', '105', '2024-03-04T18:00:00.000+0000')",
+        )
+        .bind(jira_started(target_date, 13).to_rfc3339())
+        .execute(&db)
+        .await
+        .unwrap();
+
+        let client = test_client(&server.base_url, RetryPolicy::default());
+        let summary = import_worklogs_for_range(&db, &client, "me", target_date, target_date)
+            .await
+            .unwrap();
+
+        assert_eq!(summary.imported, 4);
+        assert_eq!(summary.skipped, 1);
+        assert_eq!(summary.deleted, 0);
+        assert!(summary.warnings.is_empty(), "{:?}", summary.warnings);
+
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM worklogs")
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        assert_eq!(count, 5);
+
+        let worklog_pages: Vec<String> = server
+            .requests()
+            .into_iter()
+            .filter(|r| r.path.ends_with("/worklog"))
+            .map(|r| r.query.get("startAt").cloned().unwrap_or_default())
+            .collect();
+        assert_eq!(worklog_pages, vec!["0", "2", "4"]);
+    }
+
+    #[tokio::test]
+    async fn range_import_searches_once_and_reports_per_day() {
+        let monday = NaiveDate::from_ymd_opt(2024, 3, 4).unwrap();
+        let tuesday = monday.succ_opt().unwrap();
+        let wednesday = tuesday.succ_opt().unwrap();
+        let worklogs = vec![
+            jira_worklog("201", jira_started(monday, 9), 600),
+            jira_worklog("202", jira_started(wednesday, 9), 600),
+            // Outside the range: fetched but ignored
+            jira_worklog("203", jira_started(wednesday.succ_opt().unwrap(), 9), 600),
+        ];
+
+        let server = FakeServer::start(move |req| {
+            if req.path == "/rest/api/3/search/jql" {
+                return sup_1_search();
+            }
+            worklog_page(&worklogs)
+        })
+        .await;
+
+        let db = crate::db::test_pool().await;
+        // Synced on Tuesday, since deleted in Jira
+        sqlx::query(
+            "INSERT INTO worklogs (issue_key, started_at, duration_seconds, sync_status, jira_worklog_id, jira_updated_at) \
+             VALUES ('SUP-1', ?1, 600, 'synced', '150', '2024-03-05T18:00:00.000+0000')",
+        )
+        .bind(jira_started(tuesday, 10).to_rfc3339())
+        .execute(&db)
+        .await
+        .unwrap();
+
+        let client = test_client(&server.base_url, RetryPolicy::default());
+        let summary = import_worklogs_for_range(&db, &client, "me", monday, wednesday)
+            .await
+            .unwrap();
+
+        assert_eq!((summary.imported, summary.deleted), (2, 1));
+        let per_day: Vec<(&str, u32, u32)> = summary
+            .days
+            .iter()
+            .map(|d| (d.date.as_str(), d.imported, d.deleted))
+            .collect();
+        assert_eq!(
+            per_day,
+            vec![("2024-03-04", 1, 0), ("2024-03-05", 0, 1), ("2024-03-06", 1, 0)]
+        );
+        let requests = server.requests();
+        assert_eq!(requests.iter().filter(|r| r.path == "/rest/api/3/search/jql").count(), 1);
+        assert_eq!(requests.iter().filter(|r| r.path.ends_with("/worklog")).count(), 1);
+        // The removed row leaves a trace of why it went
+        let filter = sync_events::SyncEventFilter {
+            jira_worklog_id: Some("150".to_string()),
+            ..Default::default()
+        };
+        let history = sync_events::list(&db, &filter).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!((history[0].operation.as_str(), history[0].outcome.as_str()), ("import_delete", "success"));
+        assert_eq!(history[0].request, "Missing from SUP-1's worklogs in a range import");
+    }
+
+    #[tokio::test]
+    async fn range_import_links_a_pushed_worklog_instead_of_duplicating_it() {
+        let monday = NaiveDate::from_ymd_opt(2024, 3, 4).unwrap();
+        let db = crate::db::test_pool().await;
+        // The create reached Jira but its answer was lost
+        sqlx::query(
+            "INSERT INTO worklogs (issue_key, started_at, duration_seconds, push_attempted_at) \
+             VALUES ('SUP-1', ?1, 600, datetime('now'))",
+        )
+        .bind(jira_started(monday, 9).to_rfc3339())
+        .execute(&db)
+        .await
+        .unwrap();
+        let local_uuid: String = sqlx::query_scalar("SELECT local_uuid FROM worklogs")
+            .fetch_one(&db)
+            .await
+            .unwrap();
+
+        let mut pushed = jira_worklog("301", jira_started(monday, 9), 600);
+        pushed["properties"] = serde_json::json!([{ "key": LOCAL_ID_PROPERTY, "value": { "id": local_uuid } }]);
+        let server = FakeServer::start(move |req| {
+            if req.path == "/rest/api/3/search/jql" {
+                return sup_1_search();
+            }
+            assert_eq!(req.query.get("expand").map(String::as_str), Some("properties"));
+            worklog_page(std::slice::from_ref(&pushed))
+        })
+        .await;
+        let client = test_client(&server.base_url, RetryPolicy::default());
+
+        import_worklogs_for_range(&db, &client, "me", monday, monday)
+            .await
+            .unwrap();
+
+        let rows: Vec<(String, Option<String>)> =
+            sqlx::query_as("SELECT sync_status, jira_worklog_id FROM worklogs")
+                .fetch_all(&db)
+                .await
+                .unwrap();
+        assert_eq!(rows, vec![("synced".to_string(), Some("301".to_string()))]);
+    }
+
+    #[tokio::test]
+    async fn incremental_import_applies_change_feeds_and_advances_watermarks() {
+        let monday = NaiveDate::from_ymd_opt(2024, 3, 4).unwrap();
+        let worklog = |id: &str, author: &str, hour: u32, properties: serde_json::Value| {
+            let mut worklog = jira_worklog(id, jira_started(monday, hour), 1800);
+            worklog["issueId"] = serde_json::json!("10001");
+            worklog["author"]["accountId"] = serde_json::json!(author);
+            worklog["properties"] = properties;
+            worklog
+        };
+        let worklogs = serde_json::json!([
+            worklog("301", "me", 9, serde_json::json!([])),
+            worklog("302", "someone-else", 10, serde_json::json!([])),
+            // Pushed from here but the response was lost
+            worklog("303", "me", 11, serde_json::json!([{ "key": LOCAL_ID_PROPERTY, "value": { "id": "local-1" } }])),
+        ]);
+
+        let updated_until = chrono::Utc::now().timestamp_millis();
+        let deleted_until = updated_until + 1;
+        let server = FakeServer::start(move |req| match req.path.as_str() {
+            "/rest/api/3/worklog/updated" => FakeResponse::json(
+                200,
+                serde_json::json!({
+                    "values": [{ "worklogId": 301 }, { "worklogId": 302 }, { "worklogId": 303 }],
+                    "until": updated_until,
+                    "lastPage": true
+                }),
+            ),
+            "/rest/api/3/worklog/deleted" => FakeResponse::json(
+                200,
+                serde_json::json!({
+                    "values": [{ "worklogId": 150 }, { "worklogId": 999 }],
+                    "until": deleted_until,
+                    "lastPage": true
+                }),
+            ),
+            "/rest/api/3/worklog/list" => FakeResponse::json(200, worklogs.clone()),
+            "/rest/api/3/issue/10001" => FakeResponse::json(
+                200,
+                serde_json::json!({
+                    "key": "SUP-2",
+                    "fields": { "summary": "Support", "project": { "key": "SUP" } }
+                }),
+            ),
+            _ => FakeResponse::json(404, serde_json::json!({})),
+        })
+        .await;
+
+        let db = crate::db::test_pool().await;
+        sqlx::query(
+            "INSERT INTO worklogs (issue_key, started_at, duration_seconds, sync_status, jira_worklog_id, jira_updated_at) \
+             VALUES ('SUP-1', ?1, 600, 'synced', '150', '2024-03-04T18:00:00.000+0000')",
+        )
+        .bind(jira_started(monday, 8).to_rfc3339())
+        .execute(&db)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO worklogs (issue_key, started_at, duration_seconds, sync_status, local_uuid) \
+             VALUES ('SUP-2', ?1, 1800, 'pending', 'local-1')",
+        )
+        .bind(jira_started(monday, 11).to_rfc3339())
+        .execute(&db)
+        .await
+        .unwrap();
+
+        save_watermark(&db, WORKLOG_UPDATED_SINCE_KEY, 1).await.unwrap();
+
+        let client = test_client(&server.base_url, RetryPolicy::default());
+        let summary = import_worklog_changes(&db, &client, "me").await.unwrap();
+
+        assert!(summary.warnings.is_empty(), "{:?}", summary.warnings);
+        assert_eq!((summary.imported, summary.updated, summary.deleted), (1, 1, 1));
+        assert_eq!(summary.days.len(), 1);
+        assert_eq!(summary.days[0].date, "2024-03-04");
+
+        let rows: Vec<(String, String, String)> = sqlx::query_as(
+            "SELECT issue_key, jira_worklog_id, sync_status FROM worklogs ORDER BY jira_worklog_id",
+        )
+        .fetch_all(&db)
+        .await
+        .unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                ("SUP-2".to_string(), "301".to_string(), "synced".to_string()),
+                ("SUP-2".to_string(), "303".to_string(), "synced".to_string()),
+            ]
+        );
+        assert_eq!(
+            load_watermark(&db, WORKLOG_UPDATED_SINCE_KEY).await.unwrap(),
+            Some(updated_until)
+        );
+        assert_eq!(
+            load_watermark(&db, WORKLOG_DELETED_SINCE_KEY).await.unwrap(),
+            Some(deleted_until)
+        );
+        let issue_lookups = server
+            .requests()
+            .iter()
+            .filter(|r| r.path.starts_with("/rest/api/3/issue/"))
+            .count();
+        assert_eq!(issue_lookups, 1);
+    }
+
+    #[tokio::test]
+    async fn first_incremental_import_searches_our_worklogs_instead_of_the_feeds() {
+        let yesterday = Local::now().date_naive().pred_opt().unwrap();
+        let worklog = jira_worklog("401", jira_started(yesterday, 9), 600);
+        let server = FakeServer::start(move |req| match req.path.as_str() {
+            "/rest/api/3/search/jql" => sup_1_search(),
+            "/rest/api/3/issue/SUP-1/worklog" => worklog_page(std::slice::from_ref(&worklog)),
+            _ => FakeResponse::json(404, serde_json::json!({})),
+        })
+        .await;
+        let db = crate::db::test_pool().await;
+        let client = test_client(&server.base_url, RetryPolicy::default());
+
+        let before = chrono::Utc::now().timestamp_millis();
+        let summary = import_worklog_changes(&db, &client, "me").await.unwrap();
+
+        assert!(summary.warnings.is_empty(), "{:?}", summary.warnings);
+        assert_eq!(summary.imported, 1);
+        assert!(server.requests().iter().all(|r| !r.path.starts_with("/rest/api/3/worklog/")));
+        for key in [WORKLOG_UPDATED_SINCE_KEY, WORKLOG_DELETED_SINCE_KEY] {
+            assert!(load_watermark(&db, key).await.unwrap().is_some_and(|since| since >= before));
+        }
+    }
+
+    #[tokio::test]
+    async fn push_pending_previews_validates_and_links_duplicates() {
+        let monday = NaiveDate::from_ymd_opt(2024, 3, 4).unwrap();
+        // Same instant, written with an offset that puts the string on Tuesday
+        let far_east = chrono::FixedOffset::east_opt(14 * 3600).unwrap();
+        let noon = jira_started(monday, 12).with_timezone(&far_east);
+        let duplicate = jira_worklog("900", jira_started(monday, 9), 1800);
+        let server = FakeServer::start(move |req| match (req.method.as_str(), req.path.as_str()) {
+            ("GET", "/rest/api/3/issue/SUP-1") => FakeResponse::json(
+                200,
+                serde_json::json!({ "key": "SUP-1", "fields": { "summary": "Support" } }),
+            ),
+            ("GET", "/rest/api/3/issue/SUP-1/worklog") => worklog_page(std::slice::from_ref(&duplicate)),
+            ("GET", "/rest/api/3/issue/SUP-1/worklog/900") => FakeResponse::json(200, duplicate.clone()),
+            ("GET", "/rest/api/3/issue/OPS-1") => {
+                FakeResponse::json(403, serde_json::json!({ "errorMessages": ["Forbidden"] }))
+            }
+            ("POST", "/rest/api/3/issue/SUP-1/worklog") => FakeResponse::json(
+                201,
+                serde_json::json!({ "id": "901", "updated": "2024-03-05T09:00:00.000+0000" }),
+            ),
+            _ => FakeResponse::json(404, serde_json::json!({ "errorMessages": ["Issue does not exist"] })),
+        })
+        .await;
+
+        let db = crate::db::test_pool().await;
+        let rows = [
+            ("SUP-1", jira_started(monday, 9).to_rfc3339(), 1800, ""),
+            ("SUP-1", noon.to_rfc3339(), 3600, "Triage"),
+            ("GONE-1", jira_started(monday, 14).to_rfc3339(), 600, ""),
+            ("SUP-1", jira_started(monday, 15).to_rfc3339(), 0, ""),
+            // Its lookup fails, which only affects this row
+            ("OPS-1", jira_started(monday, 16).to_rfc3339(), 600, ""),
+            // Outside the range
+            ("SUP-1", jira_started(monday.succ_opt().unwrap(), 9).to_rfc3339(), 600, ""),
+        ];
+        for (issue_key, started_at, duration, description) in rows {
+            sqlx::query(
+                "INSERT INTO worklogs (issue_key, started_at, duration_seconds, description, sync_status) \
+                 VALUES (?1, ?2, ?3, ?4, 'pending')",
+            )
+            .bind(issue_key)
+            .bind(started_at)
+            .bind(duration)
+            .bind(description)
+            .execute(&db)
+            .await
+            .unwrap();
+        }
+
+        let client = test_client(&server.base_url, RetryPolicy::default());
+        let preview = push_pending(&db, &client, "me", Some(monday), Some(monday), true)
+            .await
+            .unwrap();
+
+        assert!(preview.summary.is_none());
+        let found: Vec<(&str, i64, Option<&str>, usize)> = preview
+            .items
+            .iter()
+            .map(|i| (i.issue_key.as_str(), i.duration_seconds, i.duplicate_of.as_deref(), i.problems.len()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("SUP-1", 1800, Some("900"), 0),
+                ("SUP-1", 3600, None, 0),
+                ("GONE-1", 600, None, 1),
+                ("SUP-1", 0, None, 1),
+                ("OPS-1", 600, None, 1),
+            ]
+        );
+        assert!(preview.items[4].problems[0].starts_with("Could not look up issue OPS-1"));
+        let triage = &preview.items[1];
+        assert_eq!(triage.started, noon.format("%Y-%m-%dT%H:%M:%S%.3f%z").to_string());
+        assert_eq!(triage.comment.as_ref().unwrap()["content"][0]["content"][0]["text"], "Triage");
+        assert!(server.requests().iter().all(|r| r.method == "GET"));
+
+        link_pending_worklog(&db, &client, "me", preview.items[0].worklog_id, "900")
+            .await
+            .unwrap();
+        let lookups = server.requests().len();
+        let report = push_pending(&db, &client, "me", Some(monday), Some(monday), false)
+            .await
+            .unwrap();
+        let summary = report.summary.unwrap();
+        // The zero-length row is held back; Jira rejects the missing issues
+        assert_eq!((summary.total, summary.success, summary.failed), (4, 1, 3));
+        let synced: Vec<(i64, String)> = sqlx::query_as(
+            "SELECT duration_seconds, jira_worklog_id FROM worklogs WHERE sync_status = 'synced' ORDER BY started_at",
+        )
+        .fetch_all(&db)
+        .await
+        .unwrap();
+        assert_eq!(synced, vec![(1800, "900".to_string()), (3600, "901".to_string())]);
+        let pushed = &server.requests()[lookups..];
+        assert!(pushed.iter().all(|r| r.method == "POST"));
+        assert_eq!(pushed.iter().filter(|r| r.path == "/rest/api/3/issue/SUP-1/worklog").count(), 1);
+    }
+}
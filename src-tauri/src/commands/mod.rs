@@ -1,5 +1,8 @@
+pub mod backup;
+pub mod conflicts;
 pub mod jira;
 pub mod settings;
+pub mod sync;
 pub mod timer;
 pub mod updater;
 pub mod worklogs;
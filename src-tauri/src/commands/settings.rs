@@ -1,4 +1,12 @@
+use crate::jira::client::JiraClient;
+use crate::jira::oauth::{OAuthApp, OAuthConnection};
+use crate::jira::retry::{RetryPolicy, MIN_REQUESTS_PER_SECOND};
+use crate::jira::types::JiraFlavor;
+use crate::rounding::{self, RoundingPolicy};
+use crate::secrets::is_secret_key;
 use crate::state::{AppState, JiraConfig};
+use crate::tray_menu;
+use sqlx::SqlitePool;
 use std::collections::HashMap;
 use tauri::State;
 
@@ -7,6 +15,10 @@ pub async fn settings_get(
     state: State<'_, AppState>,
     key: String,
 ) -> Result<Option<String>, String> {
+    // Secrets never reach the webview through the generic settings API
+    if is_secret_key(&key) {
+        return Ok(None);
+    }
     let row: Option<(String,)> =
         sqlx::query_as("SELECT value FROM settings WHERE key = ?1")
             .bind(&key)
@@ -22,6 +34,9 @@ pub async fn settings_set(
     key: String,
     value: String,
 ) -> Result<(), String> {
+    if is_secret_key(&key) {
+        return Err(format!("'{}' is a secret and cannot be stored as a plain setting", key));
+    }
     sqlx::query(
         "INSERT INTO settings (key, value) VALUES (?1, ?2) ON CONFLICT(key) DO UPDATE SET value = ?2",
     )
@@ -30,6 +45,8 @@ pub async fn settings_set(
     .execute(&state.db)
     .await
     .map_err(|e| e.to_string())?;
+    // Pinned issues feed the tray's "Start on…" list
+    tray_menu::invalidate(&state);
     Ok(())
 }
 
@@ -42,7 +59,33 @@ pub async fn settings_get_all(
             .fetch_all(&state.db)
             .await
             .map_err(|e| e.to_string())?;
-    Ok(rows.into_iter().collect())
+    Ok(rows
+        .into_iter()
+        .filter(|(key, _)| !is_secret_key(key))
+        .collect())
+}
+
+#[tauri::command]
+pub async fn settings_get_rounding(state: State<'_, AppState>) -> Result<RoundingPolicy, String> {
+    rounding::load(&state.db).await.map_err(|e| e.to_string())
+}
+
+/// Applies to worklogs recorded or edited from now on, and with `at_push` to
+/// every worklog pushed from now on.
+#[tauri::command]
+pub async fn settings_save_rounding(
+    state: State<'_, AppState>,
+    policy: RoundingPolicy,
+) -> Result<(), String> {
+    if policy.increment_seconds <= 0 {
+        return Err("The rounding increment must be greater than zero".to_string());
+    }
+    if policy.minimum_seconds < 0 {
+        return Err("The minimum billable duration cannot be negative".to_string());
+    }
+    rounding::save(&state.db, &policy)
+        .await
+        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -51,11 +94,27 @@ pub async fn settings_save_jira_config(
     base_url: String,
     email: String,
     api_token: String,
+    flavor: Option<JiraFlavor>,
 ) -> Result<(), String> {
+    // Auto-detect when the user didn't pick Cloud or Data Center explicitly
+    let flavor = match flavor {
+        Some(f) => f,
+        None => JiraClient::detect_flavor(&state.http_client, &base_url)
+            .await
+            .map_err(|e| format!("Could not detect Jira deployment type: {}", e))?,
+    };
+
+    // The token first: settings rows pointing at a missing token would look configured
+    state
+        .secrets
+        .set("jira_api_token", &api_token)
+        .map_err(|e| e.to_string())?;
+
     let pairs = [
-        ("jira_base_url", &base_url),
-        ("jira_email", &email),
-        ("jira_api_token", &api_token),
+        ("jira_auth_method", "api_token"),
+        ("jira_base_url", base_url.as_str()),
+        ("jira_flavor", flavor.as_str()),
+        ("jira_email", email.as_str()),
     ];
 
     for (key, value) in pairs {
@@ -71,8 +130,10 @@ pub async fn settings_save_jira_config(
 
     *state.jira_config.lock().unwrap_or_else(|e| e.into_inner()) = Some(JiraConfig {
         base_url,
+        flavor,
         email,
         api_token,
+        oauth: None,
     });
 
     // Clear cached account_id when config changes
@@ -89,7 +150,8 @@ pub async fn settings_load_jira_config(
     state: State<'_, AppState>,
 ) -> Result<Option<HashMap<String, String>>, String> {
     let rows: Vec<(String, String)> = sqlx::query_as(
-        "SELECT key, value FROM settings WHERE key IN ('jira_base_url', 'jira_email', 'jira_api_token')",
+        "SELECT key, value FROM settings WHERE key IN \
+         ('jira_auth_method', 'jira_base_url', 'jira_flavor', 'jira_email', 'jira_oauth_cloud_id')",
     )
     .fetch_all(&state.db)
     .await
@@ -97,14 +159,50 @@ pub async fn settings_load_jira_config(
 
     let mut map: HashMap<String, String> = rows.into_iter().collect();
 
-    if map.contains_key("jira_base_url")
-        && map.contains_key("jira_email")
-        && map.contains_key("jira_api_token")
-    {
+    if map.get("jira_auth_method").map(String::as_str) == Some("oauth") {
+        let app = state
+            .secrets
+            .get("jira_oauth_app")
+            .map_err(|e| e.to_string())?
+            .and_then(|a| serde_json::from_str::<OAuthApp>(&a).ok());
+        let (Some(app), Some(base_url), Some(cloud_id)) = (
+            app,
+            map.get("jira_base_url").cloned(),
+            map.get("jira_oauth_cloud_id").cloned(),
+        ) else {
+            return Ok(None);
+        };
+        *state.jira_config.lock().unwrap_or_else(|e| e.into_inner()) = Some(JiraConfig {
+            base_url,
+            flavor: JiraFlavor::Cloud,
+            email: String::new(),
+            api_token: String::new(),
+            oauth: Some(OAuthConnection { app, cloud_id }),
+        });
+        return Ok(Some(map));
+    }
+
+    let api_token = state
+        .secrets
+        .get("jira_api_token")
+        .map_err(|e| e.to_string())?;
+    if let (true, true, Some(api_token)) = (
+        map.contains_key("jira_base_url"),
+        map.contains_key("jira_email"),
+        api_token,
+    ) {
+        // Configs saved before Data Center support are always Cloud
+        let flavor = map
+            .get("jira_flavor")
+            .and_then(|f| JiraFlavor::parse(f))
+            .unwrap_or_default();
+        map.insert("jira_flavor".to_string(), flavor.as_str().to_string());
         let config = JiraConfig {
             base_url: map["jira_base_url"].clone(),
+            flavor,
             email: map["jira_email"].clone(),
-            api_token: map["jira_api_token"].clone(),
+            api_token,
+            oauth: None,
         };
         *state.jira_config.lock().unwrap_or_else(|e| e.into_inner()) = Some(config);
         map.insert("jira_api_token".to_string(), "***".to_string());
@@ -113,3 +211,88 @@ pub async fn settings_load_jira_config(
         Ok(None)
     }
 }
+
+/// Read the Jira retry policy from settings, falling back to defaults for missing,
+/// unparsable or out-of-range keys.
+pub async fn load_retry_policy(db: &SqlitePool) -> RetryPolicy {
+    let rows: Vec<(String, String)> = sqlx::query_as(
+        "SELECT key, value FROM settings WHERE key IN \
+         ('jira_retry_max_retries', 'jira_retry_base_delay_ms', 'jira_retry_max_delay_ms', 'jira_requests_per_second')",
+    )
+    .fetch_all(db)
+    .await
+    .unwrap_or_else(|e| {
+        eprintln!("Failed to read Jira retry settings: {}", e);
+        Vec::new()
+    });
+
+    let mut policy = RetryPolicy::default();
+    for (key, value) in rows {
+        match key.as_str() {
+            "jira_retry_max_retries" => policy.max_retries = value.parse().unwrap_or(policy.max_retries),
+            "jira_retry_base_delay_ms" => policy.base_delay_ms = value.parse().unwrap_or(policy.base_delay_ms),
+            "jira_retry_max_delay_ms" => policy.max_delay_ms = value.parse().unwrap_or(policy.max_delay_ms),
+            "jira_requests_per_second" => {
+                policy.requests_per_second = value
+                    .parse()
+                    .ok()
+                    .filter(|rate| RetryPolicy::valid_rate(*rate))
+                    .unwrap_or(policy.requests_per_second)
+            }
+            _ => {}
+        }
+    }
+    policy
+}
+
+#[tauri::command]
+pub async fn settings_get_jira_retry_policy(
+    state: State<'_, AppState>,
+) -> Result<RetryPolicy, String> {
+    Ok(state
+        .jira_retry_policy
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone())
+}
+
+#[tauri::command]
+pub async fn settings_save_jira_retry_policy(
+    state: State<'_, AppState>,
+    policy: RetryPolicy,
+) -> Result<(), String> {
+    if !RetryPolicy::valid_rate(policy.requests_per_second) {
+        return Err(format!(
+            "Requests per second must be zero or at least {}",
+            MIN_REQUESTS_PER_SECOND
+        ));
+    }
+    if policy.base_delay_ms > policy.max_delay_ms {
+        return Err("Base retry delay cannot exceed the maximum delay".to_string());
+    }
+
+    let pairs = [
+        ("jira_retry_max_retries", policy.max_retries.to_string()),
+        ("jira_retry_base_delay_ms", policy.base_delay_ms.to_string()),
+        ("jira_retry_max_delay_ms", policy.max_delay_ms.to_string()),
+        ("jira_requests_per_second", policy.requests_per_second.to_string()),
+    ];
+
+    for (key, value) in pairs {
+        sqlx::query(
+            "INSERT INTO settings (key, value) VALUES (?1, ?2) ON CONFLICT(key) DO UPDATE SET value = ?2",
+        )
+        .bind(key)
+        .bind(value)
+        .execute(&state.db)
+        .await
+        .map_err(|e| e.to_string())?;
+    }
+
+    *state
+        .jira_retry_policy
+        .lock()
+        .unwrap_or_else(|e| e.into_inner()) = policy;
+
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use crate::state::AppState;
+use crate::sync::{self, SyncSettings, SyncStatus};
+use crate::sync_events::{self, SyncEventFilter, SyncEventRow};
+use tauri::State;
+
+#[tauri::command]
+pub async fn sync_get_settings(state: State<'_, AppState>) -> Result<SyncSettings, String> {
+    Ok(sync::load_settings(&state.db).await)
+}
+
+#[tauri::command]
+pub async fn sync_save_settings(
+    state: State<'_, AppState>,
+    settings: SyncSettings,
+) -> Result<(), String> {
+    let pairs = [
+        ("sync_auto_push", settings.auto_push.to_string()),
+        ("sync_grace_minutes", settings.grace_minutes.to_string()),
+    ];
+    for (key, value) in pairs {
+        sqlx::query(
+            "INSERT INTO settings (key, value) VALUES (?1, ?2) ON CONFLICT(key) DO UPDATE SET value = ?2",
+        )
+        .bind(key)
+        .bind(value)
+        .execute(&state.db)
+        .await
+        .map_err(|e| e.to_string())?;
+    }
+    state.sync.wake();
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn sync_get_status(state: State<'_, AppState>) -> Result<SyncStatus, String> {
+    let mut status = state.sync.status();
+    status.pending = sync::count_pending(&state.db)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(status)
+}
+
+/// Recorded pushes, updates, deletes and import decisions, newest first.
+#[tauri::command]
+pub async fn sync_get_events(
+    state: State<'_, AppState>,
+    filter: Option<SyncEventFilter>,
+) -> Result<Vec<SyncEventRow>, String> {
+    sync_events::list(&state.db, &filter.unwrap_or_default())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Queue every pending worklog regardless of the grace period, requeue offline
+/// edits and deletes whose last replay failed, and run the worker now.
+#[tauri::command]
+pub async fn sync_now(state: State<'_, AppState>) -> Result<(), String> {
+    sync::enqueue_due_creates(&state.db, 0)
+        .await
+        .map_err(|e| e.to_string())?;
+    sync::enqueue_local_changes(&state.db)
+        .await
+        .map_err(|e| e.to_string())?;
+    state.sync.wake();
+    Ok(())
+}
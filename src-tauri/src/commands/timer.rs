@@ -1,268 +1,770 @@
+use crate::rounding;
 use crate::state::AppState;
-use chrono::Utc;
+use chrono::{DateTime, Local, NaiveDate, NaiveTime, TimeZone, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::SqliteConnection;
+use sqlx::{SqliteConnection, SqlitePool};
+use std::collections::BTreeMap;
 use tauri::State;
 
+/// Settings key for the local time (`HH:MM`) at which one tracking day ends and
+/// the next begins. Defaults to midnight; night owls can move it to e.g. `04:00`.
+pub const DAY_BOUNDARY_KEY: &str = "timer_day_boundary";
+/// Settings key for the [`StopMode`] applied when a timer is stopped.
+pub const STOP_MODE_KEY: &str = "timer_stop_mode";
+/// Settings key for whether only one timer may exist at a time. Unless it is
+/// `false`, starting a timer stops and saves every other one.
+pub const EXCLUSIVE_KEY: &str = "timer_exclusive";
+
+/// How a stopped timer's segments become worklogs. Either way nothing spans a
+/// day boundary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum StopMode {
+    /// One worklog per day, starting when the timer first ran that day and
+    /// lasting as long as it ran in total
+    #[default]
+    PerDay,
+    /// One worklog per run between pauses, at its real start and end
+    PerSegment,
+}
+
+impl StopMode {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "per_day" => Some(Self::PerDay),
+            "per_segment" => Some(Self::PerSegment),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
 pub struct TimerState {
+    pub id: i64,
+    /// Label to tell parallel timers apart; empty unless the user named it
+    pub name: String,
     pub issue_key: String,
     pub started_at: String,
     pub accumulated_secs: i64,
     pub is_paused: bool,
     pub paused_at: Option<String>,
     pub description: String,
+    /// Every stretch the timer ran, oldest first; the last is open while running
+    #[sqlx(skip)]
+    pub segments: Vec<TimerSegment>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub struct TimerSegment {
+    pub started_at: String,
+    pub ended_at: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct StoppedWorklog {
     pub id: i64,
     pub issue_key: String,
+    pub started_at: String,
     pub duration_seconds: i64,
 }
 
-/// Shared logic: read active timer, calculate duration, create worklog, delete timer.
-/// Returns None if no active timer exists, or Some(StoppedWorklog) on success.
-/// Skips creating worklog if rounded duration is 0.
-async fn stop_and_save(conn: &mut SqliteConnection) -> Result<Option<StoppedWorklog>, String> {
-    let row: Option<(String, String, i64, bool, Option<String>, String)> = sqlx::query_as(
-        "SELECT issue_key, started_at, accumulated_secs, is_paused, paused_at, description FROM active_timer WHERE id = 1",
+/// Stop and save every timer as of `end`.
+async fn stop_all_at(conn: &mut SqliteConnection, end: DateTime<Utc>) -> Result<Vec<StoppedWorklog>, String> {
+    let ids: Vec<i64> = sqlx::query_scalar("SELECT id FROM active_timer ORDER BY id")
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?;
+    let mut saved = Vec::new();
+    for id in ids {
+        saved.extend(stop_and_save_at(conn, id, end).await?.unwrap_or_default());
+    }
+    Ok(saved)
+}
+
+/// In exclusive mode a new timer takes over from the running ones at `start`, so
+/// they are saved up to it. Back-dated time may not overlap saved worklogs nor
+/// reach back before a running timer began.
+async fn hand_over_at(
+    conn: &mut SqliteConnection,
+    start: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> Result<Vec<StoppedWorklog>, String> {
+    if start < now {
+        // Checked before saving the running timers, whose rounded worklogs may reach past `start`
+        check_no_overlap(conn, start, now).await?;
+        let started_later: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM timer_segments WHERE CAST(strftime('%s', started_at) AS INTEGER) > ?1)",
+        )
+        .bind(start.timestamp())
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?;
+        if started_later {
+            return Err("The running timer started after that time; stop it first".to_string());
+        }
+    }
+    stop_all_at(conn, start).await
+}
+
+/// Shared logic: close the running segment at `end`, split the recorded segments
+/// into one worklog per tracking day, delete the timer. Time recorded after `end`
+/// is dropped.
+/// Returns None if the timer doesn't exist, otherwise the worklogs created.
+/// Durations are rounded by the rounding policy; pieces that round to 0 get no worklog.
+async fn stop_and_save_at(
+    conn: &mut SqliteConnection,
+    timer_id: i64,
+    end: DateTime<Utc>,
+) -> Result<Option<Vec<StoppedWorklog>>, String> {
+    let row: Option<(String, i64, String)> = sqlx::query_as(
+        "SELECT issue_key, accumulated_secs, description FROM active_timer WHERE id = ?1",
     )
+    .bind(timer_id)
     .fetch_optional(&mut *conn)
     .await
     .map_err(|e| e.to_string())?;
 
-    let Some((issue_key, started_at, accumulated, is_paused, _, description)) = row else {
+    let Some((issue_key, accumulated, description)) = row else {
         return Ok(None);
     };
 
-    let total_secs = if is_paused {
-        accumulated
-    } else {
-        let now = Utc::now();
-        let started = chrono::DateTime::parse_from_rfc3339(&started_at)
-            .map_err(|e| format!("Invalid started_at: {}", e))?;
-        let elapsed = (now - started.with_timezone(&Utc)).num_seconds();
-        accumulated + elapsed
-    };
-    let total_secs = total_secs.max(0);
-    // Round up to nearest minute
-    let total_secs = if total_secs > 0 {
-        ((total_secs + 59) / 60) * 60
-    } else {
-        total_secs
+    close_segment(conn, timer_id, &end.to_rfc3339()).await?;
+    let rows: Vec<(String, Option<String>)> =
+        sqlx::query_as("SELECT started_at, ended_at FROM timer_segments WHERE timer_id = ?1 ORDER BY id")
+            .bind(timer_id)
+            .fetch_all(&mut *conn)
+            .await
+            .map_err(|e| e.to_string())?;
+    let parse = |s: &str| {
+        DateTime::parse_from_rfc3339(s)
+            .map(|dt| dt.with_timezone(&Utc))
+            .map_err(|e| format!("Invalid segment time '{}': {}", s, e))
     };
+    let mut segments = Vec::with_capacity(rows.len());
+    for (started_at, ended_at) in &rows {
+        let started = parse(started_at)?;
+        let ended = match ended_at {
+            Some(ended_at) => parse(ended_at)?.min(end),
+            None => end,
+        };
+        if started < ended {
+            segments.push((started, ended));
+        }
+    }
+    // A timer with no recorded segments can only have its accumulated total
+    if rows.is_empty() && accumulated > 0 {
+        segments.push((end - chrono::Duration::seconds(accumulated), end));
+    }
 
-    // Delete active timer
-    sqlx::query("DELETE FROM active_timer WHERE id = 1")
+    sqlx::query("DELETE FROM timer_segments WHERE timer_id = ?1")
+        .bind(timer_id)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?;
+    sqlx::query("DELETE FROM active_timer WHERE id = ?1")
+        .bind(timer_id)
         .execute(&mut *conn)
         .await
         .map_err(|e| e.to_string())?;
 
-    // Don't create worklog with zero duration
-    if total_secs == 0 {
-        return Ok(None);
+    let boundary = day_boundary(conn).await?;
+    let mode = stop_mode(conn).await?;
+    let policy = rounding::load(&mut *conn).await.map_err(|e| e.to_string())?;
+    let mut saved = Vec::new();
+    for piece in worklog_pieces(&segments, boundary, mode, &Local) {
+        let duration = policy.apply(piece.seconds);
+        // Don't create worklog with zero duration
+        if duration == 0 {
+            continue;
+        }
+        let started_at = piece.started.with_timezone(&Local).to_rfc3339();
+        let result = sqlx::query(
+            "INSERT INTO worklogs (issue_key, started_at, duration_seconds, raw_duration_seconds, description, sync_status) \
+             VALUES (?1, ?2, ?3, ?4, ?5, 'pending')",
+        )
+        .bind(&issue_key)
+        .bind(&started_at)
+        .bind(duration)
+        .bind(piece.seconds)
+        .bind(&description)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?;
+        saved.push(StoppedWorklog {
+            id: result.last_insert_rowid(),
+            issue_key: issue_key.clone(),
+            started_at,
+            duration_seconds: duration,
+        });
     }
 
-    let stop_time = Utc::now();
-    let worklog_started = stop_time - chrono::Duration::seconds(total_secs);
-    let started_str = worklog_started.to_rfc3339();
+    Ok(Some(saved))
+}
 
-    let result = sqlx::query(
-        "INSERT INTO worklogs (issue_key, started_at, duration_seconds, description, sync_status) \
-         VALUES (?1, ?2, ?3, ?4, 'pending')",
-    )
-    .bind(&issue_key)
-    .bind(&started_str)
-    .bind(total_secs)
-    .bind(&description)
-    .execute(&mut *conn)
-    .await
-    .map_err(|e| e.to_string())?;
+async fn open_segment(conn: &mut SqliteConnection, timer_id: i64, at: &str) -> Result<(), String> {
+    sqlx::query("INSERT INTO timer_segments (timer_id, started_at) VALUES (?1, ?2)")
+        .bind(timer_id)
+        .bind(at)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+async fn close_segment(conn: &mut SqliteConnection, timer_id: i64, at: &str) -> Result<(), String> {
+    sqlx::query("UPDATE timer_segments SET ended_at = ?1 WHERE timer_id = ?2 AND ended_at IS NULL")
+        .bind(at)
+        .bind(timer_id)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+async fn load_segments(db: &SqlitePool, timer_id: i64) -> Result<Vec<TimerSegment>, String> {
+    sqlx::query_as("SELECT started_at, ended_at FROM timer_segments WHERE timer_id = ?1 ORDER BY id")
+        .bind(timer_id)
+        .fetch_all(db)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+pub(crate) async fn setting(conn: &mut SqliteConnection, key: &str) -> Result<Option<String>, String> {
+    sqlx::query_scalar("SELECT value FROM settings WHERE key = ?1")
+        .bind(key)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+pub(crate) async fn day_boundary(conn: &mut SqliteConnection) -> Result<NaiveTime, String> {
+    Ok(setting(conn, DAY_BOUNDARY_KEY)
+        .await?
+        .and_then(|v| NaiveTime::parse_from_str(&v, "%H:%M").ok())
+        .unwrap_or(NaiveTime::MIN))
+}
+
+async fn exclusive(conn: &mut SqliteConnection) -> Result<bool, String> {
+    Ok(setting(conn, EXCLUSIVE_KEY).await?.as_deref() != Some("false"))
+}
 
-    Ok(Some(StoppedWorklog {
-        id: result.last_insert_rowid(),
-        issue_key,
-        duration_seconds: total_secs,
-    }))
+async fn stop_mode(conn: &mut SqliteConnection) -> Result<StopMode, String> {
+    Ok(setting(conn, STOP_MODE_KEY)
+        .await?
+        .and_then(|v| StopMode::parse(&v))
+        .unwrap_or_default())
+}
+
+/// The worklogs a stop produces under `mode`, in time order.
+fn worklog_pieces<Tz: TimeZone>(
+    segments: &[(DateTime<Utc>, DateTime<Utc>)],
+    boundary: NaiveTime,
+    mode: StopMode,
+    tz: &Tz,
+) -> Vec<DayPiece> {
+    match mode {
+        StopMode::PerDay => split_by_day(segments, boundary, tz),
+        StopMode::PerSegment => segments
+            .iter()
+            .flat_map(|segment| split_by_day(std::slice::from_ref(segment), boundary, tz))
+            .collect(),
+    }
+}
+
+/// Tracked time falling on one tracking day.
+#[derive(Debug, PartialEq)]
+struct DayPiece {
+    day: NaiveDate,
+    /// Start of the first segment (or part of one) on this day
+    started: DateTime<Utc>,
+    seconds: i64,
+}
+
+/// Cut run segments at each day boundary (`boundary` local time in `tz`) and
+/// sum them per tracking day, in day order.
+fn split_by_day<Tz: TimeZone>(
+    segments: &[(DateTime<Utc>, DateTime<Utc>)],
+    boundary: NaiveTime,
+    tz: &Tz,
+) -> Vec<DayPiece> {
+    let mut days: BTreeMap<NaiveDate, DayPiece> = BTreeMap::new();
+    for &(start, end) in segments {
+        let mut cursor = start;
+        while cursor < end {
+            let day = tracking_day(cursor, boundary, tz);
+            let next = day
+                .succ_opt()
+                .and_then(|next| tracking_day_start(next, boundary, tz))
+                .filter(|next| *next > cursor)
+                .unwrap_or(end);
+            let piece_end = next.min(end);
+            let piece = days.entry(day).or_insert(DayPiece {
+                day,
+                started: cursor,
+                seconds: 0,
+            });
+            piece.started = piece.started.min(cursor);
+            piece.seconds += (piece_end - cursor).num_seconds();
+            cursor = piece_end;
+        }
+    }
+    days.into_values().collect()
+}
+
+/// The tracking day `t` falls on, for days beginning at `boundary` local time in `tz`.
+pub(crate) fn tracking_day<Tz: TimeZone>(t: DateTime<Utc>, boundary: NaiveTime, tz: &Tz) -> NaiveDate {
+    let offset = boundary.signed_duration_since(NaiveTime::MIN);
+    (t.with_timezone(tz).naive_local() - offset).date()
+}
+
+/// The instant tracking day `day` begins, `None` if it can't be placed in `tz`.
+pub(crate) fn tracking_day_start<Tz: TimeZone>(day: NaiveDate, boundary: NaiveTime, tz: &Tz) -> Option<DateTime<Utc>> {
+    let local = day.and_time(boundary);
+    // A boundary inside a DST gap falls back to the first instant after it
+    tz.from_local_datetime(&local)
+        .earliest()
+        .or_else(|| tz.from_local_datetime(&(local + chrono::Duration::hours(1))).earliest())
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+/// The timer a command acts on: `timer_id` when given, otherwise the only one.
+/// With several timers and no id this is an error rather than a guess.
+async fn resolve_timer(conn: &mut SqliteConnection, timer_id: Option<i64>) -> Result<Option<i64>, String> {
+    let ids: Vec<i64> = sqlx::query_scalar("SELECT id FROM active_timer ORDER BY id")
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?;
+    match timer_id {
+        Some(id) if ids.contains(&id) => Ok(Some(id)),
+        Some(id) => Err(format!("No timer with id {}", id)),
+        None if ids.len() > 1 => Err("Several timers are running; choose one".to_string()),
+        None => Ok(ids.first().copied()),
+    }
+}
+
+async fn require_timer(conn: &mut SqliteConnection, timer_id: Option<i64>) -> Result<i64, String> {
+    resolve_timer(conn, timer_id)
+        .await?
+        .ok_or_else(|| "No active timer".to_string())
+}
+
+const TIMER_COLUMNS: &str =
+    "id, name, issue_key, started_at, accumulated_secs, is_paused, paused_at, description";
+
+async fn load_state(db: &SqlitePool, timer_id: i64) -> Result<Option<TimerState>, String> {
+    let timer: Option<TimerState> =
+        sqlx::query_as(&format!("SELECT {} FROM active_timer WHERE id = ?1", TIMER_COLUMNS))
+            .bind(timer_id)
+            .fetch_optional(db)
+            .await
+            .map_err(|e| e.to_string())?;
+    let Some(mut timer) = timer else {
+        return Ok(None);
+    };
+    timer.segments = load_segments(db, timer_id).await?;
+    Ok(Some(timer))
+}
+
+async fn require_state(db: &SqlitePool, timer_id: i64) -> Result<TimerState, String> {
+    load_state(db, timer_id)
+        .await?
+        .ok_or_else(|| "No active timer".to_string())
 }
 
 #[tauri::command]
 pub async fn timer_get_state(
     state: State<'_, AppState>,
+    timer_id: Option<i64>,
 ) -> Result<Option<TimerState>, String> {
-    let row: Option<(String, String, i64, bool, Option<String>, String)> = sqlx::query_as(
-        "SELECT issue_key, started_at, accumulated_secs, is_paused, paused_at, description FROM active_timer WHERE id = 1",
+    let mut conn = state.db.acquire().await.map_err(|e| e.to_string())?;
+    match resolve_timer(&mut conn, timer_id).await? {
+        Some(id) => load_state(&state.db, id).await,
+        None => Ok(None),
+    }
+}
+
+/// Every timer, oldest first.
+#[tauri::command]
+pub async fn timer_list(state: State<'_, AppState>) -> Result<Vec<TimerState>, String> {
+    let mut timers: Vec<TimerState> =
+        sqlx::query_as(&format!("SELECT {} FROM active_timer ORDER BY id", TIMER_COLUMNS))
+            .fetch_all(&state.db)
+            .await
+            .map_err(|e| e.to_string())?;
+    for timer in &mut timers {
+        timer.segments = load_segments(&state.db, timer.id).await?;
+    }
+    Ok(timers)
+}
+
+pub(crate) fn parse_instant(value: &str) -> Result<DateTime<Utc>, String> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|e| format!("Invalid date '{}': {}", value, e))
+}
+
+/// Refuse to claim `start..end` for the timer when a worklog already covers part
+/// of it. Worklogs on their way out of Jira don't count.
+async fn check_no_overlap(
+    conn: &mut SqliteConnection,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<(), String> {
+    // strftime('%s') reads the stored offset, so mixed offsets compare correctly
+    let overlap: Option<(String, String, i64)> = sqlx::query_as(
+        "SELECT issue_key, started_at, duration_seconds FROM worklogs \
+         WHERE sync_status != 'pending_delete' \
+         AND CAST(strftime('%s', started_at) AS INTEGER) < ?2 \
+         AND CAST(strftime('%s', started_at) AS INTEGER) + duration_seconds > ?1 \
+         ORDER BY started_at LIMIT 1",
     )
-    .fetch_optional(&state.db)
+    .bind(start.timestamp())
+    .bind(end.timestamp())
+    .fetch_optional(&mut *conn)
     .await
     .map_err(|e| e.to_string())?;
+    match overlap {
+        Some((issue_key, started_at, duration)) => {
+            let from = parse_instant(&started_at)?.with_timezone(&Local);
+            let to = from + chrono::Duration::seconds(duration);
+            Err(format!(
+                "Overlaps the worklog on {} from {} to {}",
+                issue_key,
+                from.format("%Y-%m-%d %H:%M"),
+                to.format("%H:%M")
+            ))
+        }
+        None => Ok(()),
+    }
+}
 
-    Ok(row.map(|(issue_key, started_at, accumulated_secs, is_paused, paused_at, description)| TimerState {
-        issue_key,
-        started_at,
-        accumulated_secs,
-        is_paused,
-        paused_at,
-        description,
-    }))
+/// Bring a timer's `accumulated_secs` and running `started_at` in line with its
+/// segments after they were edited.
+async fn refresh_totals(conn: &mut SqliteConnection, timer_id: i64) -> Result<(), String> {
+    let rows: Vec<(String, Option<String>)> =
+        sqlx::query_as("SELECT started_at, ended_at FROM timer_segments WHERE timer_id = ?1 ORDER BY id")
+            .bind(timer_id)
+            .fetch_all(&mut *conn)
+            .await
+            .map_err(|e| e.to_string())?;
+    let mut accumulated = 0;
+    let mut running_since = None;
+    for (started_at, ended_at) in rows {
+        match ended_at {
+            Some(ended_at) => {
+                accumulated += (parse_instant(&ended_at)? - parse_instant(&started_at)?).num_seconds();
+            }
+            None => running_since = Some(started_at),
+        }
+    }
+    sqlx::query(
+        "UPDATE active_timer SET accumulated_secs = ?1, started_at = COALESCE(?2, started_at) WHERE id = ?3",
+    )
+    .bind(accumulated)
+    .bind(running_since)
+    .bind(timer_id)
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(())
 }
 
+/// Start a timer on `issue_key`. In exclusive mode every running timer is stopped
+/// and saved up to the new start. `started_at` back-dates the start for when the timer was
+/// started late; `name` labels it among parallel timers.
 #[tauri::command]
 pub async fn timer_start(
     state: State<'_, AppState>,
     issue_key: String,
+    started_at: Option<String>,
+    name: Option<String>,
 ) -> Result<TimerState, String> {
     let mut tx = state.db.begin().await.map_err(|e| e.to_string())?;
 
-    // Stop existing timer if running (reuses shared logic)
-    stop_and_save(&mut *tx).await?;
+    let now = Utc::now();
+    let start = match started_at {
+        Some(ref s) => parse_instant(s)?,
+        None => now,
+    };
+    if start > now {
+        return Err("The timer cannot start in the future".to_string());
+    }
+    // Parallel timers overlap other tracked time by design
+    if exclusive(&mut tx).await? {
+        hand_over_at(&mut tx, start, now).await?;
+    }
 
-    let now = Utc::now().to_rfc3339();
-    sqlx::query(
-        "INSERT OR REPLACE INTO active_timer (id, issue_key, started_at, accumulated_secs, is_paused, paused_at, description) \
-         VALUES (1, ?1, ?2, 0, 0, NULL, '')",
+    let start = start.to_rfc3339();
+    let id = sqlx::query(
+        "INSERT INTO active_timer (name, issue_key, started_at, accumulated_secs, is_paused, paused_at, description) \
+         VALUES (?1, ?2, ?3, 0, 0, NULL, '')",
     )
+    .bind(name.unwrap_or_default())
     .bind(&issue_key)
-    .bind(&now)
+    .bind(&start)
     .execute(&mut *tx)
     .await
+    .map_err(|e| e.to_string())?
+    .last_insert_rowid();
+    open_segment(&mut tx, id, &start).await?;
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+    require_state(&state.db, id).await
+}
+
+/// Correct when a timer started: either move its first start to `started_at`,
+/// or add (or with a negative value, remove) `add_seconds` of tracked time at
+/// the beginning. In exclusive mode time newly claimed may not overlap an
+/// existing worklog.
+#[tauri::command]
+pub async fn timer_adjust(
+    state: State<'_, AppState>,
+    timer_id: Option<i64>,
+    started_at: Option<String>,
+    add_seconds: Option<i64>,
+) -> Result<TimerState, String> {
+    let mut tx = state.db.begin().await.map_err(|e| e.to_string())?;
+    let timer_id = require_timer(&mut tx, timer_id).await?;
+    let first: (i64, String, Option<String>) = sqlx::query_as(
+        "SELECT id, started_at, ended_at FROM timer_segments WHERE timer_id = ?1 ORDER BY id LIMIT 1",
+    )
+    .bind(timer_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?
+    .ok_or("The timer has no recorded time to adjust")?;
+    let (segment_id, first_start, first_end) = first;
+    let first_start = parse_instant(&first_start)?;
+
+    let now = Utc::now();
+    let new_start = match (started_at, add_seconds) {
+        (Some(s), None) => parse_instant(&s)?,
+        (None, Some(secs)) => first_start - chrono::Duration::seconds(secs),
+        _ => return Err("Give either a new start time or the seconds to add".to_string()),
+    };
+    let first_end = match first_end {
+        Some(end) => parse_instant(&end)?,
+        None => now,
+    };
+    if new_start > now {
+        return Err("The timer cannot start in the future".to_string());
+    }
+    if new_start >= first_end {
+        return Err("That would remove all of the timer's first run".to_string());
+    }
+    if new_start < first_start && exclusive(&mut tx).await? {
+        check_no_overlap(&mut tx, new_start, first_start).await?;
+    }
+
+    sqlx::query("UPDATE timer_segments SET started_at = ?1 WHERE id = ?2")
+        .bind(new_start.to_rfc3339())
+        .bind(segment_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    refresh_totals(&mut tx, timer_id).await?;
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    require_state(&state.db, timer_id).await
+}
+
+#[derive(Debug, Serialize)]
+pub struct TimerSwitch {
+    /// Worklogs saved for the timer that was switched away from
+    pub saved: Vec<StoppedWorklog>,
+    pub timer: TimerState,
+}
+
+/// Hand a running timer over to `issue_key` as of `switch_at` (default now):
+/// the old timer is saved up to that moment and the new one, under the same
+/// name, runs from it. `keep_description` carries the old description over
+/// instead of starting blank.
+#[tauri::command]
+pub async fn timer_switch(
+    state: State<'_, AppState>,
+    timer_id: Option<i64>,
+    issue_key: String,
+    switch_at: Option<String>,
+    keep_description: bool,
+) -> Result<TimerSwitch, String> {
+    let mut tx = state.db.begin().await.map_err(|e| e.to_string())?;
+    let timer_id = require_timer(&mut tx, timer_id).await?;
+    let (name, description): (String, String) =
+        sqlx::query_as("SELECT name, description FROM active_timer WHERE id = ?1")
+            .bind(timer_id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+    let first_start: Option<String> = sqlx::query_scalar(
+        "SELECT started_at FROM timer_segments WHERE timer_id = ?1 ORDER BY id LIMIT 1",
+    )
+    .bind(timer_id)
+    .fetch_optional(&mut *tx)
+    .await
     .map_err(|e| e.to_string())?;
 
+    let now = Utc::now();
+    let switch_at = match switch_at {
+        Some(ref s) => parse_instant(s)?,
+        None => now,
+    };
+    if switch_at > now {
+        return Err("The switch time cannot be in the future".to_string());
+    }
+    if let Some(first_start) = first_start {
+        if switch_at < parse_instant(&first_start)? {
+            return Err("The switch time is before the current timer started".to_string());
+        }
+    }
+    // Checked before saving the old timer, whose rounded worklog may reach past the switch
+    if switch_at < now && exclusive(&mut tx).await? {
+        check_no_overlap(&mut tx, switch_at, now).await?;
+    }
+
+    let saved = stop_and_save_at(&mut tx, timer_id, switch_at)
+        .await?
+        .unwrap_or_default();
+
+    let start = switch_at.to_rfc3339();
+    let description = if keep_description { description } else { String::new() };
+    let id = sqlx::query(
+        "INSERT INTO active_timer (name, issue_key, started_at, accumulated_secs, is_paused, paused_at, description) \
+         VALUES (?1, ?2, ?3, 0, 0, NULL, ?4)",
+    )
+    .bind(&name)
+    .bind(&issue_key)
+    .bind(&start)
+    .bind(&description)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?
+    .last_insert_rowid();
+    open_segment(&mut tx, id, &start).await?;
     tx.commit().await.map_err(|e| e.to_string())?;
 
-    Ok(TimerState {
-        issue_key,
-        started_at: now,
-        accumulated_secs: 0,
-        is_paused: false,
-        paused_at: None,
-        description: String::new(),
+    Ok(TimerSwitch {
+        saved,
+        timer: require_state(&state.db, id).await?,
     })
 }
 
 #[tauri::command]
 pub async fn timer_pause(
     state: State<'_, AppState>,
+    timer_id: Option<i64>,
 ) -> Result<TimerState, String> {
-    let row: (String, String, i64, bool, Option<String>, String) = sqlx::query_as(
-        "SELECT issue_key, started_at, accumulated_secs, is_paused, paused_at, description FROM active_timer WHERE id = 1",
+    let mut tx = state.db.begin().await.map_err(|e| e.to_string())?;
+    let timer_id = require_timer(&mut tx, timer_id).await?;
+    let (started_at, accumulated, is_paused): (String, i64, bool) = sqlx::query_as(
+        "SELECT started_at, accumulated_secs, is_paused FROM active_timer WHERE id = ?1",
     )
-    .fetch_optional(&state.db)
+    .bind(timer_id)
+    .fetch_one(&mut *tx)
     .await
-    .map_err(|e| e.to_string())?
-    .ok_or("No active timer")?;
-
-    let (issue_key, started_at, accumulated, is_paused, _, description) = row;
+    .map_err(|e| e.to_string())?;
     if is_paused {
         return Err("Timer is already paused".to_string());
     }
 
     let now = Utc::now();
-    let started = chrono::DateTime::parse_from_rfc3339(&started_at)
-        .map_err(|e| format!("Invalid started_at: {}", e))?;
-    let elapsed = (now - started.with_timezone(&Utc)).num_seconds();
-    let new_accumulated = accumulated + elapsed;
+    let elapsed = (now - parse_instant(&started_at)?).num_seconds();
     let now_str = now.to_rfc3339();
 
     sqlx::query(
-        "UPDATE active_timer SET accumulated_secs = ?1, is_paused = 1, paused_at = ?2 WHERE id = 1",
+        "UPDATE active_timer SET accumulated_secs = ?1, is_paused = 1, paused_at = ?2 WHERE id = ?3",
     )
-    .bind(new_accumulated)
+    .bind(accumulated + elapsed)
     .bind(&now_str)
-    .execute(&state.db)
+    .bind(timer_id)
+    .execute(&mut *tx)
     .await
     .map_err(|e| e.to_string())?;
+    close_segment(&mut tx, timer_id, &now_str).await?;
+    tx.commit().await.map_err(|e| e.to_string())?;
 
-    Ok(TimerState {
-        issue_key,
-        started_at,
-        accumulated_secs: new_accumulated,
-        is_paused: true,
-        paused_at: Some(now_str),
-        description,
-    })
+    require_state(&state.db, timer_id).await
 }
 
 #[tauri::command]
 pub async fn timer_resume(
     state: State<'_, AppState>,
+    timer_id: Option<i64>,
 ) -> Result<TimerState, String> {
-    let row: (String, String, i64, bool, Option<String>, String) = sqlx::query_as(
-        "SELECT issue_key, started_at, accumulated_secs, is_paused, paused_at, description FROM active_timer WHERE id = 1",
-    )
-    .fetch_optional(&state.db)
-    .await
-    .map_err(|e| e.to_string())?
-    .ok_or("No active timer")?;
-
-    let (issue_key, _, accumulated, is_paused, _, description) = row;
+    let mut tx = state.db.begin().await.map_err(|e| e.to_string())?;
+    let timer_id = require_timer(&mut tx, timer_id).await?;
+    let is_paused: bool = sqlx::query_scalar("SELECT is_paused FROM active_timer WHERE id = ?1")
+        .bind(timer_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
     if !is_paused {
         return Err("Timer is not paused".to_string());
     }
 
     let now = Utc::now().to_rfc3339();
-
     sqlx::query(
-        "UPDATE active_timer SET started_at = ?1, is_paused = 0, paused_at = NULL WHERE id = 1",
+        "UPDATE active_timer SET started_at = ?1, is_paused = 0, paused_at = NULL WHERE id = ?2",
     )
     .bind(&now)
-    .execute(&state.db)
+    .bind(timer_id)
+    .execute(&mut *tx)
     .await
     .map_err(|e| e.to_string())?;
+    open_segment(&mut tx, timer_id, &now).await?;
+    tx.commit().await.map_err(|e| e.to_string())?;
 
-    Ok(TimerState {
-        issue_key,
-        started_at: now,
-        accumulated_secs: accumulated,
-        is_paused: false,
-        paused_at: None,
-        description,
-    })
+    require_state(&state.db, timer_id).await
 }
 
 #[tauri::command]
 pub async fn timer_stop(
     state: State<'_, AppState>,
-) -> Result<StoppedWorklog, String> {
+    timer_id: Option<i64>,
+) -> Result<Vec<StoppedWorklog>, String> {
     let mut tx = state.db.begin().await.map_err(|e| e.to_string())?;
-    let result = stop_and_save(&mut *tx)
+    let timer_id = require_timer(&mut tx, timer_id).await?;
+    let result = stop_and_save_at(&mut tx, timer_id, Utc::now())
         .await?
         .ok_or("No active timer")?;
     tx.commit().await.map_err(|e| e.to_string())?;
     Ok(result)
 }
 
-#[tauri::command]
-pub async fn timer_update_tray(
-    app_handle: tauri::AppHandle,
-    display_text: String,
-) -> Result<(), String> {
-    if let Some(tray) = app_handle.tray_by_id("main-tray") {
-        tray.set_title(Some(&display_text)).map_err(|e| e.to_string())?;
-    }
-    Ok(())
-}
-
 #[tauri::command]
 pub async fn timer_update_description(
     state: State<'_, AppState>,
+    timer_id: Option<i64>,
     description: String,
 ) -> Result<(), String> {
-    sqlx::query("UPDATE active_timer SET description = ?1 WHERE id = 1")
+    let mut conn = state.db.acquire().await.map_err(|e| e.to_string())?;
+    let timer_id = require_timer(&mut conn, timer_id).await?;
+    sqlx::query("UPDATE active_timer SET description = ?1 WHERE id = ?2")
         .bind(&description)
-        .execute(&state.db)
+        .bind(timer_id)
+        .execute(&mut *conn)
         .await
         .map_err(|e| e.to_string())?;
     Ok(())
 }
 
 #[tauri::command]
-pub async fn timer_set_tray_icon(
-    app_handle: tauri::AppHandle,
-    icon_name: String,
-) -> Result<(), String> {
-    let icon_bytes: &[u8] = match icon_name.as_str() {
+pub async fn timer_rename(
+    state: State<'_, AppState>,
+    timer_id: i64,
+    name: String,
+) -> Result<TimerState, String> {
+    sqlx::query("UPDATE active_timer SET name = ?1 WHERE id = ?2")
+        .bind(name.trim())
+        .bind(timer_id)
+        .execute(&state.db)
+        .await
+        .map_err(|e| e.to_string())?;
+    require_state(&state.db, timer_id).await
+}
+
+/// Show the `working`, `paused` or idle tray icon.
+pub(crate) fn set_tray_icon(app_handle: &tauri::AppHandle, icon_name: &str) -> Result<(), String> {
+    let icon_bytes: &[u8] = match icon_name {
         "working" => include_bytes!("../../icons/tray-working.png"),
         "paused" => include_bytes!("../../icons/tray-paused.png"),
         _ => include_bytes!("../../icons/tray-idle.png"),
@@ -278,3 +780,297 @@ pub async fn timer_set_tray_icon(
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::FixedOffset;
+
+    fn at(tz: &FixedOffset, day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        tz.with_ymd_and_hms(2024, 3, day, hour, minute, 0)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 3, day).unwrap()
+    }
+
+    #[test]
+    fn splits_overnight_segments_at_midnight() {
+        let tz = FixedOffset::east_opt(2 * 3600).unwrap();
+        let segments = [
+            (at(&tz, 4, 22, 0), at(&tz, 4, 23, 0)),
+            // Paused 23:00–23:30, then ran overnight
+            (at(&tz, 4, 23, 30), at(&tz, 5, 1, 15)),
+        ];
+
+        let pieces = split_by_day(&segments, NaiveTime::MIN, &tz);
+
+        assert_eq!(
+            pieces,
+            vec![
+                DayPiece { day: date(4), started: at(&tz, 4, 22, 0), seconds: 90 * 60 },
+                DayPiece { day: date(5), started: at(&tz, 5, 0, 0), seconds: 75 * 60 },
+            ]
+        );
+    }
+
+    #[test]
+    fn later_day_boundary_keeps_late_night_on_the_previous_day() {
+        let tz = FixedOffset::west_opt(5 * 3600).unwrap();
+        let boundary = NaiveTime::from_hms_opt(4, 0, 0).unwrap();
+        let segments = [(at(&tz, 4, 23, 0), at(&tz, 5, 5, 0))];
+
+        let pieces = split_by_day(&segments, boundary, &tz);
+
+        assert_eq!(
+            pieces,
+            vec![
+                DayPiece { day: date(4), started: at(&tz, 4, 23, 0), seconds: 5 * 3600 },
+                DayPiece { day: date(5), started: at(&tz, 5, 4, 0), seconds: 3600 },
+            ]
+        );
+    }
+
+    #[test]
+    fn per_segment_mode_keeps_each_run_at_its_real_time() {
+        let tz = FixedOffset::east_opt(0).unwrap();
+        let segments = [
+            (at(&tz, 4, 9, 0), at(&tz, 4, 10, 0)),
+            (at(&tz, 4, 13, 0), at(&tz, 4, 13, 30)),
+            (at(&tz, 4, 23, 30), at(&tz, 5, 0, 30)),
+        ];
+
+        let per_day = worklog_pieces(&segments, NaiveTime::MIN, StopMode::PerDay, &tz);
+        let per_segment = worklog_pieces(&segments, NaiveTime::MIN, StopMode::PerSegment, &tz);
+
+        let summary = |pieces: &[DayPiece]| -> Vec<(DateTime<Utc>, i64)> {
+            pieces.iter().map(|p| (p.started, p.seconds / 60)).collect()
+        };
+        assert_eq!(summary(&per_day), vec![(at(&tz, 4, 9, 0), 120), (at(&tz, 5, 0, 0), 30)]);
+        assert_eq!(
+            summary(&per_segment),
+            vec![
+                (at(&tz, 4, 9, 0), 60),
+                (at(&tz, 4, 13, 0), 30),
+                (at(&tz, 4, 23, 30), 30),
+                (at(&tz, 5, 0, 0), 30),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn stop_saves_one_worklog_per_day_from_recorded_segments() {
+        let db = crate::db::test_pool().await;
+        let local = |day: u32, hour: u32| {
+            Local
+                .from_local_datetime(&date(day).and_hms_opt(hour, 0, 0).unwrap())
+                .earliest()
+                .unwrap()
+                .to_rfc3339()
+        };
+        sqlx::query(
+            "INSERT INTO active_timer (id, issue_key, started_at, accumulated_secs, is_paused, paused_at, description) \
+             VALUES (1, 'SUP-1', ?1, 7200, 1, ?2, 'Overnight deploy')",
+        )
+        .bind(local(5, 1))
+        .bind(local(5, 1))
+        .execute(&db)
+        .await
+        .unwrap();
+        sqlx::query("INSERT INTO timer_segments (timer_id, started_at, ended_at) VALUES (1, ?1, ?2)")
+            .bind(local(4, 23))
+            .bind(local(5, 1))
+            .execute(&db)
+            .await
+            .unwrap();
+
+        let mut conn = db.acquire().await.unwrap();
+        let saved = stop_and_save_at(&mut conn, 1, Utc::now()).await.unwrap().unwrap();
+
+        let got: Vec<(&str, i64)> = saved
+            .iter()
+            .map(|w| (w.started_at.as_str(), w.duration_seconds))
+            .collect();
+        let (day_one, day_two) = (local(4, 23), local(5, 0));
+        assert_eq!(got, vec![(day_one.as_str(), 3600), (day_two.as_str(), 3600)]);
+        let left: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM timer_segments")
+            .fetch_one(&mut *conn)
+            .await
+            .unwrap();
+        assert_eq!(left, 0);
+    }
+
+    #[tokio::test]
+    async fn back_dated_time_may_not_overlap_worklogs_and_totals_follow_segments() {
+        let db = crate::db::test_pool().await;
+        let tz = FixedOffset::east_opt(2 * 3600).unwrap();
+        // 09:00–10:00 local, stored with its offset
+        sqlx::query(
+            "INSERT INTO worklogs (issue_key, started_at, duration_seconds, description, sync_status, created_at, updated_at) \
+             VALUES ('SUP-9', ?1, 3600, '', 'pending', '', '')",
+        )
+        .bind(at(&tz, 4, 9, 0).with_timezone(&tz).to_rfc3339())
+        .execute(&db)
+        .await
+        .unwrap();
+        let mut conn = db.acquire().await.unwrap();
+
+        let err = check_no_overlap(&mut conn, at(&tz, 4, 9, 45), at(&tz, 4, 11, 0))
+            .await
+            .unwrap_err();
+        assert!(err.contains("SUP-9"), "{}", err);
+        // Touching the end is fine
+        check_no_overlap(&mut conn, at(&tz, 4, 10, 0), at(&tz, 4, 11, 0))
+            .await
+            .unwrap();
+
+        sqlx::query(
+            "INSERT INTO active_timer (id, issue_key, started_at, accumulated_secs, is_paused, paused_at, description) \
+             VALUES (1, 'SUP-1', ?1, 0, 0, NULL, '')",
+        )
+        .bind(at(&tz, 4, 12, 0).to_rfc3339())
+        .execute(&mut *conn)
+        .await
+        .unwrap();
+        for (started, ended) in [
+            (at(&tz, 4, 10, 30), Some(at(&tz, 4, 11, 0))),
+            (at(&tz, 4, 12, 0), None),
+        ] {
+            sqlx::query("INSERT INTO timer_segments (timer_id, started_at, ended_at) VALUES (1, ?1, ?2)")
+                .bind(started.to_rfc3339())
+                .bind(ended.map(|e| e.to_rfc3339()))
+                .execute(&mut *conn)
+                .await
+                .unwrap();
+        }
+        refresh_totals(&mut conn, 1).await.unwrap();
+
+        let (started_at, accumulated): (String, i64) =
+            sqlx::query_as("SELECT started_at, accumulated_secs FROM active_timer WHERE id = 1")
+                .fetch_one(&mut *conn)
+                .await
+                .unwrap();
+        assert_eq!(accumulated, 1800);
+        assert_eq!(parse_instant(&started_at).unwrap(), at(&tz, 4, 12, 0));
+    }
+
+    #[tokio::test]
+    async fn stopping_at_an_earlier_time_drops_what_was_tracked_after_it() {
+        let db = crate::db::test_pool().await;
+        let local = |hour: u32, minute: u32| {
+            Local
+                .from_local_datetime(&date(4).and_hms_opt(hour, minute, 0).unwrap())
+                .earliest()
+                .unwrap()
+                .with_timezone(&Utc)
+        };
+        sqlx::query(
+            "INSERT INTO active_timer (id, issue_key, started_at, accumulated_secs, is_paused, paused_at, description) \
+             VALUES (1, 'SUP-1', ?1, 3600, 0, NULL, '')",
+        )
+        .bind(local(11, 0).to_rfc3339())
+        .execute(&db)
+        .await
+        .unwrap();
+        for (started, ended) in [
+            (local(9, 0), Some(local(10, 0))),
+            (local(10, 30), Some(local(10, 50))),
+            (local(11, 0), None),
+        ] {
+            sqlx::query("INSERT INTO timer_segments (timer_id, started_at, ended_at) VALUES (1, ?1, ?2)")
+                .bind(started.to_rfc3339())
+                .bind(ended.map(|e| e.to_rfc3339()))
+                .execute(&db)
+                .await
+                .unwrap();
+        }
+
+        let mut conn = db.acquire().await.unwrap();
+        let saved = stop_and_save_at(&mut conn, 1, local(10, 40)).await.unwrap().unwrap();
+
+        assert_eq!(saved.len(), 1);
+        assert_eq!(saved[0].duration_seconds, 70 * 60);
+        assert_eq!(parse_instant(&saved[0].started_at).unwrap(), local(9, 0));
+    }
+
+    #[tokio::test]
+    async fn back_dated_start_takes_over_the_running_timer_at_its_start() {
+        let db = crate::db::test_pool().await;
+        let tz = FixedOffset::east_opt(3600).unwrap();
+        sqlx::query(
+            "INSERT INTO worklogs (issue_key, started_at, duration_seconds, description, sync_status) \
+             VALUES ('SUP-9', ?1, 3600, '', 'pending')",
+        )
+        .bind(at(&tz, 4, 8, 0).with_timezone(&tz).to_rfc3339())
+        .execute(&db)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO active_timer (id, issue_key, started_at, accumulated_secs, is_paused, paused_at, description) \
+             VALUES (1, 'SUP-1', ?1, 0, 0, NULL, '')",
+        )
+        .bind(at(&tz, 4, 9, 30).to_rfc3339())
+        .execute(&db)
+        .await
+        .unwrap();
+        sqlx::query("INSERT INTO timer_segments (timer_id, started_at) VALUES (1, ?1)")
+            .bind(at(&tz, 4, 9, 30).to_rfc3339())
+            .execute(&db)
+            .await
+            .unwrap();
+        let mut conn = db.acquire().await.unwrap();
+        let now = at(&tz, 4, 11, 0);
+
+        assert!(hand_over_at(&mut conn, at(&tz, 4, 8, 30), now).await.unwrap_err().contains("SUP-9"));
+        assert!(hand_over_at(&mut conn, at(&tz, 4, 9, 0), now).await.is_err());
+
+        let saved = hand_over_at(&mut conn, at(&tz, 4, 10, 0), now).await.unwrap();
+        assert_eq!((saved[0].issue_key.as_str(), saved[0].duration_seconds), ("SUP-1", 1800));
+        assert_eq!(resolve_timer(&mut conn, None).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn parallel_timers_keep_their_own_segments() {
+        let db = crate::db::test_pool().await;
+        let tz = FixedOffset::east_opt(0).unwrap();
+        for (id, issue_key, started) in [(1, "OPS-1", at(&tz, 4, 9, 0)), (2, "SUP-2", at(&tz, 4, 9, 30))] {
+            sqlx::query(
+                "INSERT INTO active_timer (id, issue_key, started_at, accumulated_secs, is_paused, paused_at, description) \
+                 VALUES (?1, ?2, ?3, 0, 0, NULL, '')",
+            )
+            .bind(id)
+            .bind(issue_key)
+            .bind(started.to_rfc3339())
+            .execute(&db)
+            .await
+            .unwrap();
+            sqlx::query("INSERT INTO timer_segments (timer_id, started_at) VALUES (?1, ?2)")
+                .bind(id)
+                .bind(started.to_rfc3339())
+                .execute(&db)
+                .await
+                .unwrap();
+        }
+        let mut conn = db.acquire().await.unwrap();
+
+        assert!(resolve_timer(&mut conn, None).await.is_err());
+        assert!(resolve_timer(&mut conn, Some(3)).await.is_err());
+        assert_eq!(resolve_timer(&mut conn, Some(2)).await.unwrap(), Some(2));
+
+        let ops = stop_and_save_at(&mut conn, 1, at(&tz, 4, 10, 0)).await.unwrap().unwrap();
+        assert_eq!((ops[0].issue_key.as_str(), ops[0].duration_seconds), ("OPS-1", 3600));
+        // The other timer is untouched and is now the only one
+        assert_eq!(resolve_timer(&mut conn, None).await.unwrap(), Some(2));
+        drop(conn);
+        let timer = load_state(&db, 2).await.unwrap().unwrap();
+        assert_eq!(timer.segments.len(), 1);
+        assert!(timer.segments[0].ended_at.is_none());
+        let mut conn = db.acquire().await.unwrap();
+
+        let rest = stop_all_at(&mut conn, at(&tz, 4, 10, 0)).await.unwrap();
+        assert_eq!((rest[0].issue_key.as_str(), rest[0].duration_seconds), ("SUP-2", 1800));
+        assert_eq!(resolve_timer(&mut conn, None).await.unwrap(), None);
+    }
+}
@@ -1,4 +1,7 @@
+use crate::rounding;
 use crate::state::AppState;
+use crate::sync::{self, SyncOperation};
+use crate::tray_menu;
 use serde::{Deserialize, Serialize};
 use tauri::State;
 
@@ -8,6 +11,8 @@ pub struct Worklog {
     pub issue_key: String,
     pub started_at: String,
     pub duration_seconds: i64,
+    /// Tracked time before rounding; `None` for worklogs that came from Jira
+    pub raw_duration_seconds: Option<i64>,
     pub description: String,
     pub sync_status: String,
     pub jira_worklog_id: Option<String>,
@@ -84,18 +89,24 @@ pub async fn create_worklog(
     description: Option<String>,
 ) -> Result<Worklog, String> {
     let desc = description.unwrap_or_default();
+    let rounded = rounding::load(&state.db)
+        .await
+        .map_err(|e| e.to_string())?
+        .apply(duration_seconds);
 
     let result = sqlx::query(
-        "INSERT INTO worklogs (issue_key, started_at, duration_seconds, description, sync_status) \
-         VALUES (?1, ?2, ?3, ?4, 'pending')",
+        "INSERT INTO worklogs (issue_key, started_at, duration_seconds, raw_duration_seconds, description, sync_status) \
+         VALUES (?1, ?2, ?3, ?4, ?5, 'pending')",
     )
     .bind(&issue_key)
     .bind(&started_at)
+    .bind(rounded)
     .bind(duration_seconds)
     .bind(&desc)
     .execute(&state.db)
     .await
     .map_err(|e| e.to_string())?;
+    tray_menu::invalidate(&state);
 
     let id = result.last_insert_rowid();
 
@@ -119,24 +130,34 @@ pub async fn update_worklog(
     started_at: Option<String>,
 ) -> Result<Worklog, String> {
     // Check status
-    let (sync_status,): (String,) =
-        sqlx::query_as("SELECT sync_status FROM worklogs WHERE id = ?1")
+    let (sync_status, cur_issue_key, jira_worklog_id): (String, String, Option<String>) =
+        sqlx::query_as("SELECT sync_status, issue_key, jira_worklog_id FROM worklogs WHERE id = ?1")
             .bind(id)
             .fetch_optional(&state.db)
             .await
             .map_err(|e| e.to_string())?
             .ok_or("Worklog not found")?;
 
-    if sync_status == "synced" {
-        return Err("Cannot edit a synced worklog".to_string());
+    // Synced worklogs are edited offline and replayed to Jira by the sync worker
+    let in_jira = sync_status == "synced" || sync_status == "modified";
+    match sync_status.as_str() {
+        "pending_delete" => return Err("Worklog is queued for deletion".to_string()),
+        "conflict" => return Err("Resolve the sync conflict before editing this worklog".to_string()),
+        _ => {}
+    }
+    if in_jira && issue_key.as_ref().is_some_and(|k| *k != cur_issue_key) {
+        return Err("Cannot move a synced worklog to another issue".to_string());
     }
 
     let mut set_clauses = Vec::new();
     let mut binds: Vec<String> = Vec::new();
 
     if let Some(dur) = duration_seconds {
-        binds.push(dur.to_string());
+        let policy = rounding::load(&state.db).await.map_err(|e| e.to_string())?;
+        binds.push(policy.apply(dur).to_string());
         set_clauses.push(format!("duration_seconds = ?{}", binds.len()));
+        binds.push(dur.to_string());
+        set_clauses.push(format!("raw_duration_seconds = ?{}", binds.len()));
     }
     if let Some(ref desc) = description {
         binds.push(desc.clone());
@@ -154,12 +175,14 @@ pub async fn update_worklog(
     if !set_clauses.is_empty() {
         // Reset error state in the same UPDATE
         set_clauses.push("updated_at = datetime('now')".to_string());
-        set_clauses.push(format!(
-            "sync_status = CASE WHEN sync_status = 'error' THEN 'pending' ELSE sync_status END"
-        ));
-        set_clauses.push(format!(
+        set_clauses.push(
+            "sync_status = CASE sync_status WHEN 'error' THEN 'pending' WHEN 'synced' THEN 'modified' ELSE sync_status END"
+                .to_string(),
+        );
+        set_clauses.push(
             "sync_error = CASE WHEN sync_status = 'error' THEN NULL ELSE sync_error END"
-        ));
+                .to_string(),
+        );
 
         binds.push(id.to_string());
         let sql = format!(
@@ -172,7 +195,24 @@ pub async fn update_worklog(
         for bind in &binds {
             query = query.bind(bind);
         }
-        query.execute(&state.db).await.map_err(|e| e.to_string())?;
+        let mut tx = state.db.begin().await.map_err(|e| e.to_string())?;
+        query.execute(&mut *tx).await.map_err(|e| e.to_string())?;
+        if in_jira {
+            sync::enqueue(
+                &mut *tx,
+                id,
+                SyncOperation::Update,
+                &cur_issue_key,
+                jira_worklog_id.as_deref(),
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+        }
+        tx.commit().await.map_err(|e| e.to_string())?;
+        tray_menu::invalidate(&state);
+        if in_jira {
+            state.sync.wake();
+        }
     }
 
     sqlx::query_as::<_, Worklog>(
@@ -190,16 +230,36 @@ pub async fn delete_worklog(
     state: State<'_, AppState>,
     id: i64,
 ) -> Result<(), String> {
-    let (sync_status,): (String,) =
-        sqlx::query_as("SELECT sync_status FROM worklogs WHERE id = ?1")
+    let (sync_status, issue_key, jira_worklog_id): (String, String, Option<String>) =
+        sqlx::query_as("SELECT sync_status, issue_key, jira_worklog_id FROM worklogs WHERE id = ?1")
             .bind(id)
             .fetch_optional(&state.db)
             .await
             .map_err(|e| e.to_string())?
             .ok_or("Worklog not found")?;
 
-    if sync_status == "synced" {
-        return Err("Cannot delete a synced worklog".to_string());
+    match sync_status.as_str() {
+        // Keep the row until the sync worker has deleted it in Jira
+        "synced" | "modified" => {
+            let mut tx = state.db.begin().await.map_err(|e| e.to_string())?;
+            sqlx::query(
+                "UPDATE worklogs SET sync_status = 'pending_delete', updated_at = datetime('now') WHERE id = ?1",
+            )
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+            sync::enqueue(&mut *tx, id, SyncOperation::Delete, &issue_key, jira_worklog_id.as_deref())
+                .await
+                .map_err(|e| e.to_string())?;
+            tx.commit().await.map_err(|e| e.to_string())?;
+            tray_menu::invalidate(&state);
+            state.sync.wake();
+            return Ok(());
+        }
+        "pending_delete" => return Ok(()),
+        "conflict" => return Err("Resolve the sync conflict before deleting this worklog".to_string()),
+        _ => {}
     }
 
     sqlx::query("DELETE FROM worklogs WHERE id = ?1")
@@ -207,6 +267,7 @@ pub async fn delete_worklog(
         .execute(&state.db)
         .await
         .map_err(|e| e.to_string())?;
+    tray_menu::invalidate(&state);
 
     Ok(())
 }
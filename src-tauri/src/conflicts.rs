@@ -0,0 +1,569 @@
+//! Three-way comparison of a synced worklog: the snapshot we last agreed on with
+//! Jira (the `base_*` columns), the local row, and what Jira has now.
+//!
+//! A field changed on one side only is taken from that side. When both sides
+//! changed the same field differently, the worklog is marked `conflict` and
+//! Jira's version is parked in `worklog_conflicts` until the user resolves it.
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+use crate::jira::client::comment_text;
+use crate::jira::types::JiraWorklogEntry;
+use crate::sync::{self, SyncOperation};
+
+/// The parts of a worklog that are synced with Jira.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct WorklogFields {
+    pub started_at: String,
+    pub duration_seconds: i64,
+    pub description: String,
+}
+
+impl WorklogFields {
+    /// Jira's values, with `started` converted to local RFC 3339 like imported rows.
+    pub fn from_jira(entry: &JiraWorklogEntry) -> Option<Self> {
+        let started = DateTime::parse_from_str(&entry.started, "%Y-%m-%dT%H:%M:%S%.3f%z").ok()?;
+        Some(Self {
+            started_at: started.with_timezone(&Local).to_rfc3339(),
+            duration_seconds: entry.time_spent_seconds,
+            description: entry.comment.as_ref().map(comment_text).unwrap_or_default(),
+        })
+    }
+
+    pub fn same_as(&self, other: &Self) -> bool {
+        same_start(&self.started_at, &other.started_at)
+            && self.duration_seconds == other.duration_seconds
+            && self.description == other.description
+    }
+}
+
+/// Start times compared as instants; local rows and Jira spell them differently.
+fn same_start(a: &str, b: &str) -> bool {
+    match (DateTime::parse_from_rfc3339(a), DateTime::parse_from_rfc3339(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+#[derive(Debug)]
+pub enum Merge {
+    /// Nothing local to send; the row should take Jira's values
+    TakeRemote,
+    /// Local changes, possibly combined with Jira's, that still need pushing
+    Push(WorklogFields),
+    /// Both sides changed the same field
+    Conflict,
+}
+
+/// Merge `local` and `remote` against the last-synced `base`. Without a base
+/// the two sides can only be told apart when they are equal.
+pub fn three_way(base: Option<&WorklogFields>, local: &WorklogFields, remote: &WorklogFields) -> Merge {
+    if local.same_as(remote) {
+        return Merge::TakeRemote;
+    }
+    let Some(base) = base else {
+        return Merge::Conflict;
+    };
+    let started_at = merge_field(&base.started_at, &local.started_at, &remote.started_at, |a, b| same_start(a, b));
+    let duration_seconds = merge_field(&base.duration_seconds, &local.duration_seconds, &remote.duration_seconds, |a, b| a == b);
+    let description = merge_field(&base.description, &local.description, &remote.description, |a, b| a == b);
+    match (started_at, duration_seconds, description) {
+        (Some(started_at), Some(duration_seconds), Some(description)) => {
+            let merged = WorklogFields {
+                started_at,
+                duration_seconds,
+                description,
+            };
+            if merged.same_as(remote) {
+                Merge::TakeRemote
+            } else {
+                Merge::Push(merged)
+            }
+        }
+        _ => Merge::Conflict,
+    }
+}
+
+fn merge_field<T: Clone>(base: &T, local: &T, remote: &T, eq: impl Fn(&T, &T) -> bool) -> Option<T> {
+    if eq(local, base) {
+        Some(remote.clone())
+    } else if eq(remote, base) || eq(local, remote) {
+        Some(local.clone())
+    } else {
+        None
+    }
+}
+
+/// Both descriptions in one, without repeating text one side already contains.
+pub fn merge_descriptions(local: &str, remote: &str) -> String {
+    let (l, r) = (local.trim(), remote.trim());
+    if r.is_empty() || l.contains(r) {
+        local.to_string()
+    } else if l.is_empty() || r.contains(l) {
+        remote.to_string()
+    } else {
+        format!("{}\n\n{}", l, r)
+    }
+}
+
+/// A synced worklog as the merge sees it.
+#[derive(sqlx::FromRow)]
+pub struct SyncedRow {
+    pub issue_key: String,
+    pub jira_worklog_id: Option<String>,
+    pub sync_status: String,
+    pub started_at: String,
+    pub duration_seconds: i64,
+    /// Tracked time behind a duration edited here, before rounding
+    pub raw_duration_seconds: Option<i64>,
+    pub description: String,
+    pub jira_updated_at: Option<String>,
+    pub base_started_at: Option<String>,
+    pub base_duration_seconds: Option<i64>,
+    pub base_description: Option<String>,
+}
+
+impl SyncedRow {
+    pub const COLUMNS: &'static str = "issue_key, jira_worklog_id, sync_status, started_at, duration_seconds, \
+         raw_duration_seconds, description, jira_updated_at, base_started_at, base_duration_seconds, base_description";
+
+    pub fn local(&self) -> WorklogFields {
+        WorklogFields {
+            started_at: self.started_at.clone(),
+            duration_seconds: self.duration_seconds,
+            description: self.description.clone(),
+        }
+    }
+
+    pub fn base(&self) -> Option<WorklogFields> {
+        Some(WorklogFields {
+            started_at: self.base_started_at.clone()?,
+            duration_seconds: self.base_duration_seconds?,
+            description: self.base_description.clone()?,
+        })
+    }
+}
+
+pub async fn load_row(db: &SqlitePool, worklog_id: i64) -> Result<Option<SyncedRow>, sqlx::Error> {
+    sqlx::query_as(&format!("SELECT {} FROM worklogs WHERE id = ?1", SyncedRow::COLUMNS))
+        .bind(worklog_id)
+        .fetch_optional(db)
+        .await
+}
+
+/// Take `fields` as both the local values and the new base, and mark the row synced.
+pub async fn apply_synced<'c, A>(
+    db: A,
+    worklog_id: i64,
+    fields: &WorklogFields,
+    jira_updated_at: Option<&str>,
+) -> Result<(), sqlx::Error>
+where
+    A: sqlx::Acquire<'c, Database = sqlx::Sqlite>,
+{
+    let mut tx = db.begin().await?;
+    sqlx::query(
+        "UPDATE worklogs SET started_at = ?1, duration_seconds = ?2, description = ?3, \
+         base_started_at = ?1, base_duration_seconds = ?2, base_description = ?3, \
+         jira_updated_at = ?4, sync_status = 'synced', sync_error = NULL, updated_at = datetime('now') WHERE id = ?5",
+    )
+    .bind(&fields.started_at)
+    .bind(fields.duration_seconds)
+    .bind(&fields.description)
+    .bind(jira_updated_at)
+    .bind(worklog_id)
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query("DELETE FROM sync_outbox WHERE worklog_id = ?1")
+        .bind(worklog_id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await
+}
+
+/// Keep the local row untouched, park Jira's version (`None` when it was deleted
+/// there) and stop replaying the worklog until the user resolves it.
+pub async fn record_conflict(
+    db: &SqlitePool,
+    worklog_id: i64,
+    local_deleted: bool,
+    remote: Option<(&WorklogFields, &str)>,
+    reason: &str,
+) -> Result<(), sqlx::Error> {
+    let mut tx = db.begin().await?;
+    sqlx::query(
+        "INSERT OR REPLACE INTO worklog_conflicts \
+         (worklog_id, local_deleted, remote_started_at, remote_duration_seconds, remote_description, remote_updated_at, remote_deleted) \
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+    )
+    .bind(worklog_id)
+    .bind(local_deleted)
+    .bind(remote.map(|(f, _)| f.started_at.as_str()))
+    .bind(remote.map(|(f, _)| f.duration_seconds))
+    .bind(remote.map(|(f, _)| f.description.as_str()))
+    .bind(remote.map(|(_, updated)| updated))
+    .bind(remote.is_none())
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query(
+        "UPDATE worklogs SET sync_status = 'conflict', sync_error = ?1, updated_at = datetime('now') WHERE id = ?2",
+    )
+    .bind(reason)
+    .bind(worklog_id)
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query("DELETE FROM sync_outbox WHERE worklog_id = ?1")
+        .bind(worklog_id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportMerge {
+    /// The local row now has Jira's values
+    Updated,
+    /// Local changes were kept (and merged with Jira's where they don't overlap)
+    Kept,
+    Conflict,
+}
+
+/// Apply a change found in Jira during import to the local row holding `jira_worklog_id`.
+pub async fn merge_remote_change(
+    db: &SqlitePool,
+    worklog_id: i64,
+    row: &SyncedRow,
+    remote: &WorklogFields,
+    remote_updated: &str,
+) -> Result<ImportMerge, sqlx::Error> {
+    let local = row.local();
+    match row.sync_status.as_str() {
+        "pending_delete" => {
+            // Deleting is still what the user wants unless Jira's values moved on
+            if row.base().is_some_and(|base| base.same_as(remote)) {
+                set_jira_updated_at(db, worklog_id, remote_updated).await?;
+                Ok(ImportMerge::Kept)
+            } else {
+                record_conflict(db, worklog_id, true, Some((remote, remote_updated)), "Changed in Jira after it was deleted here").await?;
+                Ok(ImportMerge::Conflict)
+            }
+        }
+        // A synced row has no local intent: its own values are the base
+        status => {
+            let base = if status == "synced" { Some(local.clone()) } else { row.base() };
+            match three_way(base.as_ref(), &local, remote) {
+                Merge::TakeRemote => {
+                    apply_synced(db, worklog_id, remote, Some(remote_updated)).await?;
+                    Ok(ImportMerge::Updated)
+                }
+                Merge::Push(merged) => {
+                    let mut tx = db.begin().await?;
+                    sqlx::query(
+                        "UPDATE worklogs SET started_at = ?1, duration_seconds = ?2, description = ?3, \
+                         base_started_at = ?4, base_duration_seconds = ?5, base_description = ?6, \
+                         jira_updated_at = ?7, sync_status = 'modified', updated_at = datetime('now') WHERE id = ?8",
+                    )
+                    .bind(&merged.started_at)
+                    .bind(merged.duration_seconds)
+                    .bind(&merged.description)
+                    .bind(&remote.started_at)
+                    .bind(remote.duration_seconds)
+                    .bind(&remote.description)
+                    .bind(remote_updated)
+                    .bind(worklog_id)
+                    .execute(&mut *tx)
+                    .await?;
+                    sync::enqueue(&mut *tx, worklog_id, SyncOperation::Update, &row.issue_key, row.jira_worklog_id.as_deref()).await?;
+                    tx.commit().await?;
+                    Ok(ImportMerge::Kept)
+                }
+                Merge::Conflict => {
+                    record_conflict(db, worklog_id, false, Some((remote, remote_updated)), "Changed both here and in Jira").await?;
+                    Ok(ImportMerge::Conflict)
+                }
+            }
+        }
+    }
+}
+
+async fn set_jira_updated_at(db: &SqlitePool, worklog_id: i64, updated: &str) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE worklogs SET jira_updated_at = ?1 WHERE id = ?2")
+        .bind(updated)
+        .bind(worklog_id)
+        .execute(db)
+        .await?;
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WorklogConflict {
+    pub worklog_id: i64,
+    pub issue_key: String,
+    pub jira_worklog_id: Option<String>,
+    pub reason: Option<String>,
+    pub detected_at: String,
+    /// `None` when the worklog was deleted here
+    pub local: Option<WorklogFields>,
+    pub base: Option<WorklogFields>,
+    /// `None` when the worklog was deleted in Jira
+    pub remote: Option<WorklogFields>,
+}
+
+#[derive(sqlx::FromRow)]
+struct ConflictRow {
+    worklog_id: i64,
+    local_deleted: bool,
+    remote_started_at: Option<String>,
+    remote_duration_seconds: Option<i64>,
+    remote_description: Option<String>,
+    remote_updated_at: Option<String>,
+    remote_deleted: bool,
+    detected_at: String,
+}
+
+impl ConflictRow {
+    fn remote(&self) -> Option<WorklogFields> {
+        if self.remote_deleted {
+            return None;
+        }
+        Some(WorklogFields {
+            started_at: self.remote_started_at.clone()?,
+            duration_seconds: self.remote_duration_seconds?,
+            description: self.remote_description.clone().unwrap_or_default(),
+        })
+    }
+}
+
+const CONFLICT_COLUMNS: &str = "worklog_id, local_deleted, remote_started_at, remote_duration_seconds, \
+     remote_description, remote_updated_at, remote_deleted, detected_at";
+
+pub async fn list_conflicts(db: &SqlitePool) -> Result<Vec<WorklogConflict>, sqlx::Error> {
+    let rows: Vec<ConflictRow> =
+        sqlx::query_as(&format!("SELECT {} FROM worklog_conflicts ORDER BY detected_at", CONFLICT_COLUMNS))
+            .fetch_all(db)
+            .await?;
+    let mut conflicts = Vec::with_capacity(rows.len());
+    for conflict in rows {
+        let Some(row) = load_row(db, conflict.worklog_id).await? else {
+            continue;
+        };
+        let reason: Option<String> = sqlx::query_scalar("SELECT sync_error FROM worklogs WHERE id = ?1")
+            .bind(conflict.worklog_id)
+            .fetch_one(db)
+            .await?;
+        conflicts.push(WorklogConflict {
+            worklog_id: conflict.worklog_id,
+            issue_key: row.issue_key.clone(),
+            jira_worklog_id: row.jira_worklog_id.clone(),
+            reason,
+            detected_at: conflict.detected_at.clone(),
+            local: (!conflict.local_deleted).then(|| row.local()),
+            base: row.base(),
+            remote: conflict.remote(),
+        });
+    }
+    Ok(conflicts)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Resolution {
+    /// Push the local version (or the local delete) over Jira's
+    KeepMine,
+    /// Drop local changes and take Jira's version (or its delete)
+    TakeTheirs,
+    /// Take Jira's times and combine both descriptions
+    MergeDescription,
+}
+
+/// Settle a conflict. Returns whether anything was queued for the sync worker.
+pub async fn resolve(db: &SqlitePool, worklog_id: i64, resolution: Resolution) -> Result<bool, String> {
+    let conflict: ConflictRow = sqlx::query_as(&format!(
+        "SELECT {} FROM worklog_conflicts WHERE worklog_id = ?1",
+        CONFLICT_COLUMNS
+    ))
+    .bind(worklog_id)
+    .fetch_optional(db)
+    .await
+    .map_err(|e| e.to_string())?
+    .ok_or("No conflict recorded for this worklog")?;
+    let row = load_row(db, worklog_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or("Worklog not found")?;
+    let remote = conflict.remote();
+    let remote_updated = conflict.remote_updated_at.as_deref();
+    let jira_id = row.jira_worklog_id.as_deref();
+
+    // The row, its outbox entry and the conflict record change together
+    let mut tx = db.begin().await.map_err(|e| e.to_string())?;
+    let queued = match (resolution, &remote) {
+        // Gone in Jira: keeping ours means creating it again
+        (Resolution::KeepMine, None) if !conflict.local_deleted => {
+            sqlx::query(
+                "UPDATE worklogs SET sync_status = 'pending', sync_error = NULL, jira_worklog_id = NULL, jira_updated_at = NULL, \
+                 base_started_at = NULL, base_duration_seconds = NULL, base_description = NULL, push_attempted_at = NULL, \
+                 updated_at = datetime('now') WHERE id = ?1",
+            )
+            .bind(worklog_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+            false
+        }
+        (Resolution::KeepMine, _) => {
+            let (status, operation) = if conflict.local_deleted {
+                ("pending_delete", SyncOperation::Delete)
+            } else {
+                ("modified", SyncOperation::Update)
+            };
+            // Jira's current version becomes the base, so the replay doesn't trip over it again
+            sqlx::query(
+                "UPDATE worklogs SET sync_status = ?1, sync_error = NULL, jira_updated_at = ?2, \
+                 base_started_at = COALESCE(?3, base_started_at), base_duration_seconds = COALESCE(?4, base_duration_seconds), \
+                 base_description = COALESCE(?5, base_description), updated_at = datetime('now') WHERE id = ?6",
+            )
+            .bind(status)
+            .bind(remote_updated)
+            .bind(remote.as_ref().map(|f| f.started_at.as_str()))
+            .bind(remote.as_ref().map(|f| f.duration_seconds))
+            .bind(remote.as_ref().map(|f| f.description.as_str()))
+            .bind(worklog_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+            sync::enqueue(&mut *tx, worklog_id, operation, &row.issue_key, jira_id)
+                .await
+                .map_err(|e| e.to_string())?;
+            true
+        }
+        (Resolution::TakeTheirs, None) => {
+            sqlx::query("DELETE FROM worklogs WHERE id = ?1")
+                .bind(worklog_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| e.to_string())?;
+            false
+        }
+        (Resolution::TakeTheirs, Some(remote)) => {
+            apply_synced(&mut *tx, worklog_id, remote, remote_updated)
+                .await
+                .map_err(|e| e.to_string())?;
+            false
+        }
+        (Resolution::MergeDescription, None) => {
+            return Err("The worklog was deleted in Jira; there is no description to merge".to_string())
+        }
+        (Resolution::MergeDescription, Some(remote)) => {
+            let local_description = if conflict.local_deleted { "" } else { row.description.as_str() };
+            let description = merge_descriptions(local_description, &remote.description);
+            sqlx::query(
+                "UPDATE worklogs SET started_at = ?1, duration_seconds = ?2, description = ?3, \
+                 base_started_at = ?1, base_duration_seconds = ?2, base_description = ?4, \
+                 jira_updated_at = ?5, sync_status = 'modified', sync_error = NULL, updated_at = datetime('now') WHERE id = ?6",
+            )
+            .bind(&remote.started_at)
+            .bind(remote.duration_seconds)
+            .bind(&description)
+            .bind(&remote.description)
+            .bind(remote_updated)
+            .bind(worklog_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+            sync::enqueue(&mut *tx, worklog_id, SyncOperation::Update, &row.issue_key, jira_id)
+                .await
+                .map_err(|e| e.to_string())?;
+            true
+        }
+    };
+
+    sqlx::query("DELETE FROM worklog_conflicts WHERE worklog_id = ?1")
+        .bind(worklog_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    tx.commit().await.map_err(|e| e.to_string())?;
+    Ok(queued)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(started_at: &str, duration_seconds: i64, description: &str) -> WorklogFields {
+        WorklogFields {
+            started_at: started_at.to_string(),
+            duration_seconds,
+            description: description.to_string(),
+        }
+    }
+
+    #[test]
+    fn changes_on_different_fields_merge() {
+        let base = fields("2024-03-04T09:00:00+00:00", 600, "review");
+        let local = fields("2024-03-04T09:00:00+00:00", 1200, "review");
+        let remote = fields("2024-03-04T11:00:00+02:00", 600, "code review");
+
+        let Merge::Push(merged) = three_way(Some(&base), &local, &remote) else {
+            panic!("expected a merge to push");
+        };
+        assert_eq!(merged.duration_seconds, 1200);
+        assert_eq!(merged.description, "code review");
+
+        // Only Jira changed: nothing to push
+        assert!(matches!(three_way(Some(&base), &base, &remote), Merge::TakeRemote));
+        // Same new value on both sides is not a conflict
+        assert!(matches!(three_way(Some(&base), &local, &local), Merge::TakeRemote));
+    }
+
+    #[test]
+    fn same_field_changed_on_both_sides_conflicts() {
+        let base = fields("2024-03-04T09:00:00+00:00", 600, "review");
+        let local = fields("2024-03-04T09:00:00+00:00", 1200, "review");
+        let remote = fields("2024-03-04T09:00:00+00:00", 900, "review");
+        assert!(matches!(three_way(Some(&base), &local, &remote), Merge::Conflict));
+        // Without a base any difference is a conflict
+        assert!(matches!(three_way(None, &local, &remote), Merge::Conflict));
+    }
+
+    #[test]
+    fn descriptions_merge_without_repeating() {
+        assert_eq!(merge_descriptions("fix login", "fix login"), "fix login");
+        assert_eq!(merge_descriptions("fix login", "fix login and tests"), "fix login and tests");
+        assert_eq!(merge_descriptions("", "standup"), "standup");
+        assert_eq!(merge_descriptions("fix login", "standup"), "fix login\n\nstandup");
+    }
+
+    #[tokio::test]
+    async fn keep_mine_requeues_against_the_new_base() {
+        let db = crate::db::test_pool().await;
+        let id = sqlx::query(
+            "INSERT INTO worklogs (issue_key, started_at, duration_seconds, description, sync_status, jira_worklog_id, \
+             jira_updated_at, base_started_at, base_duration_seconds, base_description) \
+             VALUES ('SUP-1', '2024-03-04T09:00:00+00:00', 1200, 'mine', 'modified', '300', 't1', \
+             '2024-03-04T09:00:00+00:00', 600, 'base')",
+        )
+        .execute(&db)
+        .await
+        .unwrap()
+        .last_insert_rowid();
+        let remote = fields("2024-03-04T09:00:00+00:00", 900, "theirs");
+        record_conflict(&db, id, false, Some((&remote, "t2")), "Changed both here and in Jira").await.unwrap();
+
+        let listed = list_conflicts(&db).await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].remote.as_ref().unwrap().duration_seconds, 900);
+        assert_eq!(listed[0].local.as_ref().unwrap().duration_seconds, 1200);
+
+        assert!(resolve(&db, id, Resolution::KeepMine).await.unwrap());
+        let row = load_row(&db, id).await.unwrap().unwrap();
+        assert_eq!(row.sync_status, "modified");
+        assert_eq!(row.jira_updated_at.as_deref(), Some("t2"));
+        assert_eq!(row.base_duration_seconds, Some(900));
+        assert_eq!(sync::count_pending(&db).await.unwrap(), 1);
+        assert!(list_conflicts(&db).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,288 @@
+//! Rotating copies of `jira-tracker.db` made with `VACUUM INTO`, so unsynced
+//! worklogs survive a corrupted file or a bad migration.
+//!
+//! Restoring cannot swap the file under an open pool, so it is done in two steps:
+//! `schedule_restore` leaves a marker next to the database and the app restarts;
+//! `apply_pending_restore` then copies the backup into place before anything
+//! connects.
+
+use chrono::{Local, NaiveDateTime};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+pub const BACKUP_DIR: &str = "backups";
+pub const DEFAULT_RETENTION: usize = 10;
+const DAILY: Duration = Duration::from_secs(24 * 60 * 60);
+const DAILY_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+const FILE_PREFIX: &str = "jira-tracker-";
+const FILE_SUFFIX: &str = ".db";
+const TIMESTAMP_FORMAT: &str = "%Y%m%d-%H%M%S";
+const RESTORE_MARKER: &str = "restore.pending";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupInfo {
+    pub file_name: String,
+    pub created_at: String,
+    pub size_bytes: u64,
+}
+
+/// Write a consistent snapshot of the live database into `dir` and drop the
+/// oldest backups beyond `retention`.
+pub async fn create_backup(
+    pool: &SqlitePool,
+    dir: &Path,
+    retention: usize,
+) -> Result<BackupInfo, String> {
+    std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create backup dir: {}", e))?;
+
+    let now = Local::now();
+    let mut file_name = format!("{}{}{}", FILE_PREFIX, now.format(TIMESTAMP_FORMAT), FILE_SUFFIX);
+    // Two backups within the same second (e.g. startup + manual) get a suffix
+    let mut n = 1;
+    while dir.join(&file_name).exists() {
+        file_name = format!("{}{}-{}{}", FILE_PREFIX, now.format(TIMESTAMP_FORMAT), n, FILE_SUFFIX);
+        n += 1;
+    }
+    let path = dir.join(&file_name);
+
+    sqlx::query("VACUUM INTO ?1")
+        .bind(path.to_string_lossy().to_string())
+        .execute(pool)
+        .await
+        .map_err(|e| format!("Backup failed: {}", e))?;
+
+    prune(dir, retention)?;
+
+    let size_bytes = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+    Ok(BackupInfo {
+        file_name,
+        created_at: now.to_rfc3339(),
+        size_bytes,
+    })
+}
+
+/// Backups in `dir`, newest first.
+pub fn list_backups(dir: &Path) -> Result<Vec<BackupInfo>, String> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read backup dir: {}", e)),
+    };
+
+    let mut backups: Vec<((NaiveDateTime, u32), BackupInfo)> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            let order = parse_timestamp(&file_name)?;
+            let size_bytes = entry.metadata().map(|m| m.len()).unwrap_or(0);
+            let created_at = order
+                .0
+                .and_local_timezone(Local)
+                .earliest()
+                .map(|t| t.to_rfc3339())
+                .unwrap_or_default();
+            Some((
+                order,
+                BackupInfo {
+                    file_name,
+                    created_at,
+                    size_bytes,
+                },
+            ))
+        })
+        .collect();
+    backups.sort_by_key(|(order, _)| std::cmp::Reverse(*order));
+    Ok(backups.into_iter().map(|(_, info)| info).collect())
+}
+
+fn prune(dir: &Path, retention: usize) -> Result<(), String> {
+    for old in list_backups(dir)?.into_iter().skip(retention.max(1)) {
+        std::fs::remove_file(dir.join(&old.file_name))
+            .map_err(|e| format!("Failed to remove old backup {}: {}", old.file_name, e))?;
+    }
+    Ok(())
+}
+
+/// Timestamp and same-second counter of a backup name. Only names we generated
+/// are accepted, which also rules out path traversal.
+fn parse_timestamp(file_name: &str) -> Option<(NaiveDateTime, u32)> {
+    let stamp = file_name.strip_prefix(FILE_PREFIX)?.strip_suffix(FILE_SUFFIX)?;
+    let taken_at = NaiveDateTime::parse_from_str(stamp.get(..15)?, TIMESTAMP_FORMAT).ok()?;
+    let counter = match &stamp[15..] {
+        "" => 0,
+        rest => rest.strip_prefix('-')?.parse().ok()?,
+    };
+    Some((taken_at, counter))
+}
+
+/// Age of the newest backup, or `None` when there is none yet.
+fn newest_backup_age(dir: &Path) -> Option<Duration> {
+    let newest = list_backups(dir).ok()?.into_iter().next()?;
+    let modified = std::fs::metadata(dir.join(newest.file_name)).ok()?.modified().ok()?;
+    modified.elapsed().ok()
+}
+
+/// Background loop for a long-running app: take a backup whenever the newest one
+/// is a day old. Checking the file age instead of sleeping a full day means a
+/// laptop that was asleep catches up soon after it wakes.
+pub async fn run_daily(pool: SqlitePool, dir: PathBuf) {
+    loop {
+        tokio::time::sleep(DAILY_CHECK_INTERVAL).await;
+        if newest_backup_age(&dir).is_some_and(|age| age < DAILY) {
+            continue;
+        }
+        let retention = load_retention(&pool).await;
+        if let Err(e) = create_backup(&pool, &dir, retention).await {
+            eprintln!("Daily backup failed: {}", e);
+        }
+    }
+}
+
+/// `PRAGMA integrity_check` problems; empty when the database is healthy.
+pub async fn integrity_check(pool: &SqlitePool) -> Result<Vec<String>, sqlx::Error> {
+    let rows: Vec<String> = sqlx::query_scalar("PRAGMA integrity_check")
+        .fetch_all(pool)
+        .await?;
+    Ok(rows.into_iter().filter(|r| r != "ok").collect())
+}
+
+/// Mark `file_name` to replace the database on the next start.
+pub fn schedule_restore(app_dir: &Path, file_name: &str) -> Result<(), String> {
+    if parse_timestamp(file_name).is_none() {
+        return Err(format!("Not a backup file: {}", file_name));
+    }
+    if !app_dir.join(BACKUP_DIR).join(file_name).is_file() {
+        return Err(format!("Backup not found: {}", file_name));
+    }
+    std::fs::write(app_dir.join(RESTORE_MARKER), file_name)
+        .map_err(|e| format!("Failed to schedule restore: {}", e))
+}
+
+/// Copy a scheduled backup over `db_path`. Must run before the pool is opened.
+/// The current file is kept as `<db>.before-restore` in case the choice was wrong.
+pub fn apply_pending_restore(app_dir: &Path, db_path: &Path) -> Result<Option<String>, String> {
+    let marker = app_dir.join(RESTORE_MARKER);
+    let file_name = match std::fs::read_to_string(&marker) {
+        Ok(name) => name.trim().to_string(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(format!("Failed to read restore marker: {}", e)),
+    };
+    // Remove the marker first so a backup that fails to restore can't loop forever
+    std::fs::remove_file(&marker).map_err(|e| format!("Failed to clear restore marker: {}", e))?;
+    if parse_timestamp(&file_name).is_none() {
+        return Err(format!("Not a backup file: {}", file_name));
+    }
+
+    let source = app_dir.join(BACKUP_DIR).join(&file_name);
+    if db_path.exists() {
+        std::fs::copy(db_path, sibling(db_path, ".before-restore"))
+            .map_err(|e| format!("Failed to keep current database: {}", e))?;
+    }
+    // Stale WAL/SHM files belong to the old database and would corrupt the restored one
+    for suffix in ["-wal", "-shm"] {
+        let _ = std::fs::remove_file(sibling(db_path, suffix));
+    }
+    std::fs::copy(&source, db_path).map_err(|e| format!("Failed to restore {}: {}", file_name, e))?;
+    Ok(Some(file_name))
+}
+
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Retention count from settings, falling back to the default.
+pub async fn load_retention(pool: &SqlitePool) -> usize {
+    sqlx::query_scalar::<_, String>("SELECT value FROM settings WHERE key = 'backup_retention'")
+        .fetch_optional(pool)
+        .await
+        .ok()
+        .flatten()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_RETENTION)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "jtt-backup-{}-{}-{}",
+            name,
+            std::process::id(),
+            fastrand::u64(..)
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    async fn file_pool(path: &Path) -> SqlitePool {
+        SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect(&format!("sqlite:{}?mode=rwc", path.display()))
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn backups_rotate_and_restore_replaces_the_database() {
+        let app_dir = temp_dir("rotate");
+        let db_path = app_dir.join("jira-tracker.db");
+        let backup_dir = app_dir.join(BACKUP_DIR);
+
+        let pool = file_pool(&db_path).await;
+        crate::db::migrations::run_migrations(&pool).await.unwrap();
+        assert!(integrity_check(&pool).await.unwrap().is_empty());
+        sqlx::query("INSERT INTO settings (key, value) VALUES ('marker', 'before')")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let first = create_backup(&pool, &backup_dir, 2).await.unwrap();
+        sqlx::query("UPDATE settings SET value = 'after' WHERE key = 'marker'")
+            .execute(&pool)
+            .await
+            .unwrap();
+        create_backup(&pool, &backup_dir, 2).await.unwrap();
+        create_backup(&pool, &backup_dir, 2).await.unwrap();
+
+        let backups = list_backups(&backup_dir).unwrap();
+        assert_eq!(backups.len(), 2);
+        assert!(backups.iter().all(|b| b.file_name != first.file_name));
+        pool.close().await;
+
+        // Restoring the newest of the kept backups brings back 'after'
+        assert!(schedule_restore(&app_dir, "../jira-tracker.db").is_err());
+        schedule_restore(&app_dir, &backups[1].file_name).unwrap();
+        assert_eq!(
+            apply_pending_restore(&app_dir, &db_path).unwrap().as_deref(),
+            Some(backups[1].file_name.as_str())
+        );
+        assert_eq!(apply_pending_restore(&app_dir, &db_path).unwrap(), None);
+        assert!(sibling(&db_path, ".before-restore").exists());
+
+        let pool = file_pool(&db_path).await;
+        let value: String = sqlx::query_scalar("SELECT value FROM settings WHERE key = 'marker'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(value, "after");
+        pool.close().await;
+        std::fs::remove_dir_all(app_dir).unwrap();
+    }
+
+    #[test]
+    fn only_generated_names_are_backups() {
+        let base = parse_timestamp("jira-tracker-20240102-030405.db").unwrap();
+        let second = parse_timestamp("jira-tracker-20240102-030405-2.db").unwrap();
+        assert!(second > base);
+        assert!(parse_timestamp("jira-tracker.db").is_none());
+        assert!(parse_timestamp("jira-tracker-20240102-030405/../x.db").is_none());
+    }
+}
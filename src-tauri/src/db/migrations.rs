@@ -1,84 +1,592 @@
-use sqlx::SqlitePool;
+use sqlx::{SqliteConnection, SqlitePool};
 
-pub async fn run_migrations(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+/// One schema change. Applied once, in order, inside its own transaction; the
+/// version is recorded in `schema_version` in that same transaction.
+struct Migration {
+    version: i64,
+    description: &'static str,
+    steps: &'static [Step],
+}
+
+enum Step {
+    Sql(&'static str),
+    /// `ALTER TABLE ... ADD COLUMN`, skipped when the column already exists.
+    /// Databases from before versioning may already have had it added.
+    AddColumn {
+        table: &'static str,
+        column: &'static str,
+        definition: &'static str,
+    },
+}
+
+/// SQL expression for a random RFC 4122 version 4 UUID.
+macro_rules! uuid_v4_sql {
+    () => {
+        "lower(hex(randomblob(4))) || '-' || lower(hex(randomblob(2))) || '-4' || \
+         substr(lower(hex(randomblob(2))), 2) || '-' || \
+         substr('89ab', 1 + (abs(random()) % 4), 1) || substr(lower(hex(randomblob(2))), 2) || '-' || \
+         lower(hex(randomblob(6)))"
+    };
+}
+
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "initial schema",
+        steps: &[
+            Step::Sql(
+                "CREATE TABLE IF NOT EXISTS settings (
+                    key   TEXT PRIMARY KEY NOT NULL,
+                    value TEXT NOT NULL
+                )",
+            ),
+            Step::Sql(
+                "CREATE TABLE IF NOT EXISTS issues (
+                    issue_key    TEXT PRIMARY KEY NOT NULL,
+                    summary      TEXT NOT NULL,
+                    project_key  TEXT NOT NULL,
+                    status       TEXT,
+                    issue_type   TEXT,
+                    updated_at   TEXT NOT NULL DEFAULT (datetime('now'))
+                )",
+            ),
+            Step::Sql(
+                "CREATE TABLE IF NOT EXISTS worklogs (
+                    id               INTEGER PRIMARY KEY AUTOINCREMENT,
+                    issue_key        TEXT NOT NULL,
+                    started_at       TEXT NOT NULL,
+                    duration_seconds INTEGER NOT NULL,
+                    description      TEXT NOT NULL DEFAULT '',
+                    sync_status      TEXT NOT NULL DEFAULT 'pending',
+                    jira_worklog_id  TEXT,
+                    sync_error       TEXT,
+                    created_at       TEXT NOT NULL DEFAULT (datetime('now')),
+                    updated_at       TEXT NOT NULL DEFAULT (datetime('now'))
+                )",
+            ),
+            Step::Sql(
+                "CREATE TABLE IF NOT EXISTS active_timer (
+                    id               INTEGER PRIMARY KEY CHECK (id = 1),
+                    issue_key        TEXT NOT NULL,
+                    started_at       TEXT NOT NULL,
+                    accumulated_secs INTEGER NOT NULL DEFAULT 0,
+                    is_paused        INTEGER NOT NULL DEFAULT 0,
+                    paused_at        TEXT
+                )",
+            ),
+            Step::Sql("CREATE INDEX IF NOT EXISTS idx_worklogs_sync_status ON worklogs(sync_status)"),
+            Step::Sql("CREATE INDEX IF NOT EXISTS idx_worklogs_issue_key ON worklogs(issue_key)"),
+            Step::Sql("CREATE INDEX IF NOT EXISTS idx_worklogs_started_at ON worklogs(started_at DESC)"),
+        ],
+    },
+    Migration {
+        version: 2,
+        description: "worklogs.jira_updated_at",
+        steps: &[Step::AddColumn {
+            table: "worklogs",
+            column: "jira_updated_at",
+            definition: "TEXT",
+        }],
+    },
+    Migration {
+        version: 3,
+        description: "unique jira_worklog_id",
+        steps: &[
+            // Imports before this index could store the same Jira worklog twice (keep the oldest)
+            Step::Sql(
+                "DELETE FROM worklogs WHERE jira_worklog_id IS NOT NULL AND id NOT IN \
+                 (SELECT MIN(id) FROM worklogs WHERE jira_worklog_id IS NOT NULL GROUP BY jira_worklog_id)",
+            ),
+            Step::Sql(
+                "CREATE UNIQUE INDEX IF NOT EXISTS idx_worklogs_jira_id_unique \
+                 ON worklogs(jira_worklog_id) WHERE jira_worklog_id IS NOT NULL",
+            ),
+        ],
+    },
+    Migration {
+        version: 4,
+        description: "active_timer.description",
+        steps: &[Step::AddColumn {
+            table: "active_timer",
+            column: "description",
+            definition: "TEXT NOT NULL DEFAULT ''",
+        }],
+    },
+    Migration {
+        version: 5,
+        description: "worklogs.local_uuid for idempotent push",
+        steps: &[
+            Step::AddColumn {
+                table: "worklogs",
+                column: "local_uuid",
+                definition: "TEXT",
+            },
+            // Set right before a create request goes out; a row that has it but is
+            // not synced may already exist in Jira and must be reconciled first
+            Step::AddColumn {
+                table: "worklogs",
+                column: "push_attempted_at",
+                definition: "TEXT",
+            },
+            Step::Sql(concat!(
+                "UPDATE worklogs SET local_uuid = ",
+                uuid_v4_sql!(),
+                " WHERE local_uuid IS NULL"
+            )),
+            Step::Sql("CREATE UNIQUE INDEX IF NOT EXISTS idx_worklogs_local_uuid ON worklogs(local_uuid)"),
+            // ALTER TABLE can't add a column with a random default, so every insert
+            // path gets its UUID from this trigger instead
+            Step::Sql(concat!(
+                "CREATE TRIGGER IF NOT EXISTS worklogs_local_uuid AFTER INSERT ON worklogs \
+                 WHEN NEW.local_uuid IS NULL BEGIN UPDATE worklogs SET local_uuid = ",
+                uuid_v4_sql!(),
+                " WHERE id = NEW.id; END"
+            )),
+        ],
+    },
+    Migration {
+        version: 6,
+        description: "sync_outbox",
+        steps: &[
+            // No foreign key: a queued delete outlives its worklog row
+            Step::Sql(
+                "CREATE TABLE IF NOT EXISTS sync_outbox (
+                    id               INTEGER PRIMARY KEY AUTOINCREMENT,
+                    worklog_id       INTEGER NOT NULL UNIQUE,
+                    operation        TEXT NOT NULL,
+                    issue_key        TEXT NOT NULL,
+                    jira_worklog_id  TEXT,
+                    attempts         INTEGER NOT NULL DEFAULT 0,
+                    next_attempt_at  TEXT NOT NULL DEFAULT (datetime('now')),
+                    last_error       TEXT,
+                    created_at       TEXT NOT NULL DEFAULT (datetime('now'))
+                )",
+            ),
+            Step::Sql("CREATE INDEX IF NOT EXISTS idx_sync_outbox_next_attempt ON sync_outbox(next_attempt_at)"),
+        ],
+    },
+    Migration {
+        version: 7,
+        description: "last-synced snapshot and worklog_conflicts",
+        steps: &[
+            Step::AddColumn {
+                table: "worklogs",
+                column: "base_started_at",
+                definition: "TEXT",
+            },
+            Step::AddColumn {
+                table: "worklogs",
+                column: "base_duration_seconds",
+                definition: "INTEGER",
+            },
+            Step::AddColumn {
+                table: "worklogs",
+                column: "base_description",
+                definition: "TEXT",
+            },
+            // A synced row matches Jira as far as we know
+            Step::Sql(
+                "UPDATE worklogs SET base_started_at = started_at, base_duration_seconds = duration_seconds, \
+                 base_description = description WHERE sync_status = 'synced'",
+            ),
+            Step::Sql(
+                "CREATE TABLE IF NOT EXISTS worklog_conflicts (
+                    id                       INTEGER PRIMARY KEY AUTOINCREMENT,
+                    worklog_id               INTEGER NOT NULL UNIQUE REFERENCES worklogs(id) ON DELETE CASCADE,
+                    local_deleted            INTEGER NOT NULL DEFAULT 0,
+                    remote_started_at        TEXT,
+                    remote_duration_seconds  INTEGER,
+                    remote_description       TEXT,
+                    remote_updated_at        TEXT,
+                    remote_deleted           INTEGER NOT NULL DEFAULT 0,
+                    detected_at              TEXT NOT NULL DEFAULT (datetime('now'))
+                )",
+            ),
+        ],
+    },
+    Migration {
+        version: 8,
+        description: "sync_events audit log",
+        steps: &[
+            // No foreign key: the history is most useful once the worklog is gone
+            Step::Sql(
+                "CREATE TABLE IF NOT EXISTS sync_events (
+                    id               INTEGER PRIMARY KEY AUTOINCREMENT,
+                    occurred_at      TEXT NOT NULL DEFAULT (datetime('now')),
+                    worklog_id       INTEGER,
+                    jira_worklog_id  TEXT,
+                    issue_key        TEXT,
+                    operation        TEXT NOT NULL,
+                    request          TEXT NOT NULL DEFAULT '',
+                    outcome          TEXT NOT NULL,
+                    error            TEXT
+                )",
+            ),
+            Step::Sql("CREATE INDEX IF NOT EXISTS idx_sync_events_worklog ON sync_events(worklog_id)"),
+            Step::Sql("CREATE INDEX IF NOT EXISTS idx_sync_events_jira_worklog ON sync_events(jira_worklog_id)"),
+        ],
+    },
+    Migration {
+        version: 9,
+        description: "timer_segments",
+        steps: &[
+            // One row per stretch the timer actually ran; `ended_at` is NULL while running
+            Step::Sql(
+                "CREATE TABLE IF NOT EXISTS timer_segments (
+                    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+                    started_at  TEXT NOT NULL,
+                    ended_at    TEXT
+                )",
+            ),
+            // Pause history of a timer left running across the upgrade is unknown:
+            // its accumulated time is placed right before the current stretch
+            Step::Sql(
+                "INSERT INTO timer_segments (started_at, ended_at)
+                 SELECT strftime('%Y-%m-%dT%H:%M:%S+00:00', end_at, '-' || accumulated_secs || ' seconds'),
+                        strftime('%Y-%m-%dT%H:%M:%S+00:00', end_at)
+                 FROM (SELECT accumulated_secs, CASE WHEN is_paused THEN paused_at ELSE started_at END AS end_at
+                       FROM active_timer)
+                 WHERE accumulated_secs > 0 AND end_at IS NOT NULL",
+            ),
+            Step::Sql(
+                "INSERT INTO timer_segments (started_at)
+                 SELECT started_at FROM active_timer WHERE NOT is_paused",
+            ),
+        ],
+    },
+    Migration {
+        version: 10,
+        description: "worklogs.raw_duration_seconds",
+        steps: &[
+            // Tracked time before rounding; NULL when it was never rounded here
+            Step::AddColumn {
+                table: "worklogs",
+                column: "raw_duration_seconds",
+                definition: "INTEGER",
+            },
+            Step::Sql("UPDATE worklogs SET raw_duration_seconds = duration_seconds WHERE jira_worklog_id IS NULL"),
+        ],
+    },
+    Migration {
+        version: 11,
+        description: "parallel timers",
+        steps: &[
+            // SQLite can't drop the `id = 1` check in place, so the table is rebuilt
+            Step::Sql(
+                "CREATE TABLE active_timer_next (
+                    id               INTEGER PRIMARY KEY,
+                    name             TEXT NOT NULL DEFAULT '',
+                    issue_key        TEXT NOT NULL,
+                    started_at       TEXT NOT NULL,
+                    accumulated_secs INTEGER NOT NULL DEFAULT 0,
+                    is_paused        INTEGER NOT NULL DEFAULT 0,
+                    paused_at        TEXT,
+                    description      TEXT NOT NULL DEFAULT ''
+                )",
+            ),
+            Step::Sql(
+                "INSERT INTO active_timer_next (id, issue_key, started_at, accumulated_secs, is_paused, paused_at, description)
+                 SELECT id, issue_key, started_at, accumulated_secs, is_paused, paused_at, description FROM active_timer",
+            ),
+            Step::Sql("DROP TABLE active_timer"),
+            Step::Sql("ALTER TABLE active_timer_next RENAME TO active_timer"),
+            // Segments recorded so far belong to the single timer, which had id 1.
+            // Rebuilt rather than altered so that `timer_id` gets no default.
+            Step::Sql(
+                "CREATE TABLE timer_segments_next (
+                    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+                    timer_id    INTEGER NOT NULL,
+                    started_at  TEXT NOT NULL,
+                    ended_at    TEXT
+                )",
+            ),
+            Step::Sql(
+                "INSERT INTO timer_segments_next (id, timer_id, started_at, ended_at)
+                 SELECT id, 1, started_at, ended_at FROM timer_segments",
+            ),
+            Step::Sql("DROP TABLE timer_segments"),
+            Step::Sql("ALTER TABLE timer_segments_next RENAME TO timer_segments"),
+            Step::Sql("CREATE INDEX IF NOT EXISTS idx_timer_segments_timer_id ON timer_segments(timer_id)"),
+        ],
+    },
+];
+
+#[derive(Debug, thiserror::Error)]
+pub enum MigrationError {
+    #[error("Database migration {version} ({description}) failed: {source}")]
+    Step {
+        version: i64,
+        description: &'static str,
+        source: sqlx::Error,
+    },
+    #[error("Database schema version {found} is newer than this app supports ({latest}); please update the app")]
+    TooNew { found: i64, latest: i64 },
+    #[error("Failed to read schema version: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+pub async fn run_migrations(pool: &SqlitePool) -> Result<(), MigrationError> {
+    apply(pool, MIGRATIONS).await
+}
+
+async fn apply(pool: &SqlitePool, migrations: &[Migration]) -> Result<(), MigrationError> {
     sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS settings (
-            key   TEXT PRIMARY KEY NOT NULL,
-            value TEXT NOT NULL
-        );
-
-        CREATE TABLE IF NOT EXISTS issues (
-            issue_key    TEXT PRIMARY KEY NOT NULL,
-            summary      TEXT NOT NULL,
-            project_key  TEXT NOT NULL,
-            status       TEXT,
-            issue_type   TEXT,
-            updated_at   TEXT NOT NULL DEFAULT (datetime('now'))
-        );
-
-        CREATE TABLE IF NOT EXISTS worklogs (
-            id               INTEGER PRIMARY KEY AUTOINCREMENT,
-            issue_key        TEXT NOT NULL,
-            started_at       TEXT NOT NULL,
-            duration_seconds INTEGER NOT NULL,
-            description      TEXT NOT NULL DEFAULT '',
-            sync_status      TEXT NOT NULL DEFAULT 'pending',
-            jira_worklog_id  TEXT,
-            sync_error       TEXT,
-            created_at       TEXT NOT NULL DEFAULT (datetime('now')),
-            updated_at       TEXT NOT NULL DEFAULT (datetime('now'))
-        );
-
-        CREATE TABLE IF NOT EXISTS active_timer (
-            id               INTEGER PRIMARY KEY CHECK (id = 1),
-            issue_key        TEXT NOT NULL,
-            started_at       TEXT NOT NULL,
-            accumulated_secs INTEGER NOT NULL DEFAULT 0,
-            is_paused        INTEGER NOT NULL DEFAULT 0,
-            paused_at        TEXT
-        );
-        "#,
+        "CREATE TABLE IF NOT EXISTS schema_version (
+            version     INTEGER PRIMARY KEY NOT NULL,
+            description TEXT NOT NULL,
+            applied_at  TEXT NOT NULL DEFAULT (datetime('now'))
+        )",
     )
     .execute(pool)
     .await?;
 
-    // Create indexes separately (SQLite doesn't support multiple statements well with CREATE INDEX)
-    let indexes = [
-        "CREATE INDEX IF NOT EXISTS idx_worklogs_sync_status ON worklogs(sync_status)",
-        "CREATE INDEX IF NOT EXISTS idx_worklogs_issue_key ON worklogs(issue_key)",
-        "CREATE INDEX IF NOT EXISTS idx_worklogs_started_at ON worklogs(started_at DESC)",
-    ];
+    let current: i64 = sqlx::query_scalar("SELECT COALESCE(MAX(version), 0) FROM schema_version")
+        .fetch_one(pool)
+        .await?;
+    let latest = migrations.last().map(|m| m.version).unwrap_or(0);
+    if current > latest {
+        return Err(MigrationError::TooNew {
+            found: current,
+            latest,
+        });
+    }
+
+    for migration in migrations.iter().filter(|m| m.version > current) {
+        let step_error = |source| MigrationError::Step {
+            version: migration.version,
+            description: migration.description,
+            source,
+        };
+        let mut tx = pool.begin().await.map_err(step_error)?;
+        for step in migration.steps {
+            run_step(&mut tx, step).await.map_err(step_error)?;
+        }
+        sqlx::query("INSERT INTO schema_version (version, description) VALUES (?1, ?2)")
+            .bind(migration.version)
+            .bind(migration.description)
+            .execute(&mut *tx)
+            .await
+            .map_err(step_error)?;
+        tx.commit().await.map_err(step_error)?;
+    }
+
+    Ok(())
+}
+
+async fn run_step(conn: &mut SqliteConnection, step: &Step) -> Result<(), sqlx::Error> {
+    match step {
+        Step::Sql(sql) => {
+            sqlx::query(sql).execute(&mut *conn).await?;
+        }
+        Step::AddColumn {
+            table,
+            column,
+            definition,
+        } => {
+            let exists: bool = sqlx::query_scalar(
+                "SELECT EXISTS (SELECT 1 FROM pragma_table_info(?1) WHERE name = ?2)",
+            )
+            .bind(table)
+            .bind(column)
+            .fetch_one(&mut *conn)
+            .await?;
+            if !exists {
+                sqlx::query(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition))
+                    .execute(&mut *conn)
+                    .await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn empty_pool() -> SqlitePool {
+        sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap()
+    }
 
-    for idx in indexes {
-        sqlx::query(idx).execute(pool).await?;
+    async fn exec(pool: &SqlitePool, sql: &str) {
+        sqlx::raw_sql(sql).execute(pool).await.unwrap();
     }
 
-    // Add jira_updated_at column (migration)
-    let _ = sqlx::query("ALTER TABLE worklogs ADD COLUMN jira_updated_at TEXT")
-        .execute(pool)
+    async fn has_column(pool: &SqlitePool, table: &str, column: &str) -> bool {
+        sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM pragma_table_info(?1) WHERE name = ?2)")
+            .bind(table)
+            .bind(column)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    fn all_versions() -> Vec<i64> {
+        MIGRATIONS.iter().map(|m| m.version).collect()
+    }
+
+    async fn versions(pool: &SqlitePool) -> Vec<i64> {
+        sqlx::query_scalar("SELECT version FROM schema_version ORDER BY version")
+            .fetch_all(pool)
+            .await
+            .unwrap()
+    }
+
+    /// Tables as created by the first release, before any ALTER TABLE
+    const ORIGINAL_SHAPE: &str = "
+        CREATE TABLE settings (key TEXT PRIMARY KEY NOT NULL, value TEXT NOT NULL);
+        CREATE TABLE issues (
+            issue_key TEXT PRIMARY KEY NOT NULL, summary TEXT NOT NULL, project_key TEXT NOT NULL,
+            status TEXT, issue_type TEXT, updated_at TEXT NOT NULL DEFAULT (datetime('now')));
+        CREATE TABLE worklogs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT, issue_key TEXT NOT NULL, started_at TEXT NOT NULL,
+            duration_seconds INTEGER NOT NULL, description TEXT NOT NULL DEFAULT '',
+            sync_status TEXT NOT NULL DEFAULT 'pending', jira_worklog_id TEXT, sync_error TEXT,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            updated_at TEXT NOT NULL DEFAULT (datetime('now')));
+        CREATE TABLE active_timer (
+            id INTEGER PRIMARY KEY CHECK (id = 1), issue_key TEXT NOT NULL, started_at TEXT NOT NULL,
+            accumulated_secs INTEGER NOT NULL DEFAULT 0, is_paused INTEGER NOT NULL DEFAULT 0,
+            paused_at TEXT);
+    ";
+
+    #[tokio::test]
+    async fn fresh_database_gets_every_version() {
+        let pool = empty_pool().await;
+        run_migrations(&pool).await.unwrap();
+        assert_eq!(versions(&pool).await, all_versions());
+        assert!(has_column(&pool, "worklogs", "jira_updated_at").await);
+        assert!(has_column(&pool, "active_timer", "description").await);
+
+        // Second start is a no-op
+        run_migrations(&pool).await.unwrap();
+        assert_eq!(versions(&pool).await, all_versions());
+    }
+
+    #[tokio::test]
+    async fn upgrades_original_shape_and_removes_duplicate_imports() {
+        let pool = empty_pool().await;
+        exec(&pool, ORIGINAL_SHAPE).await;
+        exec(
+            &pool,
+            "INSERT INTO worklogs (issue_key, started_at, duration_seconds, jira_worklog_id) VALUES
+                ('A-1', '2024-01-01T09:00:00', 60, '100'),
+                ('A-1', '2024-01-01T09:00:00', 60, '100'),
+                ('A-2', '2024-01-01T10:00:00', 60, NULL),
+                ('A-2', '2024-01-01T10:00:00', 60, NULL);
+             INSERT INTO active_timer (id, issue_key, started_at) VALUES (1, 'A-1', '2024-01-01T11:00:00');",
+        )
         .await;
 
-    // Deduplicate worklogs with same jira_worklog_id (keep the oldest)
-    let _ = sqlx::query(
-        "DELETE FROM worklogs WHERE jira_worklog_id IS NOT NULL AND id NOT IN \
-         (SELECT MIN(id) FROM worklogs WHERE jira_worklog_id IS NOT NULL GROUP BY jira_worklog_id)",
-    )
-    .execute(pool)
-    .await;
+        run_migrations(&pool).await.unwrap();
 
-    // Prevent future duplicates
-    let _ = sqlx::query(
-        "CREATE UNIQUE INDEX IF NOT EXISTS idx_worklogs_jira_id_unique \
-         ON worklogs(jira_worklog_id) WHERE jira_worklog_id IS NOT NULL",
-    )
-    .execute(pool)
-    .await;
+        assert_eq!(versions(&pool).await, all_versions());
+        let ids: Vec<i64> = sqlx::query_scalar("SELECT id FROM worklogs ORDER BY id")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(ids, vec![1, 3, 4]);
+        let description: String = sqlx::query_scalar("SELECT description FROM active_timer")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(description, "");
+        // The running timer keeps running as an open segment
+        let open_segments: Vec<String> =
+            sqlx::query_scalar("SELECT started_at FROM timer_segments WHERE ended_at IS NULL")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(open_segments, vec!["2024-01-01T11:00:00"]);
+        // The old singleton becomes timer 1 of many
+        let timer_ids: Vec<i64> = sqlx::query_scalar(
+            "SELECT s.timer_id FROM timer_segments s JOIN active_timer t ON t.id = s.timer_id",
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(timer_ids, vec![1]);
+        exec(&pool, "INSERT INTO active_timer (id, issue_key, started_at) VALUES (2, 'A-2', '2024-01-01T11:30:00')").await;
+        // New segments must name their timer
+        assert!(sqlx::query("INSERT INTO timer_segments (started_at) VALUES ('2024-01-01T11:30:00')")
+            .execute(&pool)
+            .await
+            .is_err());
+        assert!(sqlx::query("INSERT INTO worklogs (issue_key, started_at, duration_seconds, jira_worklog_id) VALUES ('A-1', 'x', 1, '100')")
+            .execute(&pool)
+            .await
+            .is_err());
+    }
 
-    // Add description column to active_timer
-    let _ = sqlx::query("ALTER TABLE active_timer ADD COLUMN description TEXT NOT NULL DEFAULT ''")
-        .execute(pool)
+    #[tokio::test]
+    async fn upgrades_unversioned_database_that_already_has_later_columns() {
+        // Last shape produced by the old ignore-errors migrations
+        let pool = empty_pool().await;
+        exec(&pool, ORIGINAL_SHAPE).await;
+        exec(
+            &pool,
+            "ALTER TABLE worklogs ADD COLUMN jira_updated_at TEXT;
+             CREATE UNIQUE INDEX idx_worklogs_jira_id_unique ON worklogs(jira_worklog_id) WHERE jira_worklog_id IS NOT NULL;
+             ALTER TABLE active_timer ADD COLUMN description TEXT NOT NULL DEFAULT '';
+             INSERT INTO worklogs (issue_key, started_at, duration_seconds, jira_updated_at)
+                VALUES ('A-1', '2024-01-01T09:00:00', 60, '2024-01-02T00:00:00');",
+        )
         .await;
 
-    Ok(())
+        run_migrations(&pool).await.unwrap();
+
+        assert_eq!(versions(&pool).await, all_versions());
+        let kept: String = sqlx::query_scalar("SELECT jira_updated_at FROM worklogs")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(kept, "2024-01-02T00:00:00");
+    }
+
+    #[tokio::test]
+    async fn failed_step_rolls_back_and_stops() {
+        let pool = empty_pool().await;
+        let broken = [
+            Migration {
+                version: 1,
+                description: "fine",
+                steps: &[Step::Sql("CREATE TABLE a (x INTEGER)")],
+            },
+            Migration {
+                version: 2,
+                description: "broken",
+                steps: &[
+                    Step::Sql("CREATE TABLE b (x INTEGER)"),
+                    Step::Sql("ALTER TABLE missing ADD COLUMN y TEXT"),
+                ],
+            },
+        ];
+
+        let err = apply(&pool, &broken).await.unwrap_err();
+        assert!(matches!(err, MigrationError::Step { version: 2, .. }));
+        assert!(err.to_string().starts_with("Database migration 2 (broken) failed"));
+        assert_eq!(versions(&pool).await, vec![1]);
+        let b_exists: bool = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE name = 'b')",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert!(!b_exists);
+    }
+
+    #[tokio::test]
+    async fn refuses_database_from_newer_app() {
+        let pool = empty_pool().await;
+        run_migrations(&pool).await.unwrap();
+        exec(&pool, "INSERT INTO schema_version (version, description) VALUES (999, 'future')").await;
+        assert!(matches!(
+            run_migrations(&pool).await,
+            Err(MigrationError::TooNew { found: 999, .. })
+        ));
+    }
 }
@@ -1 +1,17 @@
+pub mod backup;
 pub mod migrations;
+
+/// Fresh in-memory database with all migrations applied. A single connection keeps
+/// every query on the same in-memory database.
+#[cfg(test)]
+pub async fn test_pool() -> sqlx::SqlitePool {
+    let pool = sqlx::sqlite::SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .expect("Failed to open in-memory database");
+    migrations::run_migrations(&pool)
+        .await
+        .expect("Failed to run migrations");
+    pool
+}
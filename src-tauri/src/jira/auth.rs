@@ -0,0 +1,88 @@
+use base64::{engine::general_purpose, Engine as _};
+use futures::future::BoxFuture;
+use std::sync::Arc;
+
+use super::error::JiraError;
+use super::oauth::{OAuthApp, OAuthTokenStore};
+
+/// Supplies the `Authorization` header for `JiraClient` requests.
+pub trait AuthProvider: Send + Sync {
+    /// Header value for the next request. May refresh credentials first.
+    fn authorization(&self) -> BoxFuture<'_, Result<String, JiraError>>;
+
+    /// Called after Jira answered 401. Returns true when fresh credentials were
+    /// obtained and the request is worth sending again.
+    fn recover_unauthorized(&self) -> BoxFuture<'_, bool> {
+        Box::pin(async { false })
+    }
+}
+
+/// Jira Cloud API token: Basic auth with the account email.
+pub struct BasicAuth {
+    header: String,
+}
+
+impl BasicAuth {
+    pub fn new(email: &str, api_token: &str) -> Self {
+        let credentials = format!("{}:{}", email, api_token);
+        Self {
+            header: format!("Basic {}", general_purpose::STANDARD.encode(credentials)),
+        }
+    }
+}
+
+impl AuthProvider for BasicAuth {
+    fn authorization(&self) -> BoxFuture<'_, Result<String, JiraError>> {
+        Box::pin(async move { Ok(self.header.clone()) })
+    }
+}
+
+/// Server/Data Center Personal Access Token.
+pub struct PersonalAccessToken {
+    header: String,
+}
+
+impl PersonalAccessToken {
+    pub fn new(token: &str) -> Self {
+        Self {
+            header: format!("Bearer {}", token),
+        }
+    }
+}
+
+impl AuthProvider for PersonalAccessToken {
+    fn authorization(&self) -> BoxFuture<'_, Result<String, JiraError>> {
+        Box::pin(async move { Ok(self.header.clone()) })
+    }
+}
+
+/// OAuth 2.0 (3LO) access token that refreshes itself shortly before it expires.
+pub struct OAuthAuth {
+    http: reqwest::Client,
+    app: OAuthApp,
+    store: Arc<OAuthTokenStore>,
+}
+
+impl OAuthAuth {
+    pub fn new(http: reqwest::Client, app: OAuthApp, store: Arc<OAuthTokenStore>) -> Self {
+        Self { http, app, store }
+    }
+}
+
+impl AuthProvider for OAuthAuth {
+    fn authorization(&self) -> BoxFuture<'_, Result<String, JiraError>> {
+        Box::pin(async move {
+            let token = self.store.access_token(&self.http, &self.app, false).await?;
+            Ok(format!("Bearer {}", token))
+        })
+    }
+
+    fn recover_unauthorized(&self) -> BoxFuture<'_, bool> {
+        Box::pin(async move {
+            self.store
+                .access_token(&self.http, &self.app, true)
+                .await
+                .is_ok()
+        })
+    }
+}
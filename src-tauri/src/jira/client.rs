@@ -1,95 +1,305 @@
-use base64::{engine::general_purpose, Engine as _};
-use reqwest::Client;
+use reqwest::header::{HeaderValue, AUTHORIZATION};
+use reqwest::{Client, Method, RequestBuilder, Response, StatusCode};
+use serde::de::DeserializeOwned;
+use std::sync::Arc;
 
+use super::auth::{AuthProvider, BasicAuth, PersonalAccessToken};
+use super::error::JiraError;
+use super::retry::{RateLimiter, RetryPolicy};
 use super::types::*;
 
+/// Largest page `/search/jql` is asked for; Jira caps it server-side anyway.
+const SEARCH_PAGE_SIZE: usize = 100;
+
+const SEARCH_FIELDS: [&str; 4] = ["summary", "status", "issuetype", "project"];
+
+/// Page size requested from `/issue/{key}/worklog`.
+const WORKLOG_PAGE_SIZE: i64 = 1000;
+
+/// Most ids `/worklog/list` accepts per request.
+const WORKLOG_LIST_CHUNK: usize = 1000;
+
+/// Worklog property carrying the local worklog's UUID, so a create whose response
+/// was lost can be found again instead of being sent twice.
+pub const LOCAL_ID_PROPERTY: &str = "jira-time-tracker.local-id";
+
 #[derive(Clone)]
 pub struct JiraClient {
     client: Client,
     base_url: String,
-    auth_header: String,
+    flavor: JiraFlavor,
+    auth: Arc<dyn AuthProvider>,
+    policy: RetryPolicy,
+    limiter: Arc<RateLimiter>,
 }
 
 impl JiraClient {
-    pub fn new(client: Client, base_url: &str, email: &str, api_token: &str) -> Self {
-        let credentials = format!("{}:{}", email, api_token);
-        let auth_header = format!("Basic {}", general_purpose::STANDARD.encode(credentials));
+    /// Cloud authenticates with `email` + API token; Server/Data Center sends
+    /// `api_token` as a Personal Access Token and ignores `email`.
+    pub fn new(
+        client: Client,
+        base_url: &str,
+        flavor: JiraFlavor,
+        email: &str,
+        api_token: &str,
+    ) -> Self {
+        let auth: Arc<dyn AuthProvider> = match flavor {
+            JiraFlavor::Cloud => Arc::new(BasicAuth::new(email, api_token)),
+            JiraFlavor::DataCenter => Arc::new(PersonalAccessToken::new(api_token)),
+        };
+        Self::with_auth(client, base_url, flavor, auth)
+    }
+
+    /// Client with any auth provider, e.g. OAuth. `base_url` is where the REST API
+    /// lives, which for OAuth is the API gateway rather than the site URL.
+    pub fn with_auth(
+        client: Client,
+        base_url: &str,
+        flavor: JiraFlavor,
+        auth: Arc<dyn AuthProvider>,
+    ) -> Self {
         Self {
             client,
             base_url: base_url.trim_end_matches('/').to_string(),
-            auth_header,
+            flavor,
+            auth,
+            policy: RetryPolicy::default(),
+            limiter: Arc::new(RateLimiter::default()),
         }
     }
 
-    pub async fn get_myself(&self) -> Result<JiraUser, String> {
-        self.client
-            .get(format!("{}/rest/api/3/myself", self.base_url))
-            .header("Authorization", &self.auth_header)
+    /// Ask the anonymous `/rest/api/2/serverInfo` endpoint which product runs at `base_url`.
+    pub async fn detect_flavor(client: &Client, base_url: &str) -> Result<JiraFlavor, JiraError> {
+        let resp = client
+            .get(format!(
+                "{}/rest/api/2/serverInfo",
+                base_url.trim_end_matches('/')
+            ))
             .header("Accept", "application/json")
             .send()
-            .await
-            .map_err(|e| format!("Request failed: {}", e))?
-            .error_for_status()
-            .map_err(|e| format!("Jira API error: {}", e))?
-            .json::<JiraUser>()
-            .await
-            .map_err(|e| format!("Failed to parse response: {}", e))
+            .await?;
+        if !resp.status().is_success() {
+            return Err(JiraError::from_response(resp).await);
+        }
+        let info: JiraServerInfo = parse_json(resp).await?;
+        Ok(match info.deployment_type.as_deref() {
+            Some("Cloud") => JiraFlavor::Cloud,
+            // "Server" and "DataCenter" share the v2 API and PAT auth
+            Some(_) => JiraFlavor::DataCenter,
+            None if base_url.contains(".atlassian.net") => JiraFlavor::Cloud,
+            None => JiraFlavor::DataCenter,
+        })
     }
 
-    pub async fn verify_auth(&self) -> Result<(), String> {
-        let resp = self
-            .client
-            .get(format!("{}/rest/api/3/myself", self.base_url))
-            .header("Authorization", &self.auth_header)
-            .header("Accept", "application/json")
-            .send()
-            .await
-            .map_err(|e| format!("Request failed: {}", e))?;
-        if resp.status() == 401 || resp.status() == 403 {
-            return Err("API token expired or invalid. Please update it in Settings.".to_string());
+    /// Absolute URL of a REST resource under the API version of this flavor.
+    fn api_url(&self, path: &str) -> String {
+        let version = match self.flavor {
+            JiraFlavor::Cloud => 3,
+            JiraFlavor::DataCenter => 2,
+        };
+        format!("{}/rest/api/{}/{}", self.base_url, version, path)
+    }
+
+    /// Use `policy` and share `limiter` with other clients, so concurrent commands
+    /// stay under one request rate and all back off together on a 429.
+    pub fn with_retry(mut self, policy: RetryPolicy, limiter: Arc<RateLimiter>) -> Self {
+        self.policy = policy;
+        self.limiter = limiter;
+        self
+    }
+
+    /// Send an authenticated request, retrying transient failures under the retry
+    /// policy, and turn any final non-2xx status into a `JiraError`.
+    ///
+    /// A 429 is always retried since Jira rejected the request without acting on it.
+    /// 5xx and network errors are only retried when replaying is harmless: for every
+    /// method but POST, or for a POST marked `read_only`. A POST that failed to even
+    /// connect is always safe to resend. A 401 is retried once if the auth provider
+    /// could renew its credentials.
+    async fn execute(&self, request: RequestBuilder, read_only: bool) -> Result<Response, JiraError> {
+        let request = request.header("Accept", "application/json").build()?;
+        let replay_safe = read_only || request.method() != Method::POST;
+
+        let mut attempt = 0;
+        let mut reauthenticated = false;
+        loop {
+            self.limiter.acquire(&self.policy).await;
+            let mut this_try = request
+                .try_clone()
+                .ok_or_else(|| JiraError::Network("Request body cannot be resent".to_string()))?;
+            let auth = self.auth.authorization().await?;
+            this_try.headers_mut().insert(
+                AUTHORIZATION,
+                HeaderValue::from_str(&auth)
+                    .map_err(|_| JiraError::Auth("Credentials contain invalid characters".to_string()))?,
+            );
+
+            let (err, retryable) = match self.client.execute(this_try).await {
+                Ok(resp) if resp.status().is_success() => return Ok(resp),
+                Ok(resp) if resp.status() == StatusCode::UNAUTHORIZED && !reauthenticated => {
+                    reauthenticated = true;
+                    if self.auth.recover_unauthorized().await {
+                        continue;
+                    }
+                    (JiraError::from_response(resp).await, false)
+                }
+                Ok(resp) => {
+                    let err = JiraError::from_response(resp).await;
+                    let retryable = match err {
+                        JiraError::RateLimited { .. } => true,
+                        JiraError::Server { .. } => replay_safe,
+                        _ => false,
+                    };
+                    (err, retryable)
+                }
+                Err(e) => {
+                    let retryable = replay_safe || e.is_connect();
+                    (JiraError::from(e), retryable)
+                }
+            };
+
+            if !retryable || attempt >= self.policy.max_retries {
+                return Err(err);
+            }
+
+            match err {
+                JiraError::RateLimited { retry_after } => {
+                    let wait = retry_after
+                        .map(|d| d.min(self.policy.max_delay()))
+                        .unwrap_or_else(|| self.policy.backoff(attempt));
+                    self.limiter.pause_for(wait);
+                }
+                _ => tokio::time::sleep(self.policy.backoff(attempt)).await,
+            }
+            attempt += 1;
         }
-        resp.error_for_status()
-            .map_err(|e| format!("Jira API error: {}", e))?;
+    }
+
+    async fn send(&self, request: RequestBuilder) -> Result<Response, JiraError> {
+        self.execute(request, false).await
+    }
+
+    async fn send_json<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T, JiraError> {
+        parse_json(self.send(request).await?).await
+    }
+
+    /// `send_json` for POST endpoints that only read data, such as JQL search.
+    async fn send_json_read_only<T: DeserializeOwned>(
+        &self,
+        request: RequestBuilder,
+    ) -> Result<T, JiraError> {
+        parse_json(self.execute(request, true).await?).await
+    }
+
+    pub async fn get_myself(&self) -> Result<JiraUser, JiraError> {
+        self.send_json(self.client.get(self.api_url("myself")))
+            .await
+    }
+
+    pub async fn verify_auth(&self) -> Result<(), JiraError> {
+        self.send(self.client.get(self.api_url("myself")))
+            .await?;
         Ok(())
     }
 
-    pub async fn search_issues(
+    /// Fetch a single page of `/search/jql` (Cloud). Use `search_all_issues` unless
+    /// you need the raw page.
+    async fn search_issues_page(
         &self,
         jql: &str,
         max_results: u32,
-    ) -> Result<Vec<JiraIssue>, String> {
-        let body = serde_json::json!({
+        next_page_token: Option<&str>,
+    ) -> Result<JiraSearchResponse, JiraError> {
+        let mut body = serde_json::json!({
             "jql": jql,
-            "fields": ["summary", "status", "issuetype", "project"],
+            "fields": SEARCH_FIELDS,
             "maxResults": max_results
         });
+        if let Some(token) = next_page_token {
+            body["nextPageToken"] = serde_json::json!(token);
+        }
 
-        let raw = self
-            .client
-            .post(format!("{}/rest/api/3/search/jql", self.base_url))
-            .header("Authorization", &self.auth_header)
-            .header("Accept", "application/json")
-            .header("Content-Type", "application/json")
-            .json(&body)
-            .send()
+        self.send_json_read_only(self.client.post(self.api_url("search/jql")).json(&body))
             .await
-            .map_err(|e| format!("Request failed: {}", e))?
-            .error_for_status()
-            .map_err(|e| format!("Jira API error: {}", e))?
-            .text()
+    }
+
+    /// Fetch a single page of the offset-paged v2 `/search` (Server/Data Center).
+    async fn search_issues_page_v2(
+        &self,
+        jql: &str,
+        max_results: u32,
+        start_at: usize,
+    ) -> Result<JiraSearchResponse, JiraError> {
+        let body = serde_json::json!({
+            "jql": jql,
+            "fields": SEARCH_FIELDS,
+            "startAt": start_at,
+            "maxResults": max_results
+        });
+
+        self.send_json_read_only(self.client.post(self.api_url("search")).json(&body))
             .await
-            .map_err(|e| format!("Failed to read response: {}", e))?;
+    }
+
+    /// Search issues page by page until Jira reports the last page or `limit`
+    /// issues have been collected.
+    pub async fn search_all_issues(
+        &self,
+        jql: &str,
+        limit: usize,
+    ) -> Result<JiraIssueSearch, JiraError> {
+        let mut result = JiraIssueSearch::default();
+        if limit == 0 {
+            return Ok(result);
+        }
+        let mut next_page_token: Option<String> = None;
+
+        loop {
+            let remaining = limit - result.issues.len();
+            let page_size = remaining.min(SEARCH_PAGE_SIZE) as u32;
+            let page = match self.flavor {
+                JiraFlavor::Cloud => {
+                    self.search_issues_page(jql, page_size, next_page_token.as_deref())
+                        .await?
+                }
+                JiraFlavor::DataCenter => {
+                    self.search_issues_page_v2(jql, page_size, result.issues.len())
+                        .await?
+                }
+            };
 
-        let resp: JiraSearchResponse = serde_json::from_str(&raw)
-            .map_err(|e| format!("Failed to parse response: {}. Body preview: {}", e, &raw[..raw.len().min(500)]))?;
+            // The /search/jql endpoint silently returns empty results instead of 401
+            // when the API token is invalid. Detect this and return a clear error.
+            if self.flavor == JiraFlavor::Cloud
+                && result.issues.is_empty()
+                && page.issues.is_empty()
+            {
+                self.verify_auth().await?;
+            }
 
-        // The /search/jql endpoint silently returns empty results instead of 401
-        // when the API token is invalid. Detect this and return a clear error.
-        if resp.issues.is_empty() {
-            self.verify_auth().await?;
+            let page_len = page.issues.len();
+            let has_more = match self.flavor {
+                JiraFlavor::Cloud => page.is_last != Some(true) && page.next_page_token.is_some(),
+                JiraFlavor::DataCenter => {
+                    page.start_at.unwrap_or(0) + page_len < page.total.unwrap_or(0)
+                }
+            };
+            result
+                .issues
+                .extend(page.issues.into_iter().map(JiraIssue::from));
+
+            if !has_more || page_len == 0 {
+                break;
+            }
+            if result.issues.len() >= limit {
+                result.truncated = true;
+                break;
+            }
+            next_page_token = page.next_page_token;
         }
 
-        Ok(resp.issues.into_iter().map(JiraIssue::from).collect())
+        result.issues.truncate(limit);
+        Ok(result)
     }
 
     pub async fn add_worklog(
@@ -98,42 +308,19 @@ impl JiraClient {
         time_spent_seconds: i64,
         started: &str,
         comment: &str,
-    ) -> Result<JiraWorklogResponse, String> {
-        let mut body = serde_json::json!({
-            "timeSpentSeconds": time_spent_seconds,
-            "started": started,
-        });
-        if !comment.is_empty() {
-            body["comment"] = serde_json::json!({
-                "type": "doc",
-                "version": 1,
-                "content": [{
-                    "type": "paragraph",
-                    "content": [{
-                        "type": "text",
-                        "text": comment
-                    }]
-                }]
-            });
-        }
-
-        self.client
-            .post(format!(
-                "{}/rest/api/3/issue/{}/worklog",
-                self.base_url, issue_key
-            ))
-            .header("Authorization", &self.auth_header)
-            .header("Accept", "application/json")
-            .header("Content-Type", "application/json")
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| format!("Request failed: {}", e))?
-            .error_for_status()
-            .map_err(|e| format!("Jira API error: {}", e))?
-            .json::<JiraWorklogResponse>()
-            .await
-            .map_err(|e| format!("Failed to parse response: {}", e))
+        local_id: &str,
+    ) -> Result<JiraWorklogResponse, JiraError> {
+        let mut body = worklog_body(self.flavor, time_spent_seconds, started, comment);
+        body["properties"] = serde_json::json!([{
+            "key": LOCAL_ID_PROPERTY,
+            "value": { "id": local_id }
+        }]);
+        self.send_json(
+            self.client
+                .post(self.api_url(&format!("issue/{}/worklog", issue_key)))
+                .json(&body),
+        )
+        .await
     }
 
     pub async fn update_worklog(
@@ -143,109 +330,247 @@ impl JiraClient {
         time_spent_seconds: i64,
         started: &str,
         comment: &str,
-    ) -> Result<JiraWorklogResponse, String> {
-        let mut body = serde_json::json!({
-            "timeSpentSeconds": time_spent_seconds,
-            "started": started,
-        });
-        if !comment.is_empty() {
-            body["comment"] = serde_json::json!({
-                "type": "doc",
-                "version": 1,
-                "content": [{
-                    "type": "paragraph",
-                    "content": [{
-                        "type": "text",
-                        "text": comment
-                    }]
-                }]
-            });
-        }
-
-        self.client
-            .put(format!(
-                "{}/rest/api/3/issue/{}/worklog/{}",
-                self.base_url, issue_key, worklog_id
-            ))
-            .header("Authorization", &self.auth_header)
-            .header("Accept", "application/json")
-            .header("Content-Type", "application/json")
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| format!("Request failed: {}", e))?
-            .error_for_status()
-            .map_err(|e| format!("Jira API error: {}", e))?
-            .json::<JiraWorklogResponse>()
-            .await
-            .map_err(|e| format!("Failed to parse response: {}", e))
+    ) -> Result<JiraWorklogResponse, JiraError> {
+        let body = worklog_body(self.flavor, time_spent_seconds, started, comment);
+        self.send_json(
+            self.client
+                .put(self.api_url(&format!("issue/{}/worklog/{}", issue_key, worklog_id)))
+                .json(&body),
+        )
+        .await
     }
 
     pub async fn delete_worklog(
         &self,
         issue_key: &str,
         worklog_id: &str,
-    ) -> Result<(), String> {
-        self.client
-            .delete(format!(
-                "{}/rest/api/3/issue/{}/worklog/{}",
-                self.base_url, issue_key, worklog_id
-            ))
-            .header("Authorization", &self.auth_header)
-            .header("Accept", "application/json")
-            .send()
-            .await
-            .map_err(|e| format!("Request failed: {}", e))?
-            .error_for_status()
-            .map_err(|e| format!("Jira API error: {}", e))?;
+    ) -> Result<(), JiraError> {
+        self.send(
+            self.client
+                .delete(self.api_url(&format!("issue/{}/worklog/{}", issue_key, worklog_id))),
+        )
+        .await?;
         Ok(())
     }
 
+    pub async fn get_worklog(
+        &self,
+        issue_key: &str,
+        worklog_id: &str,
+    ) -> Result<JiraWorklogEntry, JiraError> {
+        self.send_json(
+            self.client
+                .get(self.api_url(&format!("issue/{}/worklog/{}", issue_key, worklog_id))),
+        )
+        .await
+    }
+
     pub async fn search_users(
         &self,
         query: &str,
         max_results: u32,
-    ) -> Result<Vec<JiraUser>, String> {
-        let url = format!("{}/rest/api/3/user/search", self.base_url);
-        self.client
-            .get(&url)
-            .header("Authorization", &self.auth_header)
-            .header("Accept", "application/json")
-            .query(&[("query", query), ("maxResults", &max_results.to_string())])
-            .send()
-            .await
-            .map_err(|e| format!("Request failed: {}", e))?
-            .error_for_status()
-            .map_err(|e| format!("Jira API error: {}", e))?
-            .json::<Vec<JiraUser>>()
-            .await
-            .map_err(|e| format!("Failed to parse response: {}", e))
+    ) -> Result<Vec<JiraUser>, JiraError> {
+        // Server/Data Center names the search term `username`, even though it also
+        // matches display names and emails
+        let query_param = match self.flavor {
+            JiraFlavor::Cloud => "query",
+            JiraFlavor::DataCenter => "username",
+        };
+        self.send_json(
+            self.client
+                .get(self.api_url("user/search"))
+                .query(&[(query_param, query), ("maxResults", &max_results.to_string())]),
+        )
+        .await
     }
 
-    pub async fn get_worklogs(
+    async fn get_worklogs_page(
         &self,
         issue_key: &str,
         started_after: Option<i64>,
-    ) -> Result<JiraWorklogListResponse, String> {
-        let mut url = format!(
-            "{}/rest/api/3/issue/{}/worklog",
-            self.base_url, issue_key
-        );
+        start_at: i64,
+        with_properties: bool,
+    ) -> Result<JiraWorklogListResponse, JiraError> {
+        let url = self.api_url(&format!("issue/{}/worklog", issue_key));
+        let mut query = vec![
+            ("startAt", start_at.to_string()),
+            ("maxResults", WORKLOG_PAGE_SIZE.to_string()),
+        ];
         if let Some(epoch_ms) = started_after {
-            url = format!("{}?startedAfter={}", url, epoch_ms);
+            query.push(("startedAfter", epoch_ms.to_string()));
         }
-        self.client
-            .get(&url)
-            .header("Authorization", &self.auth_header)
-            .header("Accept", "application/json")
-            .send()
-            .await
-            .map_err(|e| format!("Request failed: {}", e))?
-            .error_for_status()
-            .map_err(|e| format!("Jira API error: {}", e))?
-            .json::<JiraWorklogListResponse>()
-            .await
-            .map_err(|e| format!("Failed to parse response: {}", e))
+        if with_properties {
+            query.push(("expand", "properties".to_string()));
+        }
+        self.send_json(self.client.get(&url).query(&query)).await
+    }
+
+    /// Every worklog of an issue with its properties, so worklogs we created can be recognised.
+    pub async fn get_worklogs_with_properties(
+        &self,
+        issue_key: &str,
+        started_after: Option<i64>,
+    ) -> Result<JiraWorklogListResponse, JiraError> {
+        self.list_worklogs(issue_key, started_after, true).await
+    }
+
+    /// The comment of a worklog body exactly as `add_worklog` would send it.
+    pub fn worklog_comment(&self, comment: &str) -> Option<serde_json::Value> {
+        comment_body(self.flavor, comment)
+    }
+
+    /// The worklog on `issue_key` that was created with `local_id`, if any.
+    pub async fn find_worklog_by_local_id(
+        &self,
+        issue_key: &str,
+        local_id: &str,
+    ) -> Result<Option<JiraWorklogEntry>, JiraError> {
+        let list = self.list_worklogs(issue_key, None, true).await?;
+        Ok(list.worklogs.into_iter().find(|w| {
+            w.properties
+                .iter()
+                .any(|p| p.key == LOCAL_ID_PROPERTY && p.value["id"] == local_id)
+        }))
+    }
+
+    pub async fn get_issue(&self, id_or_key: &str) -> Result<JiraIssue, JiraError> {
+        let raw: JiraIssueRaw = self
+            .send_json(
+                self.client
+                    .get(self.api_url(&format!("issue/{}", id_or_key)))
+                    .query(&[("fields", SEARCH_FIELDS.join(","))]),
+            )
+            .await?;
+        Ok(raw.into())
+    }
+
+    /// Ids of worklogs created or changed since `since` (epoch ms), by anyone.
+    pub async fn worklogs_updated_since(&self, since: i64) -> Result<JiraWorklogChanges, JiraError> {
+        self.worklog_changes("worklog/updated", since).await
+    }
+
+    /// Ids of worklogs deleted since `since` (epoch ms), by anyone.
+    pub async fn worklogs_deleted_since(&self, since: i64) -> Result<JiraWorklogChanges, JiraError> {
+        self.worklog_changes("worklog/deleted", since).await
+    }
+
+    /// Follow a change feed page by page; each page's `until` is the next `since`.
+    async fn worklog_changes(&self, path: &str, since: i64) -> Result<JiraWorklogChanges, JiraError> {
+        let mut changes = JiraWorklogChanges {
+            values: Vec::new(),
+            until: since,
+        };
+        let mut since = since;
+        loop {
+            let page: JiraWorklogChangePage = self
+                .send_json(self.client.get(self.api_url(path)).query(&[("since", since.to_string())]))
+                .await?;
+            changes.values.extend(page.values);
+            changes.until = changes.until.max(page.until);
+            // A page that doesn't move the window forward would repeat forever
+            if page.last_page || page.until <= since {
+                break;
+            }
+            since = page.until;
+        }
+        Ok(changes)
+    }
+
+    /// Full worklogs, with properties, for ids from a change feed.
+    pub async fn get_worklogs_by_ids(&self, ids: &[i64]) -> Result<Vec<JiraWorklogEntry>, JiraError> {
+        let mut all = Vec::new();
+        for chunk in ids.chunks(WORKLOG_LIST_CHUNK) {
+            let page: Vec<JiraWorklogEntry> = self
+                .send_json_read_only(
+                    self.client
+                        .post(self.api_url("worklog/list"))
+                        .query(&[("expand", "properties")])
+                        .json(&serde_json::json!({ "ids": chunk })),
+                )
+                .await?;
+            all.extend(page);
+        }
+        Ok(all)
+    }
+
+    /// Follow `startAt` until `total` is reached. Jira may return fewer entries per
+    /// page than requested, so paging is driven by what actually came back.
+    async fn list_worklogs(
+        &self,
+        issue_key: &str,
+        started_after: Option<i64>,
+        with_properties: bool,
+    ) -> Result<JiraWorklogListResponse, JiraError> {
+        let mut all = self
+            .get_worklogs_page(issue_key, started_after, 0, with_properties)
+            .await?;
+        let mut next_start = all.start_at + all.worklogs.len() as i64;
+
+        while next_start < all.total {
+            let page = self
+                .get_worklogs_page(issue_key, started_after, next_start, with_properties)
+                .await?;
+            if page.worklogs.is_empty() {
+                break;
+            }
+            next_start = page.start_at + page.worklogs.len() as i64;
+            all.worklogs.extend(page.worklogs);
+        }
+
+        Ok(all)
+    }
+}
+
+pub async fn parse_json<T: DeserializeOwned>(resp: Response) -> Result<T, JiraError> {
+    let raw = resp.text().await?;
+    serde_json::from_str(&raw).map_err(|e| {
+        let preview: String = raw.chars().take(500).collect();
+        JiraError::Parse(format!("{}. Body preview: {}", e, preview))
+    })
+}
+
+fn worklog_body(
+    flavor: JiraFlavor,
+    time_spent_seconds: i64,
+    started: &str,
+    comment: &str,
+) -> serde_json::Value {
+    let mut body = serde_json::json!({
+        "timeSpentSeconds": time_spent_seconds,
+        "started": started,
+    });
+    if let Some(comment) = comment_body(flavor, comment) {
+        body["comment"] = comment;
+    }
+    body
+}
+
+/// A worklog comment as Jira expects it: ADF on Cloud, plain text on Data Center.
+fn comment_body(flavor: JiraFlavor, comment: &str) -> Option<serde_json::Value> {
+    if comment.is_empty() {
+        return None;
+    }
+    Some(match flavor {
+        JiraFlavor::DataCenter => serde_json::json!(comment),
+        JiraFlavor::Cloud => serde_json::json!({
+            "type": "doc",
+            "version": 1,
+            "content": [{
+                "type": "paragraph",
+                "content": [{
+                    "type": "text",
+                    "text": comment
+                }]
+            }]
+        }),
+    })
+}
+
+/// Plain text of a worklog comment: a string on Server/Data Center, ADF on Cloud.
+pub fn comment_text(comment: &serde_json::Value) -> String {
+    match comment.as_str() {
+        Some(text) => text.to_string(),
+        None => extract_adf_text(comment),
     }
 }
 
@@ -266,3 +591,274 @@ pub fn extract_adf_text(adf: &serde_json::Value) -> String {
     }
     texts.join("")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jira::fake_server::{test_client, FakeResponse, FakeServer};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::{Duration, Instant};
+
+    fn fast_policy() -> RetryPolicy {
+        RetryPolicy {
+            max_retries: 3,
+            base_delay_ms: 1,
+            max_delay_ms: 50,
+            requests_per_second: 0.0,
+        }
+    }
+
+    /// Answer with `failures` in order, then with `success` forever.
+    async fn flaky_server(failures: Vec<FakeResponse>, success: serde_json::Value) -> FakeServer {
+        let failures = std::sync::Mutex::new(failures.into_iter());
+        FakeServer::start(move |_| {
+            failures
+                .lock()
+                .unwrap()
+                .next()
+                .unwrap_or_else(|| FakeResponse::json(200, success.clone()))
+        })
+        .await
+    }
+
+    fn myself() -> serde_json::Value {
+        serde_json::json!({ "accountId": "me", "displayName": "Me" })
+    }
+
+    #[tokio::test]
+    async fn retries_rate_limited_request_after_retry_after() {
+        let server = flaky_server(
+            vec![FakeResponse::json(429, serde_json::json!({})).with_header("Retry-After", "1")],
+            myself(),
+        )
+        .await;
+
+        let started = Instant::now();
+        let user = test_client(&server.base_url, fast_policy()).get_myself().await.unwrap();
+
+        assert_eq!(user.account_id, "me");
+        assert_eq!(server.requests().len(), 2);
+        // Retry-After is honored but capped by max_delay_ms
+        assert!(started.elapsed() >= Duration::from_millis(50));
+    }
+
+    #[tokio::test]
+    async fn retries_idempotent_request_on_server_error() {
+        let server = flaky_server(
+            vec![
+                FakeResponse::json(503, serde_json::json!({})),
+                FakeResponse::json(502, serde_json::json!({})),
+            ],
+            myself(),
+        )
+        .await;
+
+        test_client(&server.base_url, fast_policy()).get_myself().await.unwrap();
+        assert_eq!(server.requests().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_max_retries() {
+        let server = FakeServer::start(|_| FakeResponse::json(503, serde_json::json!({}))).await;
+
+        let err = test_client(&server.base_url, fast_policy()).get_myself().await.unwrap_err();
+        assert!(matches!(err, JiraError::Server { status: 503, .. }));
+        assert_eq!(server.requests().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn does_not_replay_worklog_post_after_server_error() {
+        let server = FakeServer::start(|_| FakeResponse::json(500, serde_json::json!({}))).await;
+
+        let err = test_client(&server.base_url, fast_policy())
+            .add_worklog("PROJ-1", 60, "2024-03-04T09:00:00.000+0000", "", "local-1")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, JiraError::Server { status: 500, .. }));
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, "POST");
+    }
+
+    #[tokio::test]
+    async fn replays_worklog_post_rejected_with_429() {
+        let server = flaky_server(
+            vec![FakeResponse::json(429, serde_json::json!({})).with_header("Retry-After", "0")],
+            serde_json::json!({ "id": "10001" }),
+        )
+        .await;
+
+        let resp = test_client(&server.base_url, fast_policy())
+            .add_worklog("PROJ-1", 60, "2024-03-04T09:00:00.000+0000", "", "local-1")
+            .await
+            .unwrap();
+        assert_eq!(resp.id, "10001");
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn replays_read_only_search_post_after_server_error() {
+        let server = flaky_server(
+            vec![FakeResponse::json(502, serde_json::json!({}))],
+            serde_json::json!({
+                "issues": [{ "key": "PROJ-1", "fields": { "summary": "Task" } }],
+                "isLast": true
+            }),
+        )
+        .await;
+
+        let search = test_client(&server.base_url, fast_policy())
+            .search_all_issues("project = PROJ", 10)
+            .await
+            .unwrap();
+        assert_eq!(search.issues.len(), 1);
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn shared_limiter_spaces_requests() {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+        let server = FakeServer::start(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            FakeResponse::json(200, myself())
+        })
+        .await;
+
+        let policy = RetryPolicy {
+            requests_per_second: 20.0,
+            ..fast_policy()
+        };
+        let limiter = Arc::new(RateLimiter::default());
+        let a = JiraClient::new(reqwest::Client::new(), &server.base_url, JiraFlavor::Cloud, "a", "t")
+            .with_retry(policy.clone(), limiter.clone());
+        let b = a.clone();
+
+        let started = Instant::now();
+        let (r1, r2, r3) = tokio::join!(a.get_myself(), b.get_myself(), a.get_myself());
+        r1.unwrap();
+        r2.unwrap();
+        r3.unwrap();
+
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+        // Three requests at 20/s need at least two 50 ms gaps
+        assert!(started.elapsed() >= Duration::from_millis(100));
+    }
+
+    #[tokio::test]
+    async fn data_center_uses_v2_offset_search_and_bearer_pat() {
+        let server = FakeServer::start(|req| {
+            let start: usize = serde_json::from_str::<serde_json::Value>(&req.body)
+                .ok()
+                .and_then(|b| b["startAt"].as_u64())
+                .unwrap_or(0) as usize;
+            let issues: Vec<_> = (start..(start + 2).min(3))
+                .map(|i| serde_json::json!({ "key": format!("OPS-{}", i + 1), "fields": { "summary": "Ops" } }))
+                .collect();
+            FakeResponse::json(
+                200,
+                serde_json::json!({ "startAt": start, "maxResults": 2, "total": 3, "issues": issues }),
+            )
+        })
+        .await;
+
+        let client = JiraClient::new(
+            reqwest::Client::new(),
+            &server.base_url,
+            JiraFlavor::DataCenter,
+            "",
+            "pat-123",
+        );
+        let search = client.search_all_issues("project = OPS", 50).await.unwrap();
+
+        let keys: Vec<_> = search.issues.iter().map(|i| i.issue_key.as_str()).collect();
+        assert_eq!(keys, vec!["OPS-1", "OPS-2", "OPS-3"]);
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests.iter().all(|r| r.path == "/rest/api/2/search"));
+        assert_eq!(requests[0].headers["authorization"], "Bearer pat-123");
+    }
+
+    #[tokio::test]
+    async fn data_center_worklog_comment_is_plain_text() {
+        let server = FakeServer::start(|_| FakeResponse::json(201, serde_json::json!({ "id": "7" }))).await;
+
+        let client = JiraClient::new(
+            reqwest::Client::new(),
+            &server.base_url,
+            JiraFlavor::DataCenter,
+            "",
+            "pat",
+        );
+        client
+            .add_worklog("OPS-1", 900, "2024-03-04T09:00:00.000+0000", "Standup", "local-1")
+            .await
+            .unwrap();
+
+        let req = &server.requests()[0];
+        assert_eq!(req.path, "/rest/api/2/issue/OPS-1/worklog");
+        let body: serde_json::Value = serde_json::from_str(&req.body).unwrap();
+        assert_eq!(body["comment"], "Standup");
+        assert_eq!(comment_text(&body["comment"]), "Standup");
+        assert_eq!(body["properties"][0]["key"], LOCAL_ID_PROPERTY);
+        assert_eq!(body["properties"][0]["value"]["id"], "local-1");
+    }
+
+    #[tokio::test]
+    async fn detects_flavor_from_server_info() {
+        let server = FakeServer::start(|req| {
+            assert_eq!(req.path, "/rest/api/2/serverInfo");
+            FakeResponse::json(200, serde_json::json!({ "deploymentType": "DataCenter", "version": "9.12.0" }))
+        })
+        .await;
+
+        let flavor = JiraClient::detect_flavor(&reqwest::Client::new(), &server.base_url)
+            .await
+            .unwrap();
+        assert_eq!(flavor, JiraFlavor::DataCenter);
+    }
+
+    struct RenewableToken {
+        renewed: std::sync::atomic::AtomicBool,
+    }
+
+    impl AuthProvider for RenewableToken {
+        fn authorization(&self) -> futures::future::BoxFuture<'_, Result<String, JiraError>> {
+            Box::pin(async move {
+                Ok(if self.renewed.load(Ordering::SeqCst) {
+                    "Bearer fresh".to_string()
+                } else {
+                    "Bearer stale".to_string()
+                })
+            })
+        }
+
+        fn recover_unauthorized(&self) -> futures::future::BoxFuture<'_, bool> {
+            Box::pin(async move {
+                self.renewed.store(true, Ordering::SeqCst);
+                true
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn unauthorized_request_is_resent_after_auth_renewal() {
+        let server = FakeServer::start(|req| {
+            if req.headers["authorization"] == "Bearer fresh" {
+                FakeResponse::json(200, myself())
+            } else {
+                FakeResponse::json(401, serde_json::json!({}))
+            }
+        })
+        .await;
+
+        let auth = Arc::new(RenewableToken {
+            renewed: std::sync::atomic::AtomicBool::new(false),
+        });
+        let client = JiraClient::with_auth(reqwest::Client::new(), &server.base_url, JiraFlavor::Cloud, auth)
+            .with_retry(fast_policy(), Arc::new(RateLimiter::default()));
+
+        client.get_myself().await.unwrap();
+        assert_eq!(server.requests().len(), 2);
+    }
+}
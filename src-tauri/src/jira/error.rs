@@ -0,0 +1,172 @@
+use reqwest::header::RETRY_AFTER;
+use reqwest::{Response, StatusCode};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use super::types::JiraErrorBody;
+
+#[derive(Debug, thiserror::Error)]
+pub enum JiraError {
+    #[error("Authentication failed: {0}")]
+    Auth(String),
+    #[error("Not found in Jira: {0}")]
+    NotFound(String),
+    #[error("Jira rate limit exceeded{}", retry_after_hint(.retry_after))]
+    RateLimited { retry_after: Option<Duration> },
+    #[error("Jira rejected the request: {}", describe_validation(.messages, .field_errors))]
+    Validation {
+        messages: Vec<String>,
+        field_errors: BTreeMap<String, String>,
+    },
+    #[error("Jira server error ({status}): {message}")]
+    Server { status: u16, message: String },
+    #[error("Request failed: {0}")]
+    Network(String),
+    #[error("Failed to parse response: {0}")]
+    Parse(String),
+}
+
+impl JiraError {
+    /// Build an error from a non-success response, keeping Jira's own error body.
+    pub async fn from_response(resp: Response) -> Self {
+        let status = resp.status();
+        let retry_after = resp
+            .headers()
+            .get(RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(parse_retry_after);
+        let raw = resp.text().await.unwrap_or_default();
+        Self::from_parts(status, retry_after, &raw)
+    }
+
+    fn from_parts(status: StatusCode, retry_after: Option<Duration>, raw: &str) -> Self {
+        let body: JiraErrorBody = serde_json::from_str(raw).unwrap_or_default();
+        let message = || {
+            let mut parts = body.error_messages.clone();
+            parts.extend(body.errors.iter().map(|(field, msg)| format!("{}: {}", field, msg)));
+            if parts.is_empty() {
+                let trimmed = raw.trim();
+                if trimmed.is_empty() || trimmed.starts_with('<') {
+                    status.canonical_reason().unwrap_or("unknown error").to_string()
+                } else {
+                    trimmed.chars().take(300).collect()
+                }
+            } else {
+                parts.join("; ")
+            }
+        };
+
+        match status {
+            StatusCode::UNAUTHORIZED => Self::Auth(
+                "API token expired or invalid. Please update it in Settings.".to_string(),
+            ),
+            StatusCode::FORBIDDEN => Self::Auth(message()),
+            StatusCode::NOT_FOUND => Self::NotFound(message()),
+            StatusCode::TOO_MANY_REQUESTS => Self::RateLimited { retry_after },
+            s if s.is_client_error() => Self::Validation {
+                messages: if body.error_messages.is_empty() && body.errors.is_empty() {
+                    vec![message()]
+                } else {
+                    body.error_messages.clone()
+                },
+                field_errors: body.errors.clone(),
+            },
+            s => Self::Server {
+                status: s.as_u16(),
+                message: message(),
+            },
+        }
+    }
+
+    /// Transient failures that are worth retrying later as-is. Everything else
+    /// needs the user to change something first.
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            Self::RateLimited { .. } | Self::Server { .. } | Self::Network(_)
+        )
+    }
+
+    /// Jira answered with a 4xx, so the request had no effect. A `Parse` error is
+    /// not one: Jira accepted the request and only its answer was unreadable.
+    pub fn is_rejection(&self) -> bool {
+        matches!(
+            self,
+            Self::Auth(_) | Self::NotFound(_) | Self::Validation { .. }
+        )
+    }
+}
+
+impl From<reqwest::Error> for JiraError {
+    fn from(e: reqwest::Error) -> Self {
+        Self::Network(e.to_string())
+    }
+}
+
+/// `Retry-After` is either delay-seconds or an HTTP date.
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    if let Ok(secs) = value.trim().parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let at = chrono::DateTime::parse_from_rfc2822(value.trim()).ok()?;
+    let wait = at.with_timezone(&chrono::Utc) - chrono::Utc::now();
+    Some(wait.to_std().unwrap_or(Duration::ZERO))
+}
+
+fn retry_after_hint(retry_after: &Option<Duration>) -> String {
+    match retry_after {
+        Some(d) => format!(", retry after {}s", d.as_secs()),
+        None => String::new(),
+    }
+}
+
+fn describe_validation(messages: &[String], field_errors: &BTreeMap<String, String>) -> String {
+    let mut parts = messages.to_vec();
+    parts.extend(field_errors.iter().map(|(field, msg)| format!("{}: {}", field, msg)));
+    parts.join("; ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validation_keeps_jira_messages_and_field_errors() {
+        let err = JiraError::from_parts(
+            StatusCode::BAD_REQUEST,
+            None,
+            r#"{"errorMessages":["Worklog must not be null."],"errors":{"timeLogged":"You must indicate the time spent working."}}"#,
+        );
+        assert!(!err.is_transient());
+        assert_eq!(
+            err.to_string(),
+            "Jira rejected the request: Worklog must not be null.; timeLogged: You must indicate the time spent working."
+        );
+    }
+
+    #[test]
+    fn status_codes_map_to_variants() {
+        assert!(matches!(
+            JiraError::from_parts(StatusCode::UNAUTHORIZED, None, ""),
+            JiraError::Auth(_)
+        ));
+        assert!(matches!(
+            JiraError::from_parts(StatusCode::NOT_FOUND, None, r#"{"errorMessages":["Issue does not exist"]}"#),
+            JiraError::NotFound(msg) if msg == "Issue does not exist"
+        ));
+        let limited = JiraError::from_parts(
+            StatusCode::TOO_MANY_REQUESTS,
+            parse_retry_after("7"),
+            "",
+        );
+        assert!(limited.is_transient());
+        assert!(matches!(
+            limited,
+            JiraError::RateLimited { retry_after: Some(d) } if d == Duration::from_secs(7)
+        ));
+        assert!(matches!(
+            JiraError::from_parts(StatusCode::BAD_GATEWAY, None, "<html>oops</html>"),
+            JiraError::Server { status: 502, message } if message == "Bad Gateway"
+        ));
+    }
+}
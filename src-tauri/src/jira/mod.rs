@@ -1,2 +1,9 @@
+pub mod auth;
 pub mod client;
+pub mod error;
+pub mod oauth;
+pub mod retry;
 pub mod types;
+
+#[cfg(test)]
+pub mod fake_server;
@@ -0,0 +1,467 @@
+//! OAuth 2.0 (3LO) for Jira Cloud: authorization code + PKCE through a loopback
+//! redirect, plus a persisted token store that rotates refresh tokens.
+
+use base64::{engine::general_purpose, Engine as _};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+use super::client::parse_json;
+use super::error::JiraError;
+use crate::secrets::{SecretError, SecretStore};
+
+const AUTHORIZE_URL: &str = "https://auth.atlassian.com/authorize";
+const TOKEN_URL: &str = "https://auth.atlassian.com/oauth/token";
+const ACCESSIBLE_RESOURCES_URL: &str =
+    "https://api.atlassian.com/oauth/token/accessible-resources";
+/// REST calls made with an OAuth token go through this gateway, not the site URL.
+pub const API_GATEWAY_URL: &str = "https://api.atlassian.com/ex/jira";
+const SCOPES: &str = "read:jira-work write:jira-work read:jira-user offline_access";
+/// Refresh this many seconds before the access token's stated expiry.
+const EXPIRY_MARGIN_SECS: i64 = 60;
+const TOKENS_SECRET_KEY: &str = "jira_oauth_tokens";
+
+/// The OAuth app registered in the Atlassian developer console.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OAuthApp {
+    pub client_id: String,
+    #[serde(default)]
+    pub client_secret: Option<String>,
+    /// Port of the `http://127.0.0.1:{port}/callback` redirect registered for the app.
+    pub redirect_port: u16,
+    #[serde(default = "OAuthApp::default_token_url")]
+    pub token_url: String,
+}
+
+impl OAuthApp {
+    pub fn default_token_url() -> String {
+        TOKEN_URL.to_string()
+    }
+}
+
+/// A connected Atlassian site: the app that authorized it and its cloud id.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OAuthConnection {
+    pub app: OAuthApp,
+    pub cloud_id: String,
+}
+
+impl OAuthConnection {
+    pub fn api_base_url(&self) -> String {
+        format!("{}/{}", API_GATEWAY_URL, self.cloud_id)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OAuthTokens {
+    pub access_token: String,
+    pub refresh_token: String,
+    /// Unix seconds
+    pub expires_at: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    #[serde(default)]
+    refresh_token: Option<String>,
+    expires_in: i64,
+}
+
+impl TokenResponse {
+    /// Atlassian rotates refresh tokens, but keep the old one if none came back.
+    fn into_tokens(self, previous_refresh: Option<&str>) -> Result<OAuthTokens, JiraError> {
+        let refresh_token = self
+            .refresh_token
+            .or_else(|| previous_refresh.map(str::to_string))
+            .ok_or_else(|| {
+                JiraError::Auth("Atlassian returned no refresh token; is offline_access granted?".to_string())
+            })?;
+        Ok(OAuthTokens {
+            access_token: self.access_token,
+            refresh_token,
+            expires_at: chrono::Utc::now().timestamp() + self.expires_in,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccessibleResource {
+    pub id: String,
+    pub url: String,
+    pub name: String,
+}
+
+/// Persisted OAuth tokens. The async lock serializes refreshes: with rotation, two
+/// concurrent refreshes using the same refresh token would revoke each other.
+pub struct OAuthTokenStore {
+    secrets: Arc<SecretStore>,
+    tokens: tokio::sync::Mutex<Option<OAuthTokens>>,
+}
+
+impl OAuthTokenStore {
+    pub fn load(secrets: Arc<SecretStore>) -> Self {
+        let saved = secrets.get(TOKENS_SECRET_KEY).unwrap_or_else(|e| {
+            eprintln!("Failed to read OAuth tokens: {}", e);
+            None
+        });
+        let tokens = saved.and_then(|s| serde_json::from_str(&s).ok());
+        Self {
+            secrets,
+            tokens: tokio::sync::Mutex::new(tokens),
+        }
+    }
+
+    pub async fn set(&self, tokens: Option<OAuthTokens>) -> Result<(), SecretError> {
+        let mut guard = self.tokens.lock().await;
+        self.persist(tokens.as_ref())?;
+        *guard = tokens;
+        Ok(())
+    }
+
+    /// Current access token, refreshed first when it is about to expire or when
+    /// `force_refresh` is set (Jira rejected it early).
+    pub async fn access_token(
+        &self,
+        http: &reqwest::Client,
+        app: &OAuthApp,
+        force_refresh: bool,
+    ) -> Result<String, JiraError> {
+        let mut guard = self.tokens.lock().await;
+        let current = guard.as_ref().ok_or_else(|| {
+            JiraError::Auth("Not connected to Atlassian. Connect your account in Settings.".to_string())
+        })?;
+
+        let now = chrono::Utc::now().timestamp();
+        if !force_refresh && current.expires_at - EXPIRY_MARGIN_SECS > now {
+            return Ok(current.access_token.clone());
+        }
+
+        let mut body = serde_json::json!({
+            "grant_type": "refresh_token",
+            "client_id": app.client_id,
+            "refresh_token": current.refresh_token,
+        });
+        if let Some(secret) = &app.client_secret {
+            body["client_secret"] = serde_json::json!(secret);
+        }
+        let refreshed = request_tokens(http, &app.token_url, &body)
+            .await
+            .map_err(|e| match e {
+                JiraError::Auth(_) | JiraError::Validation { .. } => JiraError::Auth(
+                    "Atlassian session expired. Reconnect your account in Settings.".to_string(),
+                ),
+                e => e,
+            })?
+            .into_tokens(Some(&current.refresh_token))?;
+
+        self.persist(Some(&refreshed))
+            .map_err(|e| JiraError::Auth(format!("Failed to save refreshed token: {}", e)))?;
+        let access = refreshed.access_token.clone();
+        *guard = Some(refreshed);
+        Ok(access)
+    }
+
+    fn persist(&self, tokens: Option<&OAuthTokens>) -> Result<(), SecretError> {
+        match tokens {
+            Some(t) => self
+                .secrets
+                .set(TOKENS_SECRET_KEY, &serde_json::to_string(t).unwrap_or_default()),
+            None => self.secrets.delete(TOKENS_SECRET_KEY),
+        }
+    }
+}
+
+async fn request_tokens(
+    http: &reqwest::Client,
+    token_url: &str,
+    body: &serde_json::Value,
+) -> Result<TokenResponse, JiraError> {
+    let resp = http
+        .post(token_url)
+        .header("Accept", "application/json")
+        .json(body)
+        .send()
+        .await?;
+    if !resp.status().is_success() {
+        return Err(JiraError::from_response(resp).await);
+    }
+    parse_json(resp).await
+}
+
+fn random_urlsafe(bytes: usize) -> Result<String, String> {
+    let mut buf = vec![0u8; bytes];
+    getrandom::getrandom(&mut buf).map_err(|e| format!("No secure randomness available: {}", e))?;
+    Ok(general_purpose::URL_SAFE_NO_PAD.encode(buf))
+}
+
+fn pkce_challenge(verifier: &str) -> String {
+    general_purpose::URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()))
+}
+
+/// An authorization in progress: the browser URL to open and the loopback
+/// listener that will receive the redirect.
+pub struct PendingAuthorization {
+    pub authorize_url: String,
+    pub redirect_uri: String,
+    verifier: String,
+    state: String,
+    listener: TcpListener,
+}
+
+impl PendingAuthorization {
+    /// Bind the loopback redirect listener and build the consent URL.
+    pub async fn begin(app: &OAuthApp) -> Result<Self, String> {
+        let listener = TcpListener::bind(("127.0.0.1", app.redirect_port))
+            .await
+            .map_err(|e| format!("Cannot listen on port {} for the OAuth redirect: {}", app.redirect_port, e))?;
+        let port = listener.local_addr().map_err(|e| e.to_string())?.port();
+        // The address the listener binds; `localhost` may resolve to ::1 first
+        let redirect_uri = format!("http://127.0.0.1:{}/callback", port);
+
+        let verifier = random_urlsafe(32)?;
+        let state = random_urlsafe(16)?;
+        let authorize_url = reqwest::Url::parse_with_params(
+            AUTHORIZE_URL,
+            &[
+                ("audience", "api.atlassian.com"),
+                ("client_id", app.client_id.as_str()),
+                ("scope", SCOPES),
+                ("redirect_uri", redirect_uri.as_str()),
+                ("state", state.as_str()),
+                ("response_type", "code"),
+                ("prompt", "consent"),
+                ("code_challenge", pkce_challenge(&verifier).as_str()),
+                ("code_challenge_method", "S256"),
+            ],
+        )
+        .map_err(|e| e.to_string())?
+        .to_string();
+
+        Ok(Self {
+            authorize_url,
+            redirect_uri,
+            verifier,
+            state,
+            listener,
+        })
+    }
+
+    /// Wait for the browser redirect and return the authorization code. Requests
+    /// with a wrong `state` are answered but otherwise ignored.
+    async fn wait_for_code(&self, timeout: Duration) -> Result<String, String> {
+        tokio::time::timeout(timeout, async {
+            loop {
+                let (mut socket, _) = self.listener.accept().await.map_err(|e| e.to_string())?;
+                // The request line may arrive split over several reads
+                let mut received = Vec::new();
+                let mut buf = [0u8; 1024];
+                while !received.windows(2).any(|w| w == b"\r\n") && received.len() < 8192 {
+                    match socket.read(&mut buf).await {
+                        Ok(0) | Err(_) => break,
+                        Ok(n) => received.extend_from_slice(&buf[..n]),
+                    }
+                }
+                let head = String::from_utf8_lossy(&received).to_string();
+                let target = head
+                    .lines()
+                    .next()
+                    .and_then(|l| l.split_whitespace().nth(1))
+                    .unwrap_or("/");
+                let url = reqwest::Url::parse(&format!("http://127.0.0.1{}", target))
+                    .map_err(|e| e.to_string())?;
+                let param = |name: &str| {
+                    url.query_pairs()
+                        .find(|(k, _)| k == name)
+                        .map(|(_, v)| v.to_string())
+                };
+
+                let outcome = if url.path() != "/callback" || param("state").as_deref() != Some(&self.state) {
+                    None
+                } else if let Some(err) = param("error") {
+                    Some(Err(format!(
+                        "Authorization was denied: {}",
+                        param("error_description").unwrap_or(err)
+                    )))
+                } else {
+                    param("code").map(Ok)
+                };
+
+                let message = match &outcome {
+                    Some(Ok(_)) => "Jira Time Tracker is connected. You can close this tab.",
+                    Some(Err(_)) => "Authorization failed. Return to Jira Time Tracker for details.",
+                    None => "Unexpected request.",
+                };
+                let page = format!("<!doctype html><html><body><p>{}</p></body></html>", message);
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    page.len(),
+                    page
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+                let _ = socket.shutdown().await;
+
+                if let Some(result) = outcome {
+                    return result;
+                }
+            }
+        })
+        .await
+        .map_err(|_| "Timed out waiting for Atlassian authorization".to_string())?
+    }
+
+    /// Wait for the redirect, then exchange the code for tokens.
+    pub async fn complete(
+        self,
+        http: &reqwest::Client,
+        app: &OAuthApp,
+        timeout: Duration,
+    ) -> Result<OAuthTokens, String> {
+        let code = self.wait_for_code(timeout).await?;
+        let mut body = serde_json::json!({
+            "grant_type": "authorization_code",
+            "client_id": app.client_id,
+            "code": code,
+            "redirect_uri": self.redirect_uri,
+            "code_verifier": self.verifier,
+        });
+        if let Some(secret) = &app.client_secret {
+            body["client_secret"] = serde_json::json!(secret);
+        }
+        request_tokens(http, &app.token_url, &body)
+            .await
+            .and_then(|t| t.into_tokens(None))
+            .map_err(|e| format!("Token exchange failed: {}", e))
+    }
+}
+
+/// Sites the access token may call. Each has the cloud id used in API URLs.
+pub async fn accessible_resources(
+    http: &reqwest::Client,
+    access_token: &str,
+) -> Result<Vec<AccessibleResource>, JiraError> {
+    let resp = http
+        .get(ACCESSIBLE_RESOURCES_URL)
+        .header("Authorization", format!("Bearer {}", access_token))
+        .header("Accept", "application/json")
+        .send()
+        .await?;
+    if !resp.status().is_success() {
+        return Err(JiraError::from_response(resp).await);
+    }
+    parse_json(resp).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jira::fake_server::{FakeResponse, FakeServer};
+
+    #[test]
+    fn pkce_challenge_matches_rfc7636_example() {
+        assert_eq!(
+            pkce_challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"),
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+        );
+    }
+
+    #[tokio::test]
+    async fn loopback_listener_returns_code_for_matching_state() {
+        let app = OAuthApp {
+            client_id: "client".to_string(),
+            client_secret: None,
+            redirect_port: 0,
+            token_url: OAuthApp::default_token_url(),
+        };
+        let pending = PendingAuthorization::begin(&app).await.unwrap();
+        assert!(pending.authorize_url.contains("code_challenge_method=S256"));
+
+        let forged = format!("{}?code=evil&state=wrong", pending.redirect_uri);
+        let real = format!("{}?code=abc&state={}", pending.redirect_uri, pending.state);
+        tokio::spawn(async move {
+            let http = reqwest::Client::new();
+            http.get(forged).send().await.unwrap();
+            http.get(real).send().await.unwrap();
+        });
+
+        let code = pending.wait_for_code(Duration::from_secs(5)).await.unwrap();
+        assert_eq!(code, "abc");
+    }
+
+    #[tokio::test]
+    async fn loopback_listener_reads_a_request_line_sent_in_pieces() {
+        let app = OAuthApp {
+            client_id: "client".to_string(),
+            client_secret: None,
+            redirect_port: 0,
+            token_url: OAuthApp::default_token_url(),
+        };
+        let pending = PendingAuthorization::begin(&app).await.unwrap();
+        let addr = pending.listener.local_addr().unwrap();
+        let target = format!("/callback?code=abc&state={}", pending.state);
+        tokio::spawn(async move {
+            let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+            stream.write_all(b"GET /callback?co").await.unwrap();
+            stream.flush().await.unwrap();
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            let rest = format!("{} HTTP/1.1\r\nHost: 127.0.0.1\r\n\r\n", &target["/callback?co".len()..]);
+            stream.write_all(rest.as_bytes()).await.unwrap();
+            let mut response = Vec::new();
+            let _ = stream.read_to_end(&mut response).await;
+        });
+
+        let code = pending.wait_for_code(Duration::from_secs(5)).await.unwrap();
+        assert_eq!(code, "abc");
+    }
+
+    #[tokio::test]
+    async fn expired_token_is_refreshed_and_rotation_persisted() {
+        let server = FakeServer::start(|req| {
+            let body: serde_json::Value = serde_json::from_str(&req.body).unwrap();
+            assert_eq!(body["grant_type"], "refresh_token");
+            assert_eq!(body["refresh_token"], "refresh-1");
+            FakeResponse::json(
+                200,
+                serde_json::json!({
+                    "access_token": "access-2",
+                    "refresh_token": "refresh-2",
+                    "expires_in": 3600
+                }),
+            )
+        })
+        .await;
+        let app = OAuthApp {
+            client_id: "client".to_string(),
+            client_secret: Some("secret".to_string()),
+            redirect_port: 0,
+            token_url: format!("{}/oauth/token", server.base_url),
+        };
+
+        let dir = std::env::temp_dir().join(format!("jtt-oauth-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let secrets = Arc::new(SecretStore::open_file(&dir).unwrap());
+        let store = OAuthTokenStore::load(secrets.clone());
+        store
+            .set(Some(OAuthTokens {
+                access_token: "access-1".to_string(),
+                refresh_token: "refresh-1".to_string(),
+                expires_at: chrono::Utc::now().timestamp() - 10,
+            }))
+            .await
+            .unwrap();
+
+        let http = reqwest::Client::new();
+        assert_eq!(store.access_token(&http, &app, false).await.unwrap(), "access-2");
+        // Still fresh: no second refresh
+        assert_eq!(store.access_token(&http, &app, false).await.unwrap(), "access-2");
+        assert_eq!(server.requests().len(), 1);
+
+        let reloaded = OAuthTokenStore::load(secrets);
+        let saved = reloaded.tokens.lock().await.clone().unwrap();
+        assert_eq!(saved.refresh_token, "refresh-2");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
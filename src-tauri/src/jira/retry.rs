@@ -0,0 +1,117 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Slowest request rate that can be configured: one request every 100 seconds.
+pub const MIN_REQUESTS_PER_SECOND: f64 = 0.01;
+
+/// How `JiraClient` retries failed requests and how fast it may talk to Jira.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt; 0 disables retrying.
+    pub max_retries: u32,
+    /// First backoff delay, doubled on every further attempt.
+    pub base_delay_ms: u64,
+    /// Upper bound for a single backoff or `Retry-After` wait.
+    pub max_delay_ms: u64,
+    /// Requests per second across all clients sharing a `RateLimiter`; 0 disables throttling.
+    pub requests_per_second: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 4,
+            base_delay_ms: 500,
+            max_delay_ms: 30_000,
+            requests_per_second: 5.0,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Exponential backoff with jitter: a random delay between half and all of
+    /// `base * 2^attempt`, capped at `max_delay_ms`.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exp = self
+            .base_delay_ms
+            .saturating_mul(1u64 << attempt.min(20))
+            .min(self.max_delay_ms);
+        let jittered = exp / 2 + fastrand::u64(0..=exp / 2);
+        Duration::from_millis(jittered)
+    }
+
+    pub fn max_delay(&self) -> Duration {
+        Duration::from_millis(self.max_delay_ms)
+    }
+
+    /// Zero (no limit) or at least [`MIN_REQUESTS_PER_SECOND`]; checked wherever
+    /// a rate is loaded or saved.
+    pub fn valid_rate(requests_per_second: f64) -> bool {
+        requests_per_second == 0.0 || (requests_per_second >= MIN_REQUESTS_PER_SECOND && requests_per_second.is_finite())
+    }
+
+    fn interval(&self) -> Duration {
+        if self.requests_per_second > 0.0 {
+            Duration::try_from_secs_f64(1.0 / self.requests_per_second).unwrap_or_default()
+        } else {
+            Duration::ZERO
+        }
+    }
+}
+
+/// Spaces requests evenly and lets a single 429 pause every request that shares it.
+#[derive(Debug)]
+pub struct RateLimiter {
+    next_slot: Mutex<Instant>,
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self {
+            next_slot: Mutex::new(Instant::now()),
+        }
+    }
+}
+
+impl RateLimiter {
+    /// Wait for the next free slot under `policy`'s request rate.
+    pub async fn acquire(&self, policy: &RetryPolicy) {
+        let slot = {
+            let mut next = self.next_slot.lock().unwrap_or_else(|e| e.into_inner());
+            let slot = (*next).max(Instant::now());
+            *next = slot + policy.interval();
+            slot
+        };
+        tokio::time::sleep_until(slot).await;
+    }
+
+    /// Hold back every request until `wait` has passed, e.g. after a `Retry-After`.
+    pub fn pause_for(&self, wait: Duration) {
+        let until = Instant::now() + wait;
+        let mut next = self.next_slot.lock().unwrap_or_else(|e| e.into_inner());
+        if *next < until {
+            *next = until;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn request_rates_are_bounded() {
+        assert!(RetryPolicy::valid_rate(0.0));
+        assert!(RetryPolicy::valid_rate(MIN_REQUESTS_PER_SECOND));
+        assert!(!RetryPolicy::valid_rate(1e-300));
+        assert!(!RetryPolicy::valid_rate(-1.0));
+        assert!(!RetryPolicy::valid_rate(f64::INFINITY));
+        let slowest = RetryPolicy {
+            requests_per_second: MIN_REQUESTS_PER_SECOND,
+            ..RetryPolicy::default()
+        };
+        assert_eq!(slowest.interval(), Duration::from_secs(100));
+    }
+}
@@ -1,8 +1,45 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
+/// Which Jira product the client talks to. Cloud uses REST API v3, ADF comments and
+/// Basic auth with an API token; Server/Data Center uses REST API v2, plain-text
+/// comments and a Personal Access Token as Bearer.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum JiraFlavor {
+    #[default]
+    Cloud,
+    DataCenter,
+}
+
+impl JiraFlavor {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Cloud => "cloud",
+            Self::DataCenter => "data_center",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "cloud" => Some(Self::Cloud),
+            "data_center" => Some(Self::DataCenter),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct JiraServerInfo {
+    #[serde(rename = "deploymentType", default)]
+    pub deployment_type: Option<String>,
+}
+
+/// Server/Data Center has no `accountId`; the username (`name`) plays the same role
+/// in JQL and worklog authors.
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct JiraUser {
-    #[serde(rename = "accountId")]
+    #[serde(rename = "accountId", alias = "name")]
     pub account_id: String,
     #[serde(rename = "displayName")]
     pub display_name: String,
@@ -13,6 +50,22 @@ pub struct JiraUser {
 #[derive(Debug, Deserialize)]
 pub struct JiraSearchResponse {
     pub issues: Vec<JiraIssueRaw>,
+    #[serde(rename = "nextPageToken", default)]
+    pub next_page_token: Option<String>,
+    #[serde(rename = "isLast", default)]
+    pub is_last: Option<bool>,
+    /// Offset paging, only returned by the v2 `/search` endpoint
+    #[serde(rename = "startAt", default)]
+    pub start_at: Option<usize>,
+    #[serde(default)]
+    pub total: Option<usize>,
+}
+
+/// Issues collected across search pages, and whether the limit cut the result short.
+#[derive(Debug, Default)]
+pub struct JiraIssueSearch {
+    pub issues: Vec<JiraIssue>,
+    pub truncated: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -68,28 +121,87 @@ impl From<JiraIssueRaw> for JiraIssue {
 #[derive(Debug, Deserialize)]
 pub struct JiraWorklogResponse {
     pub id: String,
+    /// Jira's last-modified timestamp, kept as `jira_updated_at` to detect
+    /// changes made in Jira after we synced
+    #[serde(default)]
+    pub updated: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct JiraWorklogListResponse {
+    #[serde(rename = "startAt", default)]
+    pub start_at: i64,
+    #[serde(default)]
+    pub total: i64,
     pub worklogs: Vec<JiraWorklogEntry>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct JiraWorklogEntry {
     pub id: String,
+    /// Set by `/worklog/list`, which returns worklogs of many issues at once
+    #[serde(rename = "issueId", default)]
+    pub issue_id: Option<String>,
     pub started: String,
     pub updated: String,
     #[serde(rename = "timeSpentSeconds")]
     pub time_spent_seconds: i64,
     pub comment: Option<serde_json::Value>,
     pub author: JiraWorklogAuthor,
+    /// Only filled when requested with `expand=properties`
+    #[serde(default)]
+    pub properties: Vec<JiraEntityProperty>,
+}
+
+/// One page of the `/worklog/updated` or `/worklog/deleted` feed.
+#[derive(Debug, Deserialize)]
+pub struct JiraWorklogChangePage {
+    pub values: Vec<JiraWorklogChange>,
+    pub until: i64,
+    #[serde(rename = "lastPage", default = "default_last_page")]
+    pub last_page: bool,
+}
+
+fn default_last_page() -> bool {
+    true
+}
+
+/// A feed entry carries no author, so it can't tell whose worklog changed.
+#[derive(Debug, Deserialize)]
+pub struct JiraWorklogChange {
+    #[serde(rename = "worklogId")]
+    pub worklog_id: i64,
+    /// Epoch ms; the feed is ordered by it
+    #[serde(rename = "updatedTime", default)]
+    pub updated_time: i64,
+}
+
+/// Changes collected across feed pages, and the `since` to ask for next time.
+#[derive(Debug, Default)]
+pub struct JiraWorklogChanges {
+    pub values: Vec<JiraWorklogChange>,
+    pub until: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct JiraEntityProperty {
+    pub key: String,
+    pub value: serde_json::Value,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct JiraWorklogAuthor {
-    #[serde(rename = "accountId")]
+    #[serde(rename = "accountId", alias = "name")]
     pub account_id: String,
     #[serde(rename = "displayName", default)]
     pub display_name: Option<String>,
 }
+
+/// Error body Jira returns on 4xx/5xx responses.
+#[derive(Debug, Default, Deserialize)]
+pub struct JiraErrorBody {
+    #[serde(rename = "errorMessages", default)]
+    pub error_messages: Vec<String>,
+    #[serde(default)]
+    pub errors: BTreeMap<String, String>,
+}
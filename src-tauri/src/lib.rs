@@ -1,7 +1,14 @@
 mod commands;
+mod conflicts;
 mod db;
 mod jira;
+mod rounding;
+mod secrets;
 mod state;
+mod sync;
+mod sync_events;
+mod timer_tick;
+mod tray_menu;
 
 use sqlx::sqlite::SqlitePoolOptions;
 use std::sync::atomic::Ordering;
@@ -40,14 +47,24 @@ pub fn run() {
             commands::settings::settings_get_all,
             commands::settings::settings_save_jira_config,
             commands::settings::settings_load_jira_config,
+            commands::settings::settings_get_jira_retry_policy,
+            commands::settings::settings_save_jira_retry_policy,
+            commands::settings::settings_get_rounding,
+            commands::settings::settings_save_rounding,
+            commands::jira::jira_detect_flavor,
             commands::jira::jira_test_connection,
+            commands::jira::jira_oauth_connect,
+            commands::jira::jira_oauth_disconnect,
             commands::jira::jira_get_myself,
             commands::jira::jira_search_issues,
             commands::jira::jira_push_worklog,
             commands::jira::jira_push_all_pending,
+            commands::jira::jira_push_pending,
+            commands::jira::jira_link_worklog,
             commands::jira::jira_update_worklog,
             commands::jira::jira_delete_worklog,
             commands::jira::jira_import_worklogs,
+            commands::jira::jira_import_worklog_changes,
             commands::jira::jira_search_users,
             commands::jira::jira_fetch_user_worklogs,
             commands::jira::jira_fetch_issue_worklogs,
@@ -55,14 +72,27 @@ pub fn run() {
             commands::timer::timer_pause,
             commands::timer::timer_resume,
             commands::timer::timer_stop,
+            commands::timer::timer_adjust,
+            commands::timer::timer_switch,
+            commands::timer::timer_list,
+            commands::timer::timer_rename,
             commands::timer::timer_get_state,
-            commands::timer::timer_update_tray,
             commands::timer::timer_update_description,
-            commands::timer::timer_set_tray_icon,
             commands::worklogs::get_worklogs,
             commands::worklogs::create_worklog,
             commands::worklogs::update_worklog,
             commands::worklogs::delete_worklog,
+            commands::sync::sync_get_settings,
+            commands::sync::sync_save_settings,
+            commands::sync::sync_get_status,
+            commands::sync::sync_get_events,
+            commands::sync::sync_now,
+            commands::conflicts::conflict_list,
+            commands::conflicts::conflict_resolve,
+            commands::backup::db_list_backups,
+            commands::backup::db_create_backup,
+            commands::backup::db_restore_backup,
+            commands::backup::db_integrity_status,
             commands::updater::check_for_update,
             commands::quit_app,
             commands::set_dock_visible,
@@ -80,18 +110,61 @@ pub fn run() {
                 .expect("Failed to get app data dir");
             std::fs::create_dir_all(&app_dir).expect("Failed to create app data dir");
             let db_path = app_dir.join("jira-tracker.db");
+            match db::backup::apply_pending_restore(&app_dir, &db_path) {
+                Ok(Some(name)) => eprintln!("Restored database from backup {}", name),
+                Ok(None) => {}
+                Err(e) => eprintln!("Failed to restore database backup: {}", e),
+            }
+            let db_existed = db_path.exists();
+            let backup_dir = app_dir.join(db::backup::BACKUP_DIR);
             let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
 
-            let (pool, saved_window_pos) = tauri::async_runtime::block_on(async {
-                let pool = SqlitePoolOptions::new()
-                    .max_connections(5)
-                    .connect(&db_url)
-                    .await
-                    .expect("Failed to create database pool");
+            // Without its secrets the app still starts; the Jira settings report why
+            let secrets = std::sync::Arc::new(secrets::SecretStore::open(&app_dir).unwrap_or_else(|e| {
+                eprintln!("Failed to open secret store: {}", e);
+                secrets::SecretStore::unavailable(&e)
+            }));
+
+            let (pool, saved_window_pos, retry_policy, integrity_problems) = tauri::async_runtime::block_on(async {
+                let options = SqlitePoolOptions::new().max_connections(5);
+                let (pool, integrity_problems) = match options.clone().connect(&db_url).await {
+                    Ok(pool) => {
+                        let problems = db::backup::integrity_check(&pool)
+                            .await
+                            .unwrap_or_else(|e| vec![e.to_string()]);
+                        (pool, problems)
+                    }
+                    // A file SQLite can't even open still gets a pool, so the app
+                    // starts far enough to offer a restore
+                    Err(e) => (
+                        options.connect_lazy(&db_url).expect("Invalid database URL"),
+                        vec![e.to_string()],
+                    ),
+                };
+                if !integrity_problems.is_empty() {
+                    // Degraded start: nothing touches the damaged file until the
+                    // user restores a backup
+                    eprintln!("Database integrity check failed: {:?}", integrity_problems);
+                    return (pool, None, jira::retry::RetryPolicy::default(), integrity_problems);
+                }
+                if db_existed {
+                    // Snapshot before migrations touch anything. Skipped for a damaged
+                    // file so it can't rotate the last good backups away.
+                    let retention = db::backup::load_retention(&pool).await;
+                    if let Err(e) = db::backup::create_backup(&pool, &backup_dir, retention).await {
+                        eprintln!("Startup backup failed: {}", e);
+                    }
+                }
+
+                // A half-migrated database is left alone like a damaged one
+                if let Err(e) = db::migrations::run_migrations(&pool).await {
+                    eprintln!("Database migration failed: {}", e);
+                    return (pool, None, jira::retry::RetryPolicy::default(), vec![e.to_string()]);
+                }
 
-                db::migrations::run_migrations(&pool)
-                    .await
-                    .expect("Failed to run migrations");
+                if let Err(e) = secrets::migrate_plaintext_secrets(&pool, &secrets).await {
+                    eprintln!("Failed to move secrets out of settings: {}", e);
+                }
 
                 let saved_pos = sqlx::query_scalar::<_, String>(
                     "SELECT value FROM settings WHERE key = 'window_position'",
@@ -109,17 +182,35 @@ pub fn run() {
                     Some((x, y))
                 });
 
-                (pool, saved_pos)
+                let retry_policy = commands::settings::load_retry_policy(&pool).await;
+
+                (pool, saved_pos, retry_policy, integrity_problems)
             });
+            let healthy = integrity_problems.is_empty();
+            if healthy {
+                tauri::async_runtime::spawn(db::backup::run_daily(pool.clone(), backup_dir));
+            }
+            let oauth_tokens = jira::oauth::OAuthTokenStore::load(secrets.clone());
 
             app.manage(state::AppState {
                 db: pool,
+                app_dir,
+                integrity_problems,
                 jira_config: Mutex::new(None),
                 window_position: Mutex::new(saved_window_pos),
                 cached_account_id: Mutex::new(None),
                 http_client: reqwest::Client::new(),
+                jira_retry_policy: Mutex::new(retry_policy),
+                jira_rate_limiter: Default::default(),
+                oauth_tokens: std::sync::Arc::new(oauth_tokens),
+                secrets,
+                sync: Default::default(),
+                tray_menu_stale: AtomicBool::new(true),
                 suppress_blur_hide: AtomicBool::new(false),
             });
+            if healthy {
+                tauri::async_runtime::spawn(sync::run(app.handle().clone()));
+            }
 
             // Build tray context menu (required on Linux for icon visibility)
             let show_hide = MenuItem::with_id(app, "show-hide", "Show/Hide", true, None::<&str>)?;
@@ -218,7 +309,7 @@ pub fn run() {
                             }
                             app.exit(0);
                         }
-                        _ => {}
+                        id => tray_menu::handle_event(app, id),
                     }
                 })
                 .on_tray_icon_event(|tray, event| {
@@ -325,6 +416,9 @@ pub fn run() {
                     }
                 })
                 .build(app)?;
+            if healthy {
+                tauri::async_runtime::spawn(timer_tick::run(app.handle().clone()));
+            }
 
             // Show window on startup so the user sees the app immediately
             if let Some(window) = app.get_webview_window("main") {
@@ -354,60 +448,58 @@ pub fn run() {
         })
         .on_window_event(|window, event| {
             match event {
-                WindowEvent::Moved(pos) => {
-                    if window.label() == "main" {
-                        if let Some(app_state) = window.try_state::<state::AppState>() {
-                            *app_state.window_position.lock().unwrap_or_else(|e| e.into_inner()) =
-                                Some((pos.x, pos.y));
-                        }
+                WindowEvent::Moved(pos) if window.label() == "main" => {
+                    if let Some(app_state) = window.try_state::<state::AppState>() {
+                        *app_state.window_position.lock().unwrap_or_else(|e| e.into_inner()) =
+                            Some((pos.x, pos.y));
                     }
                 }
-                WindowEvent::Focused(false) => {
-                    if window.label() == "main" {
-                        // Persist position to SQLite on all platforms
-                        if let Some(app_state) = window.try_state::<state::AppState>() {
-                            let saved = *app_state.window_position.lock().unwrap_or_else(|e| e.into_inner());
-                            if let Some((x, y)) = saved {
-                                let db = app_state.db.clone();
-                                let val = format!("{},{}", x, y);
-                                tauri::async_runtime::spawn(async move {
-                                    if let Err(e) = sqlx::query(
-                                        "INSERT INTO settings (key, value) VALUES ('window_position', ?1) \
-                                         ON CONFLICT(key) DO UPDATE SET value = ?1",
-                                    )
-                                    .bind(val)
-                                    .execute(&db)
-                                    .await
-                                    {
-                                        eprintln!("Failed to save window position: {}", e);
-                                    }
-                                });
-                            }
-                        }
-                        // On Linux, skip blur-hide entirely — native widgets (<select>,
-                        // date pickers) cause focus loss that breaks the UX. Users hide
-                        // the window via the tray menu or the in-app hide button instead.
-                        #[cfg(not(target_os = "linux"))]
-                        {
-                        // Skip hide if tray click is in progress (prevents race condition
-                        // where tray show + immediate blur-hide cancel each other out)
-                        let suppressed = window
-                            .app_handle()
-                            .try_state::<state::AppState>()
-                            .map(|s| s.suppress_blur_hide.load(Ordering::SeqCst))
-                            .unwrap_or(false);
-                        if !suppressed {
-                            // Debounce hide — on Windows startDragging() briefly loses focus
-                            let window_clone = window.clone();
+                WindowEvent::Focused(false) if window.label() == "main" => {
+                    // Persist position to SQLite on all platforms, but leave a damaged
+                    // database untouched until it has been restored
+                    let app_state = window.try_state::<state::AppState>();
+                    if let Some(app_state) = app_state.filter(|s| s.integrity_problems.is_empty()) {
+                        let saved = *app_state.window_position.lock().unwrap_or_else(|e| e.into_inner());
+                        if let Some((x, y)) = saved {
+                            let db = app_state.db.clone();
+                            let val = format!("{},{}", x, y);
                             tauri::async_runtime::spawn(async move {
-                                tokio::time::sleep(std::time::Duration::from_millis(200)).await;
-                                if !window_clone.is_focused().unwrap_or(true) {
-                                    let _ = window_clone.hide();
+                                if let Err(e) = sqlx::query(
+                                    "INSERT INTO settings (key, value) VALUES ('window_position', ?1) \
+                                     ON CONFLICT(key) DO UPDATE SET value = ?1",
+                                )
+                                .bind(val)
+                                .execute(&db)
+                                .await
+                                {
+                                    eprintln!("Failed to save window position: {}", e);
                                 }
                             });
                         }
-                        } // #[cfg(not(target_os = "linux"))]
                     }
+                    // On Linux, skip blur-hide entirely — native widgets (<select>,
+                    // date pickers) cause focus loss that breaks the UX. Users hide
+                    // the window via the tray menu or the in-app hide button instead.
+                    #[cfg(not(target_os = "linux"))]
+                    {
+                    // Skip hide if tray click is in progress (prevents race condition
+                    // where tray show + immediate blur-hide cancel each other out)
+                    let suppressed = window
+                        .app_handle()
+                        .try_state::<state::AppState>()
+                        .map(|s| s.suppress_blur_hide.load(Ordering::SeqCst))
+                        .unwrap_or(false);
+                    if !suppressed {
+                        // Debounce hide — on Windows startDragging() briefly loses focus
+                        let window_clone = window.clone();
+                        tauri::async_runtime::spawn(async move {
+                            tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+                            if !window_clone.is_focused().unwrap_or(true) {
+                                let _ = window_clone.hide();
+                            }
+                        });
+                    }
+                    } // #[cfg(not(target_os = "linux"))]
                 }
                 WindowEvent::Destroyed => {
                     #[cfg(target_os = "macos")]
@@ -0,0 +1,184 @@
+//! Billing rounding for worklog durations.
+//!
+//! Worklogs keep the measured time in `raw_duration_seconds` and the rounded
+//! time in `duration_seconds`, which is what Jira receives. The policy is applied
+//! when a timer is stopped and when a worklog is created or edited by hand; with
+//! `at_push` it is applied again right before a worklog is sent, so a policy
+//! changed since the worklog was recorded still holds.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RoundingMode {
+    None,
+    Up,
+    Nearest,
+    Down,
+}
+
+impl RoundingMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Up => "up",
+            Self::Nearest => "nearest",
+            Self::Down => "down",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "none" => Some(Self::None),
+            "up" => Some(Self::Up),
+            "nearest" => Some(Self::Nearest),
+            "down" => Some(Self::Down),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RoundingPolicy {
+    pub mode: RoundingMode,
+    /// Step the duration is rounded to, e.g. 900 for quarter hours
+    pub increment_seconds: i64,
+    /// Anything tracked is billed at least this long; 0 disables it
+    pub minimum_seconds: i64,
+    /// Also round pending worklogs right before they are pushed
+    pub at_push: bool,
+}
+
+/// Whole minutes, rounded up: what the timer always did before policies existed.
+impl Default for RoundingPolicy {
+    fn default() -> Self {
+        Self {
+            mode: RoundingMode::Up,
+            increment_seconds: 60,
+            minimum_seconds: 0,
+            at_push: false,
+        }
+    }
+}
+
+impl RoundingPolicy {
+    /// The billed duration for `raw_seconds` of tracked time. Nothing tracked
+    /// stays nothing, whatever the minimum.
+    pub fn apply(&self, raw_seconds: i64) -> i64 {
+        if raw_seconds <= 0 {
+            return 0;
+        }
+        let step = self.increment_seconds.max(1);
+        let rounded = match self.mode {
+            RoundingMode::None => raw_seconds,
+            RoundingMode::Up => (raw_seconds + step - 1) / step * step,
+            RoundingMode::Nearest => (raw_seconds + step / 2) / step * step,
+            RoundingMode::Down => raw_seconds / step * step,
+        };
+        rounded.max(self.minimum_seconds)
+    }
+}
+
+const KEYS: [&str; 4] = [
+    "rounding_mode",
+    "rounding_increment_seconds",
+    "rounding_minimum_seconds",
+    "rounding_at_push",
+];
+
+pub async fn load<'e, E>(db: E) -> Result<RoundingPolicy, sqlx::Error>
+where
+    E: sqlx::SqliteExecutor<'e>,
+{
+    let rows: Vec<(String, String)> = sqlx::query_as(
+        "SELECT key, value FROM settings WHERE key IN (?1, ?2, ?3, ?4)",
+    )
+    .bind(KEYS[0])
+    .bind(KEYS[1])
+    .bind(KEYS[2])
+    .bind(KEYS[3])
+    .fetch_all(db)
+    .await?;
+
+    let mut policy = RoundingPolicy::default();
+    for (key, value) in rows {
+        match key.as_str() {
+            "rounding_mode" => policy.mode = RoundingMode::parse(&value).unwrap_or(policy.mode),
+            "rounding_increment_seconds" => {
+                policy.increment_seconds = value.parse().unwrap_or(policy.increment_seconds)
+            }
+            "rounding_minimum_seconds" => {
+                policy.minimum_seconds = value.parse().unwrap_or(policy.minimum_seconds)
+            }
+            "rounding_at_push" => policy.at_push = value == "true",
+            _ => {}
+        }
+    }
+    Ok(policy)
+}
+
+pub async fn save(db: &sqlx::SqlitePool, policy: &RoundingPolicy) -> Result<(), sqlx::Error> {
+    let values = [
+        policy.mode.as_str().to_string(),
+        policy.increment_seconds.to_string(),
+        policy.minimum_seconds.to_string(),
+        policy.at_push.to_string(),
+    ];
+    for (key, value) in KEYS.iter().zip(values) {
+        sqlx::query(
+            "INSERT INTO settings (key, value) VALUES (?1, ?2) ON CONFLICT(key) DO UPDATE SET value = ?2",
+        )
+        .bind(key)
+        .bind(value)
+        .execute(db)
+        .await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(mode: RoundingMode, increment_minutes: i64, minimum_minutes: i64) -> RoundingPolicy {
+        RoundingPolicy {
+            mode,
+            increment_seconds: increment_minutes * 60,
+            minimum_seconds: minimum_minutes * 60,
+            at_push: false,
+        }
+    }
+
+    #[test]
+    fn rounds_to_the_increment_in_each_mode() {
+        let raw = 22 * 60 + 30;
+        assert_eq!(policy(RoundingMode::None, 15, 0).apply(raw), raw);
+        assert_eq!(policy(RoundingMode::Up, 15, 0).apply(raw), 30 * 60);
+        assert_eq!(policy(RoundingMode::Nearest, 15, 0).apply(raw), 30 * 60);
+        assert_eq!(policy(RoundingMode::Nearest, 5, 0).apply(raw), 25 * 60);
+        assert_eq!(policy(RoundingMode::Down, 15, 0).apply(raw), 15 * 60);
+        // Exact multiples are left alone
+        assert_eq!(policy(RoundingMode::Up, 15, 0).apply(45 * 60), 45 * 60);
+    }
+
+    #[test]
+    fn minimum_applies_only_to_tracked_time() {
+        let billed = policy(RoundingMode::Down, 15, 15);
+        assert_eq!(billed.apply(5 * 60), 15 * 60);
+        assert_eq!(billed.apply(0), 0);
+        assert_eq!(RoundingPolicy::default().apply(61), 120);
+    }
+
+    #[tokio::test]
+    async fn saved_policy_round_trips_through_settings() {
+        let db = crate::db::test_pool().await;
+        assert_eq!(load(&db).await.unwrap(), RoundingPolicy::default());
+
+        let quarter_hours = RoundingPolicy {
+            at_push: true,
+            ..policy(RoundingMode::Nearest, 15, 15)
+        };
+        save(&db, &quarter_hours).await.unwrap();
+        assert_eq!(load(&db).await.unwrap(), quarter_hours);
+    }
+}
@@ -0,0 +1,419 @@
+//! Storage for credentials that must not sit in the plaintext `settings` table.
+//!
+//! The OS keyring (macOS Keychain, Windows Credential Manager, Secret Service on
+//! Linux) is used when it works; otherwise secrets go to an encrypted file in the
+//! app data dir, keyed by a random local master key or by a passphrase from
+//! `JIRA_TRACKER_SECRETS_PASSPHRASE`. The choice is made once and remembered, so a
+//! keyring that is locked at some later launch is reported instead of replaced.
+
+use base64::{engine::general_purpose, Engine as _};
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Settings keys whose values are secrets. They live in the secret store and are
+/// never served by `settings_get`/`settings_get_all` nor accepted by `settings_set`.
+pub const SECRET_KEYS: [&str; 3] = ["jira_api_token", "jira_oauth_tokens", "jira_oauth_app"];
+
+const KEYRING_SERVICE: &str = "jira-time-tracker";
+const PASSPHRASE_ENV: &str = "JIRA_TRACKER_SECRETS_PASSPHRASE";
+const SECRETS_FILE: &str = "secrets.enc";
+const MASTER_KEY_FILE: &str = "secrets.key";
+/// Names the backend chosen on first launch: `keyring` or `file`
+const BACKEND_FILE: &str = "secrets.backend";
+
+pub fn is_secret_key(key: &str) -> bool {
+    SECRET_KEYS.contains(&key)
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SecretError {
+    #[error("Keyring error: {0}")]
+    Keyring(#[from] keyring::Error),
+    #[error("Secret file error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Cannot decrypt secrets: wrong master key or passphrase, or the file is corrupted")]
+    Decrypt,
+    #[error("Invalid secret file: {0}")]
+    Format(String),
+    #[error("Secret store unavailable: {0}")]
+    Unavailable(String),
+}
+
+enum Backend {
+    Keyring,
+    File(EncryptedFile),
+    /// Opening the store failed; every access reports why
+    Unavailable(String),
+}
+
+pub struct SecretStore {
+    backend: Backend,
+}
+
+impl SecretStore {
+    /// Open the backend chosen on first launch. That first launch prefers the OS
+    /// keyring and falls back to the encrypted file when the keyring is missing or
+    /// locked (e.g. no Secret Service on a minimal Linux desktop), or when an
+    /// encrypted file from an earlier version already holds the secrets.
+    pub fn open(app_dir: &Path) -> Result<Self, SecretError> {
+        let marker = app_dir.join(BACKEND_FILE);
+        let chosen = match std::fs::read_to_string(&marker) {
+            Ok(name) => name.trim().to_string(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                let name = if !app_dir.join(SECRETS_FILE).exists() && keyring_available() {
+                    "keyring"
+                } else {
+                    "file"
+                };
+                write_private(&marker, name.as_bytes())?;
+                name.to_string()
+            }
+            Err(e) => return Err(e.into()),
+        };
+        match chosen.as_str() {
+            "keyring" => Ok(Self {
+                backend: Backend::Keyring,
+            }),
+            "file" => Self::open_file(app_dir),
+            other => Err(SecretError::Format(format!("unknown secret backend '{}'", other))),
+        }
+    }
+
+    /// A store that could not be opened. The app still starts, and reading or
+    /// saving a secret returns `error` so the UI can show it.
+    pub fn unavailable(error: &SecretError) -> Self {
+        Self {
+            backend: Backend::Unavailable(error.to_string()),
+        }
+    }
+
+    pub fn open_file(app_dir: &Path) -> Result<Self, SecretError> {
+        let key = match std::env::var(PASSPHRASE_ENV) {
+            Ok(passphrase) if !passphrase.is_empty() => MasterKey::Passphrase(passphrase),
+            _ => MasterKey::KeyFile(app_dir.join(MASTER_KEY_FILE)),
+        };
+        Ok(Self {
+            backend: Backend::File(EncryptedFile::open(app_dir.join(SECRETS_FILE), key)?),
+        })
+    }
+
+    pub fn get(&self, key: &str) -> Result<Option<String>, SecretError> {
+        match &self.backend {
+            Backend::Keyring => match keyring::Entry::new(KEYRING_SERVICE, key)?.get_password() {
+                Ok(value) => Ok(Some(value)),
+                Err(keyring::Error::NoEntry) => Ok(None),
+                Err(e) => Err(e.into()),
+            },
+            Backend::File(file) => Ok(file.get(key)),
+            Backend::Unavailable(reason) => Err(SecretError::Unavailable(reason.clone())),
+        }
+    }
+
+    pub fn set(&self, key: &str, value: &str) -> Result<(), SecretError> {
+        match &self.backend {
+            Backend::Keyring => Ok(keyring::Entry::new(KEYRING_SERVICE, key)?.set_password(value)?),
+            Backend::File(file) => file.set(key, Some(value)),
+            Backend::Unavailable(reason) => Err(SecretError::Unavailable(reason.clone())),
+        }
+    }
+
+    pub fn delete(&self, key: &str) -> Result<(), SecretError> {
+        match &self.backend {
+            Backend::Keyring => match keyring::Entry::new(KEYRING_SERVICE, key)?.delete_credential() {
+                Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+                Err(e) => Err(e.into()),
+            },
+            Backend::File(file) => file.set(key, None),
+            Backend::Unavailable(reason) => Err(SecretError::Unavailable(reason.clone())),
+        }
+    }
+}
+
+/// Read an entry we own; a missing entry still proves the keyring answers. Some
+/// platforms construct entries fine but fail on use, and a read changes nothing.
+fn keyring_available() -> bool {
+    let Ok(entry) = keyring::Entry::new(KEYRING_SERVICE, SECRET_KEYS[0]) else {
+        return false;
+    };
+    matches!(entry.get_password(), Ok(_) | Err(keyring::Error::NoEntry))
+}
+
+/// Move secrets saved in plaintext by earlier versions into the store, then drop
+/// them from the `settings` table. A row is only deleted once it is stored.
+pub async fn migrate_plaintext_secrets(db: &SqlitePool, store: &SecretStore) -> Result<(), String> {
+    for key in SECRET_KEYS {
+        let value: Option<String> = sqlx::query_scalar("SELECT value FROM settings WHERE key = ?1")
+            .bind(key)
+            .fetch_optional(db)
+            .await
+            .map_err(|e| e.to_string())?;
+        let Some(value) = value else {
+            continue;
+        };
+        store.set(key, &value).map_err(|e| e.to_string())?;
+        sqlx::query("DELETE FROM settings WHERE key = ?1")
+            .bind(key)
+            .execute(db)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+enum MasterKey {
+    /// 32 random bytes in a file next to the database, readable only by the user
+    KeyFile(PathBuf),
+    /// Argon2id over the passphrase with the salt stored in the secrets file
+    Passphrase(String),
+}
+
+#[derive(Serialize, Deserialize)]
+struct SealedFile {
+    version: u32,
+    /// Base64 Argon2 salt; only present for passphrase-derived keys
+    #[serde(default)]
+    salt: Option<String>,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// All secrets in one ChaCha20-Poly1305 sealed JSON map, rewritten on every change.
+struct EncryptedFile {
+    path: PathBuf,
+    cipher: ChaCha20Poly1305,
+    salt: Option<Vec<u8>>,
+    entries: Mutex<BTreeMap<String, String>>,
+}
+
+impl EncryptedFile {
+    fn open(path: PathBuf, key: MasterKey) -> Result<Self, SecretError> {
+        let sealed: Option<SealedFile> = match std::fs::read(&path) {
+            Ok(bytes) => Some(
+                serde_json::from_slice(&bytes).map_err(|e| SecretError::Format(e.to_string()))?,
+            ),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e.into()),
+        };
+
+        let (key_bytes, salt) = match key {
+            MasterKey::KeyFile(key_path) => (load_or_create_key_file(&key_path)?, None),
+            MasterKey::Passphrase(passphrase) => {
+                let salt = match sealed.as_ref().and_then(|s| s.salt.as_ref()) {
+                    Some(b64) => decode(b64)?,
+                    None => random_bytes::<16>()?.to_vec(),
+                };
+                (derive_key(&passphrase, &salt)?, Some(salt))
+            }
+        };
+        let cipher = ChaCha20Poly1305::new(Key::from_slice(&key_bytes));
+
+        let entries = match sealed {
+            Some(sealed) => {
+                let nonce = decode(&sealed.nonce)?;
+                if nonce.len() != 12 {
+                    return Err(SecretError::Format("bad nonce length".to_string()));
+                }
+                let plaintext = cipher
+                    .decrypt(Nonce::from_slice(&nonce), decode(&sealed.ciphertext)?.as_slice())
+                    .map_err(|_| SecretError::Decrypt)?;
+                serde_json::from_slice(&plaintext).map_err(|e| SecretError::Format(e.to_string()))?
+            }
+            None => BTreeMap::new(),
+        };
+
+        Ok(Self {
+            path,
+            cipher,
+            salt,
+            entries: Mutex::new(entries),
+        })
+    }
+
+    fn get(&self, key: &str) -> Option<String> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(key)
+            .cloned()
+    }
+
+    fn set(&self, key: &str, value: Option<&str>) -> Result<(), SecretError> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let mut updated = entries.clone();
+        match value {
+            Some(v) => updated.insert(key.to_string(), v.to_string()),
+            None => updated.remove(key),
+        };
+        self.write(&updated)?;
+        *entries = updated;
+        Ok(())
+    }
+
+    fn write(&self, entries: &BTreeMap<String, String>) -> Result<(), SecretError> {
+        let nonce = random_bytes::<12>()?;
+        let plaintext = serde_json::to_vec(entries).map_err(|e| SecretError::Format(e.to_string()))?;
+        let ciphertext = self
+            .cipher
+            .encrypt(Nonce::from_slice(&nonce), plaintext.as_slice())
+            .map_err(|_| SecretError::Format("encryption failed".to_string()))?;
+        let sealed = SealedFile {
+            version: 1,
+            salt: self.salt.as_ref().map(|s| general_purpose::STANDARD.encode(s)),
+            nonce: general_purpose::STANDARD.encode(nonce),
+            ciphertext: general_purpose::STANDARD.encode(ciphertext),
+        };
+        let json = serde_json::to_vec(&sealed).map_err(|e| SecretError::Format(e.to_string()))?;
+
+        // Write then rename so a crash never leaves a half-written file behind
+        let tmp = self.path.with_extension("tmp");
+        write_private(&tmp, &json)?;
+        std::fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+}
+
+fn load_or_create_key_file(path: &Path) -> Result<[u8; 32], SecretError> {
+    match std::fs::read(path) {
+        Ok(bytes) => bytes
+            .try_into()
+            .map_err(|_| SecretError::Format("master key file has the wrong size".to_string())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            let key = random_bytes::<32>()?;
+            write_private(path, &key)?;
+            Ok(key)
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; 32], SecretError> {
+    let mut key = [0u8; 32];
+    argon2::Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| SecretError::Format(format!("key derivation failed: {}", e)))?;
+    Ok(key)
+}
+
+fn random_bytes<const N: usize>() -> Result<[u8; N], SecretError> {
+    let mut buf = [0u8; N];
+    getrandom::getrandom(&mut buf)
+        .map_err(|e| SecretError::Format(format!("no secure randomness: {}", e)))?;
+    Ok(buf)
+}
+
+fn decode(b64: &str) -> Result<Vec<u8>, SecretError> {
+    general_purpose::STANDARD
+        .decode(b64)
+        .map_err(|e| SecretError::Format(e.to_string()))
+}
+
+/// Create or replace a file readable only by the current user.
+fn write_private(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    file.write_all(contents)?;
+    file.sync_all()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "jtt-secrets-{}-{}-{}",
+            name,
+            std::process::id(),
+            fastrand::u64(..)
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn encrypted_file_round_trips_without_plaintext_on_disk() {
+        let dir = temp_dir("roundtrip");
+        let path = dir.join(SECRETS_FILE);
+        let key = || MasterKey::KeyFile(dir.join(MASTER_KEY_FILE));
+
+        let file = EncryptedFile::open(path.clone(), key()).unwrap();
+        file.set("jira_api_token", Some("super-secret-token")).unwrap();
+        file.set("jira_oauth_tokens", Some("{}")).unwrap();
+        file.set("jira_oauth_tokens", None).unwrap();
+
+        let on_disk = std::fs::read_to_string(&path).unwrap();
+        assert!(!on_disk.contains("super-secret-token"));
+
+        let reopened = EncryptedFile::open(path, key()).unwrap();
+        assert_eq!(reopened.get("jira_api_token").as_deref(), Some("super-secret-token"));
+        assert_eq!(reopened.get("jira_oauth_tokens"), None);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn passphrase_key_rejects_wrong_passphrase() {
+        let dir = temp_dir("passphrase");
+        let path = dir.join(SECRETS_FILE);
+
+        let file = EncryptedFile::open(path.clone(), MasterKey::Passphrase("correct horse".into())).unwrap();
+        file.set("jira_api_token", Some("token")).unwrap();
+
+        assert!(matches!(
+            EncryptedFile::open(path.clone(), MasterKey::Passphrase("wrong".into())),
+            Err(SecretError::Decrypt)
+        ));
+        let reopened = EncryptedFile::open(path, MasterKey::Passphrase("correct horse".into())).unwrap();
+        assert_eq!(reopened.get("jira_api_token").as_deref(), Some("token"));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn existing_secret_file_pins_the_file_backend() {
+        let dir = temp_dir("backend");
+        SecretStore::open_file(&dir).unwrap().set("jira_api_token", "token").unwrap();
+
+        let store = SecretStore::open(&dir).unwrap();
+        assert!(matches!(store.backend, Backend::File(_)));
+        assert_eq!(std::fs::read_to_string(dir.join(BACKEND_FILE)).unwrap(), "file");
+        assert_eq!(store.get("jira_api_token").unwrap().as_deref(), Some("token"));
+
+        std::fs::write(dir.join(BACKEND_FILE), "vault").unwrap();
+        assert!(matches!(SecretStore::open(&dir), Err(SecretError::Format(_))));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn plaintext_rows_move_into_the_store() {
+        let dir = temp_dir("migrate");
+        let store = SecretStore::open_file(&dir).unwrap();
+        let db = crate::db::test_pool().await;
+        sqlx::query(
+            "INSERT INTO settings (key, value) VALUES ('jira_api_token', 'legacy'), ('theme', 'dark')",
+        )
+        .execute(&db)
+        .await
+        .unwrap();
+
+        migrate_plaintext_secrets(&db, &store).await.unwrap();
+
+        assert_eq!(store.get("jira_api_token").unwrap().as_deref(), Some("legacy"));
+        let keys: Vec<String> = sqlx::query_scalar("SELECT key FROM settings ORDER BY key")
+            .fetch_all(&db)
+            .await
+            .unwrap();
+        assert_eq!(keys, vec!["theme"]);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,20 +1,45 @@
+use crate::jira::oauth::{OAuthConnection, OAuthTokenStore};
+use crate::jira::retry::{RateLimiter, RetryPolicy};
+use crate::jira::types::JiraFlavor;
+use crate::secrets::SecretStore;
+use crate::sync::SyncEngine;
 use serde::{Deserialize, Serialize};
 use sqlx::SqlitePool;
+use std::path::PathBuf;
 use std::sync::atomic::AtomicBool;
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 
 pub struct AppState {
     pub db: SqlitePool,
+    /// App data dir holding the database, its backups and the secret store.
+    pub app_dir: PathBuf,
+    /// `integrity_check` findings or a failed migration from startup. When non-empty
+    /// the app starts without touching the database and the UI offers a restore.
+    pub integrity_problems: Vec<String>,
     pub jira_config: Mutex<Option<JiraConfig>>,
     pub window_position: Mutex<Option<(i32, i32)>>,
     pub cached_account_id: Mutex<Option<String>>,
     pub http_client: reqwest::Client,
+    pub jira_retry_policy: Mutex<RetryPolicy>,
+    /// Shared by every `JiraClient` so all commands respect one request rate.
+    pub jira_rate_limiter: Arc<RateLimiter>,
+    pub oauth_tokens: Arc<OAuthTokenStore>,
+    /// API token and OAuth credentials; kept out of the `settings` table.
+    pub secrets: Arc<SecretStore>,
+    pub sync: Arc<SyncEngine>,
+    /// Set when worklogs or settings changed, so the next timer tick reloads the tray menu.
+    pub tray_menu_stale: AtomicBool,
     pub suppress_blur_hide: AtomicBool,
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct JiraConfig {
     pub base_url: String,
+    #[serde(default)]
+    pub flavor: JiraFlavor,
     pub email: String,
     pub api_token: String,
+    /// Set when connected through OAuth; `email` and `api_token` are then unused.
+    #[serde(default)]
+    pub oauth: Option<OAuthConnection>,
 }
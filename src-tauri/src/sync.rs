@@ -0,0 +1,1371 @@
+//! Background push to Jira through a persistent outbox.
+//!
+//! `sync_outbox` holds at most one pending operation per worklog. The worker in
+//! [`run`] drains it whenever it is woken or its poll interval passes, backing off
+//! per entry on transient failures and pausing entirely while Jira is unreachable.
+//! With auto-push enabled it also queues creates for pending worklogs that have
+//! not been edited for the configured grace period.
+//!
+//! Synced worklogs edited or deleted offline become `modified` / `pending_delete`
+//! and are replayed as updates and deletes. Before touching Jira the worker
+//! compares the worklog's current `updated` with the `jira_updated_at` we last
+//! saw; if someone changed it in Jira meanwhile the two versions go through the
+//! three-way merge in [`crate::conflicts`] instead of being overwritten.
+
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{Emitter, Manager};
+use tokio::sync::Notify;
+
+use crate::commands::jira::{format_for_jira, get_client, link_worklog};
+use crate::conflicts::{self, Merge, WorklogFields};
+use crate::jira::client::JiraClient;
+use crate::jira::error::JiraError;
+use crate::jira::types::JiraWorklogEntry;
+use crate::rounding::{self, RoundingPolicy};
+use crate::state::AppState;
+use crate::sync_events::{self, EventOperation, SyncEvent};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+/// First and last wait between connection attempts while offline.
+const OFFLINE_RETRY_MIN: Duration = Duration::from_secs(15);
+const OFFLINE_RETRY_MAX: Duration = Duration::from_secs(5 * 60);
+/// Per-entry backoff after a transient failure: base * 2^attempts, capped.
+const ENTRY_BACKOFF_BASE_SECS: i64 = 30;
+const ENTRY_BACKOFF_MAX_SECS: i64 = 60 * 60;
+
+pub const STATUS_EVENT: &str = "sync://status";
+pub const ITEM_EVENT: &str = "sync://item";
+
+#[derive(Debug, thiserror::Error)]
+pub enum SyncError {
+    #[error(transparent)]
+    Jira(#[from] JiraError),
+    #[error("Database error: {0}")]
+    Db(#[from] sqlx::Error),
+    #[error("{0}")]
+    Local(String),
+    /// Changed in Jira since our last sync; the worklog was marked `conflict`
+    #[error("Conflict: {0}")]
+    Conflict(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncOperation {
+    Create,
+    Update,
+    Delete,
+}
+
+impl SyncOperation {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Create => "create",
+            Self::Update => "update",
+            Self::Delete => "delete",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "create" => Some(Self::Create),
+            "update" => Some(Self::Update),
+            "delete" => Some(Self::Delete),
+            _ => None,
+        }
+    }
+}
+
+/// What a queued `existing` operation becomes when `new` is queued for the same
+/// worklog. `None` means the two cancel out and the entry is dropped.
+fn coalesce(existing: SyncOperation, new: SyncOperation) -> Option<SyncOperation> {
+    use SyncOperation::*;
+    match (existing, new) {
+        // Never reached Jira: the create will carry the latest values, or nothing is needed
+        (Create, Update) | (Create, Create) => Some(Create),
+        (Create, Delete) => None,
+        (Update, Delete) | (Delete, _) => Some(Delete),
+        (Update, _) => Some(Update),
+    }
+}
+
+/// Queue `operation` for a worklog, merging with whatever is already queued for it.
+/// `issue_key` and `jira_worklog_id` are kept so a delete can be replayed after the
+/// local row is gone.
+pub async fn enqueue<'c, A>(
+    db: A,
+    worklog_id: i64,
+    operation: SyncOperation,
+    issue_key: &str,
+    jira_worklog_id: Option<&str>,
+) -> Result<(), sqlx::Error>
+where
+    A: sqlx::Acquire<'c, Database = sqlx::Sqlite>,
+{
+    let mut tx = db.begin().await?;
+    let existing: Option<String> =
+        sqlx::query_scalar("SELECT operation FROM sync_outbox WHERE worklog_id = ?1")
+            .bind(worklog_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+    match existing.as_deref().and_then(SyncOperation::parse) {
+        None => {
+            sqlx::query(
+                "INSERT OR REPLACE INTO sync_outbox (worklog_id, operation, issue_key, jira_worklog_id) \
+                 VALUES (?1, ?2, ?3, ?4)",
+            )
+            .bind(worklog_id)
+            .bind(operation.as_str())
+            .bind(issue_key)
+            .bind(jira_worklog_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+        Some(current) => match coalesce(current, operation) {
+            Some(merged) => {
+                // A changed operation is new work: retry it right away
+                sqlx::query(
+                    "UPDATE sync_outbox SET operation = ?1, issue_key = ?2, \
+                     jira_worklog_id = COALESCE(?3, jira_worklog_id), \
+                     attempts = CASE WHEN operation = ?1 THEN attempts ELSE 0 END, \
+                     next_attempt_at = CASE WHEN operation = ?1 THEN next_attempt_at ELSE datetime('now') END \
+                     WHERE worklog_id = ?4",
+                )
+                .bind(merged.as_str())
+                .bind(issue_key)
+                .bind(jira_worklog_id)
+                .bind(worklog_id)
+                .execute(&mut *tx)
+                .await?;
+            }
+            None => {
+                sqlx::query("DELETE FROM sync_outbox WHERE worklog_id = ?1")
+                    .bind(worklog_id)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+        },
+    }
+    tx.commit().await
+}
+
+/// Queue creates for pending worklogs untouched for at least `grace_minutes`.
+pub async fn enqueue_due_creates(db: &SqlitePool, grace_minutes: u32) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        "INSERT OR IGNORE INTO sync_outbox (worklog_id, operation, issue_key) \
+         SELECT id, 'create', issue_key FROM worklogs \
+         WHERE sync_status = 'pending' AND updated_at <= datetime('now', ?1)",
+    )
+    .bind(format!("-{} minutes", grace_minutes))
+    .execute(db)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// Queue replays for offline edits and deletes that have no outbox entry, e.g.
+/// because a permanent failure dropped it. Used when the user asks to sync now.
+pub async fn enqueue_local_changes(db: &SqlitePool) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        "INSERT OR IGNORE INTO sync_outbox (worklog_id, operation, issue_key, jira_worklog_id) \
+         SELECT id, CASE sync_status WHEN 'modified' THEN 'update' ELSE 'delete' END, issue_key, jira_worklog_id \
+         FROM worklogs WHERE sync_status IN ('modified', 'pending_delete') AND jira_worklog_id IS NOT NULL",
+    )
+    .execute(db)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncSettings {
+    /// Push pending worklogs without the user asking. Off by default.
+    pub auto_push: bool,
+    /// Only auto-push worklogs last edited at least this many minutes ago.
+    pub grace_minutes: u32,
+}
+
+impl Default for SyncSettings {
+    fn default() -> Self {
+        Self {
+            auto_push: false,
+            grace_minutes: 5,
+        }
+    }
+}
+
+pub async fn load_settings(db: &SqlitePool) -> SyncSettings {
+    let rows: Vec<(String, String)> = sqlx::query_as(
+        "SELECT key, value FROM settings WHERE key IN ('sync_auto_push', 'sync_grace_minutes')",
+    )
+    .fetch_all(db)
+    .await
+    .unwrap_or_else(|e| {
+        eprintln!("Failed to read sync settings: {}", e);
+        Vec::new()
+    });
+
+    let mut settings = SyncSettings::default();
+    for (key, value) in rows {
+        match key.as_str() {
+            "sync_auto_push" => settings.auto_push = value == "true",
+            "sync_grace_minutes" => settings.grace_minutes = value.parse().unwrap_or(settings.grace_minutes),
+            _ => {}
+        }
+    }
+    settings
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncState {
+    Idle,
+    Syncing,
+    /// Jira could not be reached; the worker retries with a growing delay
+    Offline,
+    NotConfigured,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncStatus {
+    pub state: SyncState,
+    /// Entries left in the outbox
+    pub pending: i64,
+    pub last_error: Option<String>,
+    pub last_run_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncOutcome {
+    Done,
+    /// Transient failure; the entry stays queued with a later `next_attempt_at`
+    Retry,
+    /// Permanent failure or conflict; the entry was dropped and the error
+    /// recorded on the worklog
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncItemEvent {
+    pub worklog_id: i64,
+    pub operation: SyncOperation,
+    pub outcome: SyncOutcome,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct DrainReport {
+    pub done: u32,
+    pub retried: u32,
+    pub failed: u32,
+    /// Stopped early because Jira was unreachable
+    pub offline: bool,
+    pub last_error: Option<String>,
+}
+
+#[derive(sqlx::FromRow)]
+struct OutboxEntry {
+    worklog_id: i64,
+    operation: String,
+    issue_key: String,
+    jira_worklog_id: Option<String>,
+    attempts: i64,
+}
+
+/// Replay every due outbox entry, oldest first.
+pub async fn drain_outbox(
+    db: &SqlitePool,
+    client: &JiraClient,
+    on_item: &(dyn Fn(&SyncItemEvent) + Send + Sync),
+) -> Result<DrainReport, sqlx::Error> {
+    let entries: Vec<OutboxEntry> = sqlx::query_as(
+        "SELECT worklog_id, operation, issue_key, jira_worklog_id, attempts FROM sync_outbox \
+         WHERE next_attempt_at <= datetime('now') ORDER BY id",
+    )
+    .fetch_all(db)
+    .await?;
+
+    let mut report = DrainReport::default();
+    for entry in entries {
+        let Some(operation) = SyncOperation::parse(&entry.operation) else {
+            remove_entry(db, entry.worklog_id).await?;
+            continue;
+        };
+
+        let result = replay(db, client, operation, &entry).await;
+        let (outcome, error) = match result {
+            Ok(()) => {
+                remove_entry(db, entry.worklog_id).await?;
+                report.done += 1;
+                (SyncOutcome::Done, None)
+            }
+            Err(SyncError::Jira(JiraError::Network(msg))) => {
+                // Everything else would fail the same way; leave the queue as it is
+                report.offline = true;
+                report.last_error = Some(format!("Request failed: {}", msg));
+                break;
+            }
+            Err(e @ SyncError::Jira(_)) if is_transient(&e) => {
+                let delay = (ENTRY_BACKOFF_BASE_SECS << entry.attempts.clamp(0, 16)).min(ENTRY_BACKOFF_MAX_SECS);
+                sqlx::query(
+                    "UPDATE sync_outbox SET attempts = attempts + 1, last_error = ?1, \
+                     next_attempt_at = datetime('now', ?2) WHERE worklog_id = ?3",
+                )
+                .bind(e.to_string())
+                .bind(format!("+{} seconds", delay))
+                .bind(entry.worklog_id)
+                .execute(db)
+                .await?;
+                report.retried += 1;
+                (SyncOutcome::Retry, Some(e.to_string()))
+            }
+            Err(e) => {
+                remove_entry(db, entry.worklog_id).await?;
+                report.failed += 1;
+                (SyncOutcome::Failed, Some(e.to_string()))
+            }
+        };
+        if let Some(e) = &error {
+            report.last_error = Some(e.clone());
+        }
+        on_item(&SyncItemEvent {
+            worklog_id: entry.worklog_id,
+            operation,
+            outcome,
+            error,
+        });
+    }
+    Ok(report)
+}
+
+fn is_transient(e: &SyncError) -> bool {
+    matches!(e, SyncError::Jira(j) if j.is_transient())
+}
+
+/// Drop the outbox entry of `worklog_id`, once its replay succeeded or failed for good.
+pub async fn remove_entry(db: &SqlitePool, worklog_id: i64) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM sync_outbox WHERE worklog_id = ?1")
+        .bind(worklog_id)
+        .execute(db)
+        .await?;
+    Ok(())
+}
+
+async fn replay(
+    db: &SqlitePool,
+    client: &JiraClient,
+    operation: SyncOperation,
+    entry: &OutboxEntry,
+) -> Result<(), SyncError> {
+    match operation {
+        SyncOperation::Create => {
+            let wl: Option<UnsyncedWorklog> = sqlx::query_as(&format!(
+                "SELECT {} FROM worklogs WHERE id = ?1 AND sync_status = 'pending'",
+                UNSYNCED_COLUMNS
+            ))
+            .bind(entry.worklog_id)
+            .fetch_optional(db)
+            .await?;
+            // Pushed by hand or deleted meanwhile
+            let Some(wl) = wl else {
+                return Ok(());
+            };
+            push_unsynced_worklog(db, client, &wl).await
+        }
+        SyncOperation::Update => push_update(db, client, entry.worklog_id, entry.jira_worklog_id.as_deref()).await,
+        SyncOperation::Delete => {
+            let jira_id = entry
+                .jira_worklog_id
+                .as_deref()
+                .ok_or_else(|| SyncError::Local("Worklog has no Jira worklog ID".to_string()))?;
+            push_delete(db, client, entry.worklog_id, &entry.issue_key, jira_id).await
+        }
+    }
+}
+
+/// Send a local edit of a synced worklog to Jira. If it also changed in Jira
+/// since the last sync, both versions are merged against the last-synced
+/// snapshot first; edits to the same field become a conflict instead.
+pub async fn push_update(
+    db: &SqlitePool,
+    client: &JiraClient,
+    worklog_id: i64,
+    queued_jira_id: Option<&str>,
+) -> Result<(), SyncError> {
+    let result = replay_update(db, client, worklog_id, queued_jira_id).await;
+    record_replay_error(db, worklog_id, &result).await?;
+    if let Err(e) = &result {
+        if !matches!(e, SyncError::Conflict(_)) {
+            SyncEvent::new(EventOperation::Update, worklog_id)
+                .jira_id(queued_jira_id)
+                .failed(db, &e.to_string())
+                .await?;
+        }
+    }
+    result
+}
+
+async fn replay_update(
+    db: &SqlitePool,
+    client: &JiraClient,
+    worklog_id: i64,
+    queued_jira_id: Option<&str>,
+) -> Result<(), SyncError> {
+    // Deleted, merged by an import or turned into a conflict meanwhile
+    let Some(row) = conflicts::load_row(db, worklog_id)
+        .await?
+        .filter(|row| row.sync_status == "modified")
+    else {
+        return Ok(());
+    };
+    let jira_id = row
+        .jira_worklog_id
+        .clone()
+        .or_else(|| queued_jira_id.map(str::to_string))
+        .ok_or_else(|| SyncError::Local("Worklog has no Jira worklog ID".to_string()))?;
+
+    let mut values = row.local();
+    let event = || {
+        SyncEvent::new(EventOperation::Update, worklog_id)
+            .issue(&row.issue_key)
+            .jira_id(Some(&jira_id))
+    };
+    match check_remote(client, &row.issue_key, &jira_id, row.jira_updated_at.as_deref()).await? {
+        RemoteState::Unchanged => {}
+        RemoteState::Deleted => {
+            return Err(conflict(db, event(), worklog_id, false, None, "Deleted in Jira since the last sync").await?)
+        }
+        RemoteState::Changed(entry) => {
+            let remote = remote_fields(&entry)?;
+            match conflicts::three_way(row.base().as_ref(), &values, &remote) {
+                Merge::TakeRemote => {
+                    conflicts::apply_synced(db, worklog_id, &remote, Some(&entry.updated)).await?;
+                    event()
+                        .request(sync_events::describe(&remote))
+                        .skipped(db, "Jira already has these values")
+                        .await?;
+                    return Ok(());
+                }
+                Merge::Push(merged) => values = merged,
+                Merge::Conflict => {
+                    let remote = Some((&remote, entry.updated.as_str()));
+                    return Err(conflict(db, event(), worklog_id, false, remote, "Changed both here and in Jira").await?);
+                }
+            }
+        }
+    }
+
+    // A duration edited here is tracked time, rounded like a create
+    if values.duration_seconds == row.duration_seconds && row.base_duration_seconds != Some(row.duration_seconds) {
+        let policy = rounding::load(db).await?;
+        values.duration_seconds = push_duration(&policy, row.duration_seconds, row.raw_duration_seconds);
+    }
+    let started_jira = format_for_jira(&values.started_at).map_err(SyncError::Local)?;
+    let resp = client
+        .update_worklog(&row.issue_key, &jira_id, values.duration_seconds, &started_jira, &values.description)
+        .await?;
+    // What Jira has now is the new base
+    sqlx::query(
+        "UPDATE worklogs SET started_at = ?1, duration_seconds = ?2, description = ?3, \
+         base_started_at = ?1, base_duration_seconds = ?2, base_description = ?3, \
+         jira_worklog_id = ?4, jira_updated_at = ?5, sync_status = 'synced', sync_error = NULL WHERE id = ?6",
+    )
+    .bind(&values.started_at)
+    .bind(values.duration_seconds)
+    .bind(&values.description)
+    .bind(&resp.id)
+    .bind(&resp.updated)
+    .bind(worklog_id)
+    .execute(db)
+    .await?;
+    event().request(sync_events::describe(&values)).succeeded(db).await?;
+    Ok(())
+}
+
+/// Delete a synced worklog in Jira, then locally. A worklog whose values were
+/// changed in Jira since the last sync is kept as a conflict instead.
+pub async fn push_delete(
+    db: &SqlitePool,
+    client: &JiraClient,
+    worklog_id: i64,
+    issue_key: &str,
+    jira_id: &str,
+) -> Result<(), SyncError> {
+    let result = replay_delete(db, client, worklog_id, issue_key, jira_id).await;
+    record_replay_error(db, worklog_id, &result).await?;
+    if let Err(e) = &result {
+        if !matches!(e, SyncError::Conflict(_)) {
+            SyncEvent::new(EventOperation::Delete, worklog_id)
+                .issue(issue_key)
+                .jira_id(Some(jira_id))
+                .failed(db, &e.to_string())
+                .await?;
+        }
+    }
+    result
+}
+
+async fn replay_delete(
+    db: &SqlitePool,
+    client: &JiraClient,
+    worklog_id: i64,
+    issue_key: &str,
+    jira_id: &str,
+) -> Result<(), SyncError> {
+    let row = conflicts::load_row(db, worklog_id).await?;
+    let event = || {
+        SyncEvent::new(EventOperation::Delete, worklog_id)
+            .issue(issue_key)
+            .jira_id(Some(jira_id))
+    };
+    let remote = match &row {
+        Some(row) => check_remote(client, issue_key, jira_id, row.jira_updated_at.as_deref()).await?,
+        // Without the local row there is nothing to compare Jira's version with
+        None => RemoteState::Unchanged,
+    };
+    let delete_in_jira = match remote {
+        RemoteState::Unchanged => true,
+        // Already gone in Jira
+        RemoteState::Deleted => false,
+        RemoteState::Changed(entry) => {
+            let remote = remote_fields(&entry)?;
+            // Only Jira's timestamp moved: the values we meant to delete are unchanged
+            if !row.as_ref().and_then(|r| r.base()).is_some_and(|base| base.same_as(&remote)) {
+                let remote = Some((&remote, entry.updated.as_str()));
+                let reason = "Changed in Jira after it was deleted here";
+                return Err(conflict(db, event(), worklog_id, true, remote, reason).await?);
+            }
+            true
+        }
+    };
+    let event = event().request(match row.as_ref() {
+        Some(row) => sync_events::describe(&row.local()),
+        None => String::new(),
+    });
+    if delete_in_jira {
+        match client.delete_worklog(issue_key, jira_id).await {
+            Ok(()) => event.succeeded(db).await?,
+            Err(JiraError::NotFound(_)) => event.skipped(db, "Already deleted in Jira").await?,
+            Err(e) => return Err(e.into()),
+        }
+    } else {
+        event.skipped(db, "Already deleted in Jira").await?;
+    }
+    sqlx::query("DELETE FROM worklogs WHERE id = ?1")
+        .bind(worklog_id)
+        .execute(db)
+        .await?;
+    Ok(())
+}
+
+/// Show a permanent replay failure on the worklog. Transient ones are retried
+/// and conflicts record their own reason.
+async fn record_replay_error(
+    db: &SqlitePool,
+    worklog_id: i64,
+    result: &Result<(), SyncError>,
+) -> Result<(), sqlx::Error> {
+    if let Err(e) = result {
+        if !is_transient(e) && !matches!(e, SyncError::Conflict(_)) {
+            sqlx::query("UPDATE worklogs SET sync_error = ?1 WHERE id = ?2")
+                .bind(e.to_string())
+                .bind(worklog_id)
+                .execute(db)
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+enum RemoteState {
+    Unchanged,
+    Changed(Box<JiraWorklogEntry>),
+    Deleted,
+}
+
+/// Compare the worklog in Jira with the `updated` timestamp we last synced.
+/// Without one (worklogs pushed before it was recorded) it counts as changed,
+/// so callers compare its values with the base snapshot instead.
+async fn check_remote(
+    client: &JiraClient,
+    issue_key: &str,
+    jira_id: &str,
+    synced_updated: Option<&str>,
+) -> Result<RemoteState, JiraError> {
+    let synced = synced_updated.and_then(parse_jira_time);
+    match client.get_worklog(issue_key, jira_id).await {
+        Ok(remote) if synced.is_some() && parse_jira_time(&remote.updated) == synced => Ok(RemoteState::Unchanged),
+        Ok(remote) => Ok(RemoteState::Changed(Box::new(remote))),
+        Err(JiraError::NotFound(_)) => Ok(RemoteState::Deleted),
+        Err(e) => Err(e),
+    }
+}
+
+/// Jira timestamps like `2024-03-04T09:00:00.000+0000`, compared as instants so
+/// a different offset or precision between endpoints doesn't look like a change.
+fn parse_jira_time(s: &str) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+    chrono::DateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.f%z")
+        .or_else(|_| chrono::DateTime::parse_from_rfc3339(s))
+        .ok()
+}
+
+fn remote_fields(entry: &JiraWorklogEntry) -> Result<WorklogFields, SyncError> {
+    WorklogFields::from_jira(entry)
+        .ok_or_else(|| SyncError::Local(format!("Unrecognised start time from Jira: {}", entry.started)))
+}
+
+async fn conflict(
+    db: &SqlitePool,
+    event: SyncEvent<'_>,
+    worklog_id: i64,
+    local_deleted: bool,
+    remote: Option<(&WorklogFields, &str)>,
+    reason: &str,
+) -> Result<SyncError, sqlx::Error> {
+    conflicts::record_conflict(db, worklog_id, local_deleted, remote, reason).await?;
+    let event = match remote {
+        Some((fields, _)) => event.request(format!("Jira has {}", sync_events::describe(fields))),
+        None => event,
+    };
+    event.conflicted(db, reason).await?;
+    Ok(SyncError::Conflict(reason.to_string()))
+}
+
+/// Record a failed push. Transient failures (rate limiting, outages) stay `pending`
+/// so the next push retries them; anything else is marked `error` for the user.
+async fn record_push_failure(
+    db: &SqlitePool,
+    worklog_id: i64,
+    err: &JiraError,
+) -> Result<(), sqlx::Error> {
+    let status = if err.is_transient() { "pending" } else { "error" };
+    sqlx::query(
+        "UPDATE worklogs SET sync_status = ?1, sync_error = ?2, updated_at = datetime('now') WHERE id = ?3",
+    )
+    .bind(status)
+    .bind(err.to_string())
+    .bind(worklog_id)
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// A local worklog waiting to be created in Jira.
+#[derive(sqlx::FromRow)]
+pub struct UnsyncedWorklog {
+    pub id: i64,
+    pub issue_key: String,
+    pub started_at: String,
+    pub duration_seconds: i64,
+    pub raw_duration_seconds: Option<i64>,
+    pub description: String,
+    pub local_uuid: String,
+    pub push_attempted_at: Option<String>,
+}
+
+pub const UNSYNCED_COLUMNS: &str =
+    "id, issue_key, started_at, duration_seconds, raw_duration_seconds, description, local_uuid, push_attempted_at";
+
+/// The duration a worklog is sent with. With rounding at push time the current
+/// policy is applied to the tracked time, unless that would leave nothing.
+pub fn push_duration(policy: &RoundingPolicy, duration_seconds: i64, raw_duration_seconds: Option<i64>) -> i64 {
+    if !policy.at_push {
+        return duration_seconds;
+    }
+    match policy.apply(raw_duration_seconds.unwrap_or(duration_seconds)) {
+        0 => duration_seconds,
+        rounded => rounded,
+    }
+}
+
+/// Create `wl` in Jira exactly once. `push_attempted_at` is stored before the
+/// create request goes out; if it is already set, an earlier attempt may have
+/// reached Jira without us seeing the answer, so the issue is searched for a
+/// worklog tagged with our UUID before creating a new one.
+pub async fn push_unsynced_worklog(
+    db: &SqlitePool,
+    client: &JiraClient,
+    wl: &UnsyncedWorklog,
+) -> Result<(), SyncError> {
+    // Jira expects: "2021-01-17T12:34:00.000+0000"
+    let started_jira = format_for_jira(&wl.started_at).map_err(SyncError::Local)?;
+    let policy = rounding::load(db).await?;
+    let duration = push_duration(&policy, wl.duration_seconds, wl.raw_duration_seconds);
+
+    let existing = match wl.push_attempted_at {
+        Some(_) => client.find_worklog_by_local_id(&wl.issue_key, &wl.local_uuid).await,
+        None => Ok(None),
+    };
+
+    let result = match existing {
+        Ok(Some(found)) => return adopt_pushed(db, wl, duration, &found).await,
+        Ok(None) => {
+            sqlx::query("UPDATE worklogs SET push_attempted_at = datetime('now') WHERE id = ?1")
+                .bind(wl.id)
+                .execute(db)
+                .await?;
+            let created = client
+                .add_worklog(
+                    &wl.issue_key,
+                    duration,
+                    &started_jira,
+                    &wl.description,
+                    &wl.local_uuid,
+                )
+                .await;
+            if matches!(&created, Err(e) if e.is_rejection()) {
+                // Jira rejected it outright, so nothing was created
+                sqlx::query("UPDATE worklogs SET push_attempted_at = NULL WHERE id = ?1")
+                    .bind(wl.id)
+                    .execute(db)
+                    .await?;
+            }
+            created.map(|resp| (resp.id, resp.updated))
+        }
+        Err(e) => Err(e),
+    };
+
+    let sent = WorklogFields {
+        started_at: started_jira,
+        duration_seconds: duration,
+        description: wl.description.clone(),
+    };
+    let event = SyncEvent::new(EventOperation::Create, wl.id)
+        .issue(&wl.issue_key)
+        .request(sync_events::describe(&sent));
+    match result {
+        Ok((jira_id, jira_updated_at)) => {
+            // The row keeps the duration Jira got, rounded or not
+            sqlx::query(
+                "UPDATE worklogs SET sync_status = 'synced', jira_worklog_id = ?1, jira_updated_at = ?2, sync_error = NULL, \
+                 duration_seconds = ?3, base_started_at = started_at, base_duration_seconds = ?3, base_description = description, \
+                 updated_at = datetime('now') WHERE id = ?4",
+            )
+            .bind(&jira_id)
+            .bind(&jira_updated_at)
+            .bind(duration)
+            .bind(wl.id)
+            .execute(db)
+            .await?;
+            event.jira_id(Some(&jira_id)).succeeded(db).await?;
+            Ok(())
+        }
+        Err(e) => {
+            record_push_failure(db, wl.id, &e).await?;
+            event.failed(db, &e.to_string()).await?;
+            Err(e.into())
+        }
+    }
+}
+
+/// Take over the worklog an earlier attempt created in Jira, like imports link
+/// tagged worklogs: Jira's values become the base, and the row is pushed as an
+/// update if it was edited after that attempt.
+async fn adopt_pushed(
+    db: &SqlitePool,
+    wl: &UnsyncedWorklog,
+    duration: i64,
+    found: &JiraWorklogEntry,
+) -> Result<(), SyncError> {
+    let started = remote_fields(found)?.started_at;
+    // That attempt sent the duration rounded for the push
+    sqlx::query("UPDATE worklogs SET duration_seconds = ?1 WHERE id = ?2")
+        .bind(duration)
+        .bind(wl.id)
+        .execute(db)
+        .await?;
+    link_worklog(db, wl.id, &wl.issue_key, found, &started, "Found in Jira tagged with this worklog's local id")
+        .await
+        .map_err(SyncError::Local)
+}
+
+/// Shared between the worker and the commands that poke it.
+pub struct SyncEngine {
+    wake: Notify,
+    status: Mutex<SyncStatus>,
+}
+
+impl Default for SyncEngine {
+    fn default() -> Self {
+        Self {
+            wake: Notify::new(),
+            status: Mutex::new(SyncStatus {
+                state: SyncState::Idle,
+                pending: 0,
+                last_error: None,
+                last_run_at: None,
+            }),
+        }
+    }
+}
+
+impl SyncEngine {
+    /// Run the worker now instead of at the end of its current wait.
+    pub fn wake(&self) {
+        self.wake.notify_one();
+    }
+
+    pub fn status(&self) -> SyncStatus {
+        self.status.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    fn set_status(&self, app: &tauri::AppHandle, status: SyncStatus) {
+        *self.status.lock().unwrap_or_else(|e| e.into_inner()) = status.clone();
+        let _ = app.emit(STATUS_EVENT, status);
+    }
+}
+
+/// Worker loop, spawned once from setup.
+pub async fn run(app: tauri::AppHandle) {
+    let mut offline_wait = OFFLINE_RETRY_MIN;
+    loop {
+        let state = app.state::<AppState>();
+        let wait = match tick(&app, &state).await {
+            Ok(true) => {
+                let wait = offline_wait;
+                offline_wait = (offline_wait * 2).min(OFFLINE_RETRY_MAX);
+                wait
+            }
+            Ok(false) => {
+                offline_wait = OFFLINE_RETRY_MIN;
+                POLL_INTERVAL
+            }
+            Err(e) => {
+                eprintln!("Sync worker error: {}", e);
+                POLL_INTERVAL
+            }
+        };
+        tokio::select! {
+            _ = tokio::time::sleep(wait) => {}
+            _ = state.sync.wake.notified() => {}
+        }
+    }
+}
+
+/// One pass of the worker. Returns whether Jira was unreachable.
+async fn tick(app: &tauri::AppHandle, state: &AppState) -> Result<bool, sqlx::Error> {
+    let settings = load_settings(&state.db).await;
+    if settings.auto_push {
+        enqueue_due_creates(&state.db, settings.grace_minutes).await?;
+    }
+
+    let pending = count_pending(&state.db).await?;
+    let previous = state.sync.status();
+    if pending == 0 {
+        if previous.state != SyncState::Idle || previous.pending != 0 {
+            state.sync.set_status(app, SyncStatus { state: SyncState::Idle, pending, ..previous });
+        }
+        return Ok(false);
+    }
+
+    let client = match get_client(state) {
+        Ok(client) => client,
+        Err(e) => {
+            state.sync.set_status(
+                app,
+                SyncStatus {
+                    state: SyncState::NotConfigured,
+                    pending,
+                    last_error: Some(e),
+                    ..previous
+                },
+            );
+            return Ok(false);
+        }
+    };
+
+    state.sync.set_status(
+        app,
+        SyncStatus {
+            state: SyncState::Syncing,
+            pending,
+            ..previous.clone()
+        },
+    );
+    let report = drain_outbox(&state.db, &client, &|item| {
+        let _ = app.emit(ITEM_EVENT, item);
+    })
+    .await?;
+
+    state.sync.set_status(
+        app,
+        SyncStatus {
+            state: if report.offline { SyncState::Offline } else { SyncState::Idle },
+            pending: count_pending(&state.db).await?,
+            last_error: report.last_error.or(previous.last_error.filter(|_| report.done == 0)),
+            last_run_at: Some(chrono::Local::now().to_rfc3339()),
+        },
+    );
+    Ok(report.offline)
+}
+
+pub async fn count_pending(db: &SqlitePool) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar("SELECT COUNT(*) FROM sync_outbox").fetch_one(db).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jira::client::LOCAL_ID_PROPERTY;
+    use crate::jira::fake_server::{self, FakeResponse, FakeServer};
+    use crate::jira::retry::RetryPolicy;
+    use crate::rounding::RoundingMode;
+
+    fn test_client(base_url: &str) -> JiraClient {
+        let no_retries = RetryPolicy {
+            max_retries: 0,
+            base_delay_ms: 1,
+            max_delay_ms: 1,
+            requests_per_second: 0.0,
+        };
+        fake_server::test_client(base_url, no_retries)
+    }
+
+    async fn insert_pending(db: &SqlitePool, issue_key: &str) -> i64 {
+        sqlx::query(
+            "INSERT INTO worklogs (issue_key, started_at, duration_seconds, updated_at) \
+             VALUES (?1, '2024-03-04T09:00:00+00:00', 600, datetime('now', '-1 hour'))",
+        )
+        .bind(issue_key)
+        .execute(db)
+        .await
+        .unwrap()
+        .last_insert_rowid()
+    }
+
+    /// A worklog due for its first push, as the worker loads it.
+    async fn pending_row(db: &SqlitePool) -> UnsyncedWorklog {
+        let id = insert_pending(db, "SUP-1").await;
+        sqlx::query_as(&format!("SELECT {} FROM worklogs WHERE id = ?1", UNSYNCED_COLUMNS))
+            .bind(id)
+            .fetch_one(db)
+            .await
+            .unwrap()
+    }
+
+    #[test]
+    fn queued_operations_coalesce() {
+        use SyncOperation::*;
+        assert_eq!(coalesce(Create, Update), Some(Create));
+        assert_eq!(coalesce(Create, Delete), None);
+        assert_eq!(coalesce(Update, Update), Some(Update));
+        assert_eq!(coalesce(Update, Delete), Some(Delete));
+        assert_eq!(coalesce(Delete, Update), Some(Delete));
+    }
+
+    #[tokio::test]
+    async fn grace_period_holds_back_fresh_worklogs() {
+        let db = crate::db::test_pool().await;
+        insert_pending(&db, "OLD-1").await;
+        sqlx::query("INSERT INTO worklogs (issue_key, started_at, duration_seconds) VALUES ('NEW-1', 'x', 60)")
+            .execute(&db)
+            .await
+            .unwrap();
+
+        assert_eq!(enqueue_due_creates(&db, 5).await.unwrap(), 1);
+        // Already queued: nothing new
+        assert_eq!(enqueue_due_creates(&db, 5).await.unwrap(), 0);
+        assert_eq!(enqueue_due_creates(&db, 0).await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn drain_pushes_and_backs_off_on_server_errors() {
+        let db = crate::db::test_pool().await;
+        let ok = insert_pending(&db, "OK-1").await;
+        let flaky = insert_pending(&db, "DOWN-1").await;
+        enqueue_due_creates(&db, 0).await.unwrap();
+
+        let server = FakeServer::start(|req| {
+            if req.path.contains("DOWN-1") {
+                FakeResponse::json(503, serde_json::json!({}))
+            } else {
+                FakeResponse::json(201, serde_json::json!({ "id": "900" }))
+            }
+        })
+        .await;
+
+        let events = Mutex::new(Vec::new());
+        let report = drain_outbox(&db, &test_client(&server.base_url), &|e| {
+            events.lock().unwrap().push((e.worklog_id, e.outcome));
+        })
+        .await
+        .unwrap();
+
+        assert_eq!((report.done, report.retried, report.offline), (1, 1, false));
+        assert_eq!(
+            events.into_inner().unwrap(),
+            vec![(ok, SyncOutcome::Done), (flaky, SyncOutcome::Retry)]
+        );
+        let (attempts, due_later): (i64, bool) = sqlx::query_as(
+            "SELECT attempts, next_attempt_at > datetime('now') FROM sync_outbox WHERE worklog_id = ?1",
+        )
+        .bind(flaky)
+        .fetch_one(&db)
+        .await
+        .unwrap();
+        assert_eq!((attempts, due_later), (1, true));
+        assert_eq!(count_pending(&db).await.unwrap(), 1);
+
+        // Not due yet: a second pass sends nothing
+        let before = server.requests().len();
+        drain_outbox(&db, &test_client(&server.base_url), &|_| {}).await.unwrap();
+        assert_eq!(server.requests().len(), before);
+    }
+
+    #[tokio::test]
+    async fn drain_pauses_when_offline() {
+        let db = crate::db::test_pool().await;
+        insert_pending(&db, "A-1").await;
+        insert_pending(&db, "A-2").await;
+        enqueue_due_creates(&db, 0).await.unwrap();
+
+        // Nothing listens on this port any more
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+
+        let report = drain_outbox(&db, &test_client(&format!("http://{}", addr)), &|_| {})
+            .await
+            .unwrap();
+        assert!(report.offline);
+        let attempts: Vec<i64> = sqlx::query_scalar("SELECT attempts FROM sync_outbox")
+            .fetch_all(&db)
+            .await
+            .unwrap();
+        assert_eq!(attempts, vec![0, 0]);
+    }
+
+    #[tokio::test]
+    async fn queued_delete_outlives_the_local_row() {
+        let db = crate::db::test_pool().await;
+        enqueue(&db, 42, SyncOperation::Update, "SUP-1", Some("300")).await.unwrap();
+        enqueue(&db, 42, SyncOperation::Delete, "SUP-1", Some("300")).await.unwrap();
+
+        let server = FakeServer::start(|_| FakeResponse::json(204, serde_json::json!(null))).await;
+        let report = drain_outbox(&db, &test_client(&server.base_url), &|_| {}).await.unwrap();
+
+        assert_eq!(report.done, 1);
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, "DELETE");
+        assert_eq!(requests[0].path, "/rest/api/3/issue/SUP-1/worklog/300");
+    }
+
+    async fn insert_modified(db: &SqlitePool, jira_id: &str) -> i64 {
+        let id = sqlx::query(
+            "INSERT INTO worklogs (issue_key, started_at, duration_seconds, sync_status, jira_worklog_id, jira_updated_at) \
+             VALUES ('SUP-1', '2024-03-04T09:00:00+00:00', 1200, 'modified', ?1, '2024-03-04T10:00:00.000+0000')",
+        )
+        .bind(jira_id)
+        .execute(db)
+        .await
+        .unwrap()
+        .last_insert_rowid();
+        enqueue(db, id, SyncOperation::Update, "SUP-1", Some(jira_id)).await.unwrap();
+        id
+    }
+
+    fn remote_worklog(id: &str, updated: &str) -> serde_json::Value {
+        serde_json::json!({
+            "id": id,
+            "started": "2024-03-04T09:00:00.000+0000",
+            "updated": updated,
+            "timeSpentSeconds": 600,
+            "author": { "accountId": "me" }
+        })
+    }
+
+    #[tokio::test]
+    async fn offline_edit_is_replayed_when_jira_is_unchanged() {
+        let db = crate::db::test_pool().await;
+        let id = insert_modified(&db, "300").await;
+
+        let server = FakeServer::start(|req| match req.method.as_str() {
+            // Same instant as stored, written with a different offset
+            "GET" => FakeResponse::json(200, remote_worklog("300", "2024-03-04T12:00:00.000+0200")),
+            _ => FakeResponse::json(200, serde_json::json!({ "id": "300", "updated": "2024-03-05T08:00:00.000+0000" })),
+        })
+        .await;
+        let report = drain_outbox(&db, &test_client(&server.base_url), &|_| {}).await.unwrap();
+        assert_eq!(report.done, 1);
+        assert!(server.requests().iter().any(|r| r.method == "PUT" && r.body.contains("1200")));
+
+        let (status, jira_updated_at): (String, String) =
+            sqlx::query_as("SELECT sync_status, jira_updated_at FROM worklogs WHERE id = ?1")
+                .bind(id)
+                .fetch_one(&db)
+                .await
+                .unwrap();
+        assert_eq!(status, "synced");
+        assert_eq!(jira_updated_at, "2024-03-05T08:00:00.000+0000");
+    }
+
+    #[tokio::test]
+    async fn offline_edit_becomes_a_conflict_when_jira_changed() {
+        let db = crate::db::test_pool().await;
+        let edited = insert_modified(&db, "300").await;
+        let deleting = insert_modified(&db, "301").await;
+        sqlx::query("UPDATE worklogs SET sync_status = 'pending_delete' WHERE id = ?1")
+            .bind(deleting)
+            .execute(&db)
+            .await
+            .unwrap();
+        enqueue(&db, deleting, SyncOperation::Delete, "SUP-1", Some("301")).await.unwrap();
+
+        let server = FakeServer::start(|req| {
+            let id = if req.path.ends_with("/300") { "300" } else { "301" };
+            FakeResponse::json(200, remote_worklog(id, "2024-03-04T11:30:00.000+0000"))
+        })
+        .await;
+        let report = drain_outbox(&db, &test_client(&server.base_url), &|_| {}).await.unwrap();
+        assert_eq!(report.failed, 2);
+        assert!(server.requests().iter().all(|r| r.method == "GET"));
+
+        let rows: Vec<(String, i64)> =
+            sqlx::query_as("SELECT sync_status, duration_seconds FROM worklogs WHERE id IN (?1, ?2) ORDER BY id")
+                .bind(edited)
+                .bind(deleting)
+                .fetch_all(&db)
+                .await
+                .unwrap();
+        assert_eq!(rows, vec![("conflict".to_string(), 1200), ("conflict".to_string(), 1200)]);
+        assert_eq!(count_pending(&db).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn jira_change_to_another_field_is_merged_into_the_push() {
+        let db = crate::db::test_pool().await;
+        let id = insert_modified(&db, "300").await;
+        sqlx::query(
+            "UPDATE worklogs SET description = 'base', base_started_at = started_at, \
+             base_duration_seconds = 600, base_description = 'base' WHERE id = ?1",
+        )
+        .bind(id)
+        .execute(&db)
+        .await
+        .unwrap();
+
+        let server = FakeServer::start(|req| match req.method.as_str() {
+            "GET" => {
+                let mut remote = remote_worklog("300", "2024-03-04T11:30:00.000+0000");
+                remote["comment"] = serde_json::json!("edited in Jira");
+                FakeResponse::json(200, remote)
+            }
+            _ => FakeResponse::json(200, serde_json::json!({ "id": "300", "updated": "2024-03-05T08:00:00.000+0000" })),
+        })
+        .await;
+        let report = drain_outbox(&db, &test_client(&server.base_url), &|_| {}).await.unwrap();
+        assert_eq!(report.done, 1);
+        let put = server.requests().into_iter().find(|r| r.method == "PUT").unwrap();
+        assert!(put.body.contains("1200") && put.body.contains("edited in Jira"));
+
+        let row = conflicts::load_row(&db, id).await.unwrap().unwrap();
+        assert_eq!(row.sync_status, "synced");
+        assert_eq!(row.description, "edited in Jira");
+        assert_eq!(row.base_duration_seconds, Some(1200));
+    }
+
+    #[tokio::test]
+    async fn delete_without_a_synced_timestamp_compares_jira_with_the_base() {
+        let db = crate::db::test_pool().await;
+        // Pushed before `jira_updated_at` was recorded
+        let untouched = insert_modified(&db, "300").await;
+        let edited = insert_modified(&db, "301").await;
+        sqlx::query(
+            "UPDATE worklogs SET sync_status = 'pending_delete', jira_updated_at = NULL, base_started_at = started_at, \
+             base_duration_seconds = 600, base_description = ''",
+        )
+        .execute(&db)
+        .await
+        .unwrap();
+        sqlx::query("DELETE FROM sync_outbox").execute(&db).await.unwrap();
+        enqueue(&db, untouched, SyncOperation::Delete, "SUP-1", Some("300")).await.unwrap();
+        enqueue(&db, edited, SyncOperation::Delete, "SUP-1", Some("301")).await.unwrap();
+
+        let server = FakeServer::start(|req| match req.method.as_str() {
+            "GET" if req.path.ends_with("/301") => {
+                let mut remote = remote_worklog("301", "2024-03-04T11:30:00.000+0000");
+                remote["comment"] = serde_json::json!("edited in Jira");
+                FakeResponse::json(200, remote)
+            }
+            "GET" => FakeResponse::json(200, remote_worklog("300", "2024-03-04T11:30:00.000+0000")),
+            _ => FakeResponse::json(204, serde_json::json!(null)),
+        })
+        .await;
+        let report = drain_outbox(&db, &test_client(&server.base_url), &|_| {}).await.unwrap();
+        assert_eq!((report.done, report.failed), (1, 1));
+        let deletes: Vec<String> = server
+            .requests()
+            .into_iter()
+            .filter(|r| r.method == "DELETE")
+            .map(|r| r.path)
+            .collect();
+        assert_eq!(deletes, vec!["/rest/api/3/issue/SUP-1/worklog/300".to_string()]);
+
+        let status: String = sqlx::query_scalar("SELECT sync_status FROM worklogs WHERE id = ?1")
+            .bind(edited)
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        assert_eq!(status, "conflict");
+    }
+
+    /// Jira holding the worklog an interrupted push of `wl` created, next to an untagged one.
+    async fn server_with_pushed(wl: &UnsyncedWorklog) -> FakeServer {
+        let tagged = wl.local_uuid.clone();
+        FakeServer::start(move |req| {
+            assert_eq!(req.method, "GET", "must not create the worklog again");
+            assert_eq!(req.query.get("expand").map(String::as_str), Some("properties"));
+            FakeResponse::json(
+                200,
+                serde_json::json!({
+                    "startAt": 0,
+                    "total": 2,
+                    "worklogs": [
+                        {
+                            "id": "500", "started": "2024-03-04T09:00:00.000+0000",
+                            "updated": "2024-03-04T09:00:00.000+0000", "timeSpentSeconds": 600,
+                            "author": { "accountId": "me" }
+                        },
+                        {
+                            "id": "501", "started": "2024-03-04T09:00:00.000+0000",
+                            "updated": "2024-03-04T09:00:00.000+0000", "timeSpentSeconds": 600,
+                            "author": { "accountId": "me" },
+                            "properties": [{ "key": LOCAL_ID_PROPERTY, "value": { "id": tagged } }]
+                        }
+                    ]
+                }),
+            )
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn interrupted_push_is_reconciled_instead_of_duplicated() {
+        let db = crate::db::test_pool().await;
+        // The first attempt reached Jira but the app died before recording it
+        let mut wl = pending_row(&db).await;
+        wl.push_attempted_at = Some("2024-03-04 10:00:00".to_string());
+        let server = server_with_pushed(&wl).await;
+        let client = test_client(&server.base_url);
+
+        push_unsynced_worklog(&db, &client, &wl).await.unwrap();
+
+        let (status, jira_id): (String, String) =
+            sqlx::query_as("SELECT sync_status, jira_worklog_id FROM worklogs")
+                .fetch_one(&db)
+                .await
+                .unwrap();
+        assert_eq!((status.as_str(), jira_id.as_str()), ("synced", "501"));
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn edit_after_an_interrupted_push_is_sent_as_an_update() {
+        let db = crate::db::test_pool().await;
+        let mut wl = pending_row(&db).await;
+        wl.push_attempted_at = Some("2024-03-04 10:00:00".to_string());
+        // Edited while the created worklog was still unknown locally
+        sqlx::query("UPDATE worklogs SET description = 'Reviewed the fix' WHERE id = ?1")
+            .bind(wl.id)
+            .execute(&db)
+            .await
+            .unwrap();
+        wl.description = "Reviewed the fix".to_string();
+        let server = server_with_pushed(&wl).await;
+        let client = test_client(&server.base_url);
+
+        push_unsynced_worklog(&db, &client, &wl).await.unwrap();
+
+        let (status, jira_id, base_description): (String, String, String) =
+            sqlx::query_as("SELECT sync_status, jira_worklog_id, base_description FROM worklogs")
+                .fetch_one(&db)
+                .await
+                .unwrap();
+        assert_eq!((status.as_str(), jira_id.as_str()), ("modified", "501"));
+        // The base is what Jira has, so the edit still counts as local
+        assert_eq!(base_description, "");
+        let queued: (String, Option<String>) =
+            sqlx::query_as("SELECT operation, jira_worklog_id FROM sync_outbox WHERE worklog_id = ?1")
+                .bind(wl.id)
+                .fetch_one(&db)
+                .await
+                .unwrap();
+        assert_eq!(queued, ("update".to_string(), Some("501".to_string())));
+    }
+
+    #[tokio::test]
+    async fn unreadable_create_response_keeps_the_push_marker() {
+        let db = crate::db::test_pool().await;
+        let wl = pending_row(&db).await;
+
+        // Created in Jira, but the answer can't be read
+        let server = FakeServer::start(|_| FakeResponse::json(201, serde_json::json!({ "unexpected": true }))).await;
+        let client = test_client(&server.base_url);
+
+        assert!(matches!(
+            push_unsynced_worklog(&db, &client, &wl).await,
+            Err(SyncError::Jira(JiraError::Parse(_)))
+        ));
+
+        // The next push looks the worklog up by its local id first
+        let marker: Option<String> = sqlx::query_scalar("SELECT push_attempted_at FROM worklogs")
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        assert!(marker.is_some());
+    }
+
+    #[tokio::test]
+    async fn rejected_push_keeps_the_unrounded_duration() {
+        let db = crate::db::test_pool().await;
+        let policy = RoundingPolicy {
+            mode: RoundingMode::Up,
+            increment_seconds: 900,
+            minimum_seconds: 0,
+            at_push: true,
+        };
+        rounding::save(&db, &policy).await.unwrap();
+        let wl = pending_row(&db).await;
+
+        let server = FakeServer::start(|_| {
+            FakeResponse::json(400, serde_json::json!({ "errorMessages": ["Worklog must not be null"] }))
+        })
+        .await;
+        let client = test_client(&server.base_url);
+
+        assert!(push_unsynced_worklog(&db, &client, &wl).await.is_err());
+
+        let duration: i64 = sqlx::query_scalar("SELECT duration_seconds FROM worklogs")
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        assert_eq!(duration, 600);
+    }
+
+    #[tokio::test]
+    async fn first_push_tags_worklog_with_local_uuid() {
+        let db = crate::db::test_pool().await;
+        let wl = pending_row(&db).await;
+
+        let server = FakeServer::start(|req| {
+            assert_eq!(req.method, "POST");
+            FakeResponse::json(201, serde_json::json!({ "id": "777" }))
+        })
+        .await;
+        let client = test_client(&server.base_url);
+
+        push_unsynced_worklog(&db, &client, &wl).await.unwrap();
+
+        let body: serde_json::Value = serde_json::from_str(&server.requests()[0].body).unwrap();
+        assert_eq!(body["properties"][0]["value"]["id"], wl.local_uuid.as_str());
+        let attempted: Option<String> = sqlx::query_scalar("SELECT push_attempted_at FROM worklogs")
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        assert!(attempted.is_some());
+    }
+}
@@ -0,0 +1,262 @@
+//! Audit trail of Jira mutations and import decisions.
+//!
+//! `sync_error` on a worklog only keeps the latest failure, and a row removed by
+//! an import leaves nothing behind. Every push, update, delete and import
+//! decision is appended to `sync_events` instead, keyed by worklog id and Jira
+//! id but without a foreign key, so the history outlives the row. Imports that
+//! find a worklog unchanged record nothing.
+
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+use crate::conflicts::WorklogFields;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventOperation {
+    /// A local worklog created in Jira
+    Create,
+    /// A local edit sent to Jira
+    Update,
+    /// A local delete sent to Jira
+    Delete,
+    /// A local worklog matched to one an earlier, unanswered create left in Jira
+    Link,
+    ImportCreate,
+    ImportUpdate,
+    ImportDelete,
+}
+
+impl EventOperation {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Create => "create",
+            Self::Update => "update",
+            Self::Delete => "delete",
+            Self::Link => "link",
+            Self::ImportCreate => "import_create",
+            Self::ImportUpdate => "import_update",
+            Self::ImportDelete => "import_delete",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventOutcome {
+    Success,
+    Error,
+    Conflict,
+    Skipped,
+}
+
+impl EventOutcome {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Success => "success",
+            Self::Error => "error",
+            Self::Conflict => "conflict",
+            Self::Skipped => "skipped",
+        }
+    }
+}
+
+/// An event about to be recorded; finish it with one of the outcome methods.
+pub struct SyncEvent<'a> {
+    operation: EventOperation,
+    worklog_id: Option<i64>,
+    issue_key: Option<&'a str>,
+    jira_worklog_id: Option<&'a str>,
+    request: String,
+}
+
+impl<'a> SyncEvent<'a> {
+    pub fn new(operation: EventOperation, worklog_id: i64) -> Self {
+        Self {
+            operation,
+            worklog_id: Some(worklog_id),
+            issue_key: None,
+            jira_worklog_id: None,
+            request: String::new(),
+        }
+    }
+
+    pub fn issue(mut self, issue_key: &'a str) -> Self {
+        self.issue_key = Some(issue_key);
+        self
+    }
+
+    pub fn jira_id(mut self, jira_worklog_id: Option<&'a str>) -> Self {
+        self.jira_worklog_id = jira_worklog_id;
+        self
+    }
+
+    /// What was sent to Jira, or why the import acted.
+    pub fn request(mut self, request: impl Into<String>) -> Self {
+        self.request = request.into();
+        self
+    }
+
+    pub async fn succeeded(self, db: &SqlitePool) -> Result<(), sqlx::Error> {
+        self.insert(db, EventOutcome::Success, None).await
+    }
+
+    pub async fn failed(self, db: &SqlitePool, error: &str) -> Result<(), sqlx::Error> {
+        self.insert(db, EventOutcome::Error, Some(error)).await
+    }
+
+    pub async fn conflicted(self, db: &SqlitePool, reason: &str) -> Result<(), sqlx::Error> {
+        self.insert(db, EventOutcome::Conflict, Some(reason)).await
+    }
+
+    pub async fn skipped(self, db: &SqlitePool, reason: &str) -> Result<(), sqlx::Error> {
+        self.insert(db, EventOutcome::Skipped, Some(reason)).await
+    }
+
+    async fn insert(self, db: &SqlitePool, outcome: EventOutcome, error: Option<&str>) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO sync_events (worklog_id, jira_worklog_id, issue_key, operation, request, outcome, error) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        )
+        .bind(self.worklog_id)
+        .bind(self.jira_worklog_id)
+        .bind(self.issue_key)
+        .bind(self.operation.as_str())
+        .bind(&self.request)
+        .bind(outcome.as_str())
+        .bind(error)
+        .execute(db)
+        .await?;
+        Ok(())
+    }
+}
+
+/// One-line summary of worklog values for the `request` column.
+pub fn describe(fields: &WorklogFields) -> String {
+    let mut text = format!("started {}, {}s", fields.started_at, fields.duration_seconds);
+    if !fields.description.is_empty() {
+        text.push_str(&format!(", \"{}\"", fields.description));
+    }
+    text
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct SyncEventRow {
+    pub id: i64,
+    pub occurred_at: String,
+    pub worklog_id: Option<i64>,
+    pub jira_worklog_id: Option<String>,
+    pub issue_key: Option<String>,
+    pub operation: String,
+    pub request: String,
+    pub outcome: String,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct SyncEventFilter {
+    pub worklog_id: Option<i64>,
+    pub jira_worklog_id: Option<String>,
+    pub issue_key: Option<String>,
+    pub operation: Option<EventOperation>,
+    pub outcome: Option<EventOutcome>,
+    /// UTC `YYYY-MM-DD HH:MM:SS` bounds on `occurred_at`, inclusive
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub limit: Option<u32>,
+}
+
+const DEFAULT_LIMIT: u32 = 200;
+const MAX_LIMIT: u32 = 5000;
+
+/// Matching events, newest first.
+pub async fn list(db: &SqlitePool, filter: &SyncEventFilter) -> Result<Vec<SyncEventRow>, sqlx::Error> {
+    let mut sql = String::from(
+        "SELECT id, occurred_at, worklog_id, jira_worklog_id, issue_key, operation, request, outcome, error \
+         FROM sync_events WHERE 1=1",
+    );
+    let mut binds: Vec<String> = Vec::new();
+    let mut clause = |column: &str, op: &str, value: String| {
+        binds.push(value);
+        sql.push_str(&format!(" AND {} {} ?{}", column, op, binds.len()));
+    };
+
+    if let Some(id) = filter.worklog_id {
+        clause("worklog_id", "=", id.to_string());
+    }
+    if let Some(ref id) = filter.jira_worklog_id {
+        clause("jira_worklog_id", "=", id.clone());
+    }
+    if let Some(ref key) = filter.issue_key {
+        clause("issue_key", "=", key.clone());
+    }
+    if let Some(operation) = filter.operation {
+        clause("operation", "=", operation.as_str().to_string());
+    }
+    if let Some(outcome) = filter.outcome {
+        clause("outcome", "=", outcome.as_str().to_string());
+    }
+    if let Some(ref since) = filter.since {
+        clause("occurred_at", ">=", since.clone());
+    }
+    if let Some(ref until) = filter.until {
+        clause("occurred_at", "<=", until.clone());
+    }
+
+    let limit = filter.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+    sql.push_str(&format!(" ORDER BY id DESC LIMIT {}", limit));
+
+    let mut query = sqlx::query_as::<_, SyncEventRow>(&sql);
+    for bind in &binds {
+        query = query.bind(bind);
+    }
+    query.fetch_all(db).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn lists_filtered_events_newest_first() {
+        let db = crate::db::test_pool().await;
+        SyncEvent::new(EventOperation::Create, 1)
+            .issue("SUP-1")
+            .request("started 2024-03-04T09:00:00+00:00, 600s")
+            .succeeded(&db)
+            .await
+            .unwrap();
+        SyncEvent::new(EventOperation::Update, 1)
+            .issue("SUP-1")
+            .jira_id(Some("100"))
+            .failed(&db, "Jira server error (500): boom")
+            .await
+            .unwrap();
+        SyncEvent::new(EventOperation::ImportDelete, 2)
+            .jira_id(Some("200"))
+            .succeeded(&db)
+            .await
+            .unwrap();
+
+        let for_worklog = list(&db, &SyncEventFilter { worklog_id: Some(1), ..Default::default() })
+            .await
+            .unwrap();
+        let ops: Vec<&str> = for_worklog.iter().map(|e| e.operation.as_str()).collect();
+        assert_eq!(ops, vec!["update", "create"]);
+        assert_eq!(for_worklog[0].error.as_deref(), Some("Jira server error (500): boom"));
+
+        let deletes = list(
+            &db,
+            &SyncEventFilter {
+                operation: Some(EventOperation::ImportDelete),
+                outcome: Some(EventOutcome::Success),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(deletes.len(), 1);
+        assert_eq!(deletes[0].jira_worklog_id.as_deref(), Some("200"));
+        assert_eq!(deletes[0].worklog_id, Some(2));
+    }
+}
@@ -0,0 +1,298 @@
+//! Timer clock owned by the backend.
+//!
+//! Once a second [`run`] reads `active_timer`, renders the tray title and icon
+//! from it and emits [`TICK_EVENT`], so the menu bar keeps counting while the
+//! webview is hidden or throttled. The title follows the `tray_title_format`
+//! setting, in which `{issue}`, `{elapsed}` and `{today}` are filled in; it is
+//! only shown while `show_tray_title` is on. The tray menu is refreshed from
+//! the same tick through [`crate::tray_menu`], but only reloaded when the
+//! timers changed or it was invalidated.
+
+use chrono::{DateTime, Local, NaiveTime, TimeZone, Utc};
+use serde::Serialize;
+use sqlx::SqliteConnection;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use tauri::menu::MenuItem;
+use tauri::{Emitter, Manager};
+
+use crate::commands::timer::{day_boundary, parse_instant, set_tray_icon, setting, tracking_day, tracking_day_start};
+use crate::state::AppState;
+use crate::tray_menu::{self, MenuModel};
+
+pub const TICK_EVENT: &str = "timer://tick";
+pub const TITLE_FORMAT_KEY: &str = "tray_title_format";
+pub const DEFAULT_TITLE_FORMAT: &str = "{issue} {elapsed}";
+const SHOW_TITLE_KEY: &str = "show_tray_title";
+const TICK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Payload of [`TICK_EVENT`].
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct TimerTick {
+    pub timers: Vec<TickTimer>,
+    /// Saved worklogs plus timer time on the current tracking day
+    pub today_seconds: i64,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct TickTimer {
+    pub id: i64,
+    pub issue_key: String,
+    pub elapsed_secs: i64,
+    pub is_paused: bool,
+}
+
+impl TimerTick {
+    /// The timer the tray speaks for: the newest running one, else the newest.
+    fn shown(&self) -> Option<&TickTimer> {
+        self.timers
+            .iter()
+            .rev()
+            .find(|t| !t.is_paused)
+            .or_else(|| self.timers.last())
+    }
+
+    fn icon(&self) -> &'static str {
+        match self.shown() {
+            Some(t) if !t.is_paused => "working",
+            Some(_) => "paused",
+            None => "idle",
+        }
+    }
+}
+
+/// Ticker loop, spawned once from setup.
+pub async fn run(app: tauri::AppHandle) {
+    let mut interval = tokio::time::interval(TICK_INTERVAL);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    // What the tray shows now, so it is only touched on change
+    let mut shown = Shown::default();
+    loop {
+        interval.tick().await;
+        if let Err(e) = tick(&app, &mut shown).await {
+            eprintln!("Timer tick failed: {}", e);
+        }
+    }
+}
+
+#[derive(Default)]
+struct Shown {
+    title_and_icon: Option<(String, &'static str)>,
+    menu: Option<MenuModel>,
+    /// The menu's "Today" item and the minutes it shows
+    today: Option<(MenuItem<tauri::Wry>, i64)>,
+}
+
+async fn tick(app: &tauri::AppHandle, shown: &mut Shown) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let mut conn = state.db.acquire().await.map_err(|e| e.to_string())?;
+    let snapshot = load_tick(&mut conn, Utc::now(), &Local).await?;
+
+    let show_title = setting(&mut conn, SHOW_TITLE_KEY)
+        .await?
+        .is_some_and(|v| v != "false");
+    let title = match snapshot.shown() {
+        Some(timer) if show_title => {
+            let format = setting(&mut conn, TITLE_FORMAT_KEY)
+                .await?
+                .filter(|f| !f.trim().is_empty())
+                .unwrap_or_else(|| DEFAULT_TITLE_FORMAT.to_string());
+            render_title(&format, timer, snapshot.today_seconds)
+        }
+        _ => String::new(),
+    };
+    // The menu only depends on the timers, worklogs and pins, not the clock
+    let stale = state.tray_menu_stale.swap(false, Ordering::SeqCst)
+        || !shown.menu.as_ref().is_some_and(|m| m.shows_timers_of(&snapshot));
+    let menu = if stale {
+        match tray_menu::load_model(&mut conn, &snapshot).await {
+            Ok(menu) => Some(menu),
+            Err(e) => {
+                tray_menu::invalidate(&state);
+                return Err(e);
+            }
+        }
+    } else {
+        None
+    };
+    drop(conn);
+
+    let next = (title, snapshot.icon());
+    if shown.title_and_icon.as_ref() != Some(&next) {
+        if let Some(tray) = app.tray_by_id("main-tray") {
+            tray.set_title(Some(&next.0)).map_err(|e| e.to_string())?;
+        }
+        set_tray_icon(app, next.1)?;
+        shown.title_and_icon = Some(next);
+    }
+    let today_minutes = snapshot.today_seconds / 60;
+    if let Some(menu) = menu.filter(|m| shown.menu.as_ref() != Some(m)) {
+        let today = tray_menu::apply(app, &menu, today_minutes).map_err(|e| e.to_string())?;
+        shown.today = today.map(|item| (item, today_minutes));
+        shown.menu = Some(menu);
+    }
+    if let Some((item, minutes)) = shown.today.as_mut().filter(|(_, minutes)| *minutes != today_minutes) {
+        tray_menu::set_today(item, today_minutes).map_err(|e| e.to_string())?;
+        *minutes = today_minutes;
+    }
+
+    let _ = app.emit(TICK_EVENT, snapshot);
+    Ok(())
+}
+
+/// Every timer's elapsed time and the total for the tracking day containing `now`.
+async fn load_tick<Tz: TimeZone>(
+    conn: &mut SqliteConnection,
+    now: DateTime<Utc>,
+    tz: &Tz,
+) -> Result<TimerTick, String> {
+    let rows: Vec<(i64, String, String, i64, bool)> = sqlx::query_as(
+        "SELECT id, issue_key, started_at, accumulated_secs, is_paused FROM active_timer ORDER BY id",
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| e.to_string())?;
+    let mut timers = Vec::with_capacity(rows.len());
+    for (id, issue_key, started_at, accumulated, is_paused) in rows {
+        let running = if is_paused {
+            0
+        } else {
+            (now - parse_instant(&started_at)?).num_seconds().max(0)
+        };
+        timers.push(TickTimer {
+            id,
+            issue_key,
+            elapsed_secs: accumulated + running,
+            is_paused,
+        });
+    }
+
+    let start = day_start(now, day_boundary(conn).await?, tz);
+    let saved: i64 = sqlx::query_scalar(
+        "SELECT COALESCE(SUM(duration_seconds), 0) FROM worklogs \
+         WHERE sync_status != 'pending_delete' \
+         AND CAST(strftime('%s', started_at) AS INTEGER) >= ?1 \
+         AND CAST(strftime('%s', started_at) AS INTEGER) <= ?2",
+    )
+    .bind(start.timestamp())
+    .bind(now.timestamp())
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|e| e.to_string())?;
+    let segments: Vec<(String, Option<String>)> =
+        sqlx::query_as("SELECT started_at, ended_at FROM timer_segments")
+            .fetch_all(&mut *conn)
+            .await
+            .map_err(|e| e.to_string())?;
+    let mut running = 0;
+    for (started_at, ended_at) in segments {
+        let from = parse_instant(&started_at)?.max(start);
+        let to = match ended_at {
+            Some(ended_at) => parse_instant(&ended_at)?,
+            None => now,
+        };
+        running += (to - from).num_seconds().max(0);
+    }
+
+    Ok(TimerTick {
+        timers,
+        today_seconds: saved + running,
+    })
+}
+
+/// Start of the tracking day containing `now`, which begins at `boundary` local time.
+fn day_start<Tz: TimeZone>(now: DateTime<Utc>, boundary: NaiveTime, tz: &Tz) -> DateTime<Utc> {
+    tracking_day_start(tracking_day(now, boundary, tz), boundary, tz).unwrap_or(now)
+}
+
+fn render_title(format: &str, timer: &TickTimer, today_seconds: i64) -> String {
+    let mut title = format
+        .replace("{issue}", &timer.issue_key)
+        .replace("{elapsed}", &clock(timer.elapsed_secs))
+        .replace("{today}", &clock(today_seconds))
+        .trim()
+        .to_string();
+    if timer.is_paused {
+        title.push_str(" ⏸");
+    }
+    title
+}
+
+/// `HH:MM:SS`, as the timer display shows it.
+fn clock(seconds: i64) -> String {
+    format!("{:02}:{:02}:{:02}", seconds / 3600, seconds % 3600 / 60, seconds % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::FixedOffset;
+
+    #[test]
+    fn renders_the_configured_title() {
+        let timer = TickTimer {
+            id: 1,
+            issue_key: "SUP-1".to_string(),
+            elapsed_secs: 3725,
+            is_paused: false,
+        };
+        assert_eq!(render_title(DEFAULT_TITLE_FORMAT, &timer, 0), "SUP-1 01:02:05");
+        assert_eq!(render_title("{elapsed} / {today}", &timer, 7 * 3600), "01:02:05 / 07:00:00");
+
+        let paused = TickTimer { is_paused: true, ..timer };
+        assert_eq!(render_title("{issue}", &paused, 0), "SUP-1 ⏸");
+    }
+
+    #[tokio::test]
+    async fn today_counts_saved_worklogs_and_timer_time_since_the_day_began() {
+        let db = crate::db::test_pool().await;
+        let tz = FixedOffset::east_opt(3600).unwrap();
+        let at = |day: u32, hour: u32| {
+            tz.with_ymd_and_hms(2024, 3, day, hour, 0, 0).unwrap()
+        };
+        sqlx::query(
+            "INSERT INTO worklogs (issue_key, started_at, duration_seconds, description, sync_status) VALUES \
+             ('SUP-1', ?1, 3600, '', 'pending'), \
+             ('SUP-1', ?2, 1800, '', 'synced'), \
+             ('SUP-1', ?3, 900, '', 'pending_delete')",
+        )
+        .bind(at(4, 23).to_rfc3339())
+        .bind(at(5, 8).to_rfc3339())
+        .bind(at(5, 9).to_rfc3339())
+        .execute(&db)
+        .await
+        .unwrap();
+        // Running since 23:00 yesterday with a pause; only time after midnight counts
+        sqlx::query(
+            "INSERT INTO active_timer (id, issue_key, started_at, accumulated_secs, is_paused, paused_at, description) \
+             VALUES (1, 'OPS-2', ?1, 7200, 0, NULL, '')",
+        )
+        .bind(at(5, 10).to_rfc3339())
+        .execute(&db)
+        .await
+        .unwrap();
+        sqlx::query("INSERT INTO timer_segments (timer_id, started_at, ended_at) VALUES (1, ?1, ?2), (1, ?3, NULL)")
+            .bind(at(4, 23).to_rfc3339())
+            .bind(at(5, 1).to_rfc3339())
+            .bind(at(5, 10).to_rfc3339())
+            .execute(&db)
+            .await
+            .unwrap();
+
+        let mut conn = db.acquire().await.unwrap();
+        let tick = load_tick(&mut conn, at(5, 11).with_timezone(&Utc), &tz).await.unwrap();
+
+        assert_eq!(
+            tick.timers,
+            vec![TickTimer {
+                id: 1,
+                issue_key: "OPS-2".to_string(),
+                elapsed_secs: 3 * 3600,
+                is_paused: false,
+            }]
+        );
+        // 08:00 worklog, 00:00–01:00 and 10:00–11:00 of the timer
+        assert_eq!(tick.today_seconds, 1800 + 2 * 3600);
+        assert_eq!(tick.icon(), "working");
+    }
+}
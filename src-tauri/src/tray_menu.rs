@@ -0,0 +1,291 @@
+//! Tray context menu built from the database.
+//!
+//! The menu lists each timer with its controls, a "Start on…" submenu of pinned
+//! and recently tracked issues, today's total and "Push pending now", so a day
+//! can be tracked without opening the window. [`crate::timer_tick`] reloads the
+//! [`MenuModel`] when the timers changed or after [`invalidate`], and the menu
+//! is only rebuilt when the model differs. Today's total is updated in place.
+
+use sqlx::SqliteConnection;
+use std::sync::atomic::Ordering;
+use tauri::menu::{Menu, MenuItem, PredefinedMenuItem, Submenu};
+use tauri::{Emitter, Manager};
+
+use crate::commands::timer::setting;
+use crate::state::AppState;
+use crate::timer_tick::TimerTick;
+
+/// Emitted after a timer was started, paused, resumed or stopped from the tray.
+pub const TIMER_CHANGED_EVENT: &str = "timer://changed";
+/// Settings key of the JSON array of issue keys pinned in the task list.
+const PINNED_KEY: &str = "pinned_issues";
+/// Recently tracked issues offered besides the pinned ones.
+const RECENT_LIMIT: i64 = 8;
+const LABEL_MAX_CHARS: usize = 48;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MenuModel {
+    timers: Vec<MenuTimer>,
+    /// Pinned issues first, then the most recently tracked, as (key, summary)
+    start_on: Vec<(String, String)>,
+}
+
+impl MenuModel {
+    /// Whether the menu still lists exactly the timers of `tick`.
+    pub fn shows_timers_of(&self, tick: &TimerTick) -> bool {
+        self.timers.len() == tick.timers.len()
+            && self
+                .timers
+                .iter()
+                .zip(&tick.timers)
+                .all(|(m, t)| m.id == t.id && m.issue_key == t.issue_key && m.is_paused == t.is_paused)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct MenuTimer {
+    id: i64,
+    issue_key: String,
+    summary: String,
+    is_paused: bool,
+}
+
+pub async fn load_model(conn: &mut SqliteConnection, tick: &TimerTick) -> Result<MenuModel, String> {
+    let pinned: Vec<String> = setting(conn, PINNED_KEY)
+        .await?
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default();
+    let recent: Vec<String> = sqlx::query_scalar(
+        "SELECT issue_key FROM worklogs WHERE sync_status != 'pending_delete' \
+         GROUP BY issue_key ORDER BY MAX(CAST(strftime('%s', started_at) AS INTEGER)) DESC LIMIT ?1",
+    )
+    .bind(RECENT_LIMIT + pinned.len() as i64)
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let mut start_keys = pinned.clone();
+    start_keys.extend(
+        recent
+            .into_iter()
+            .filter(|key| !pinned.contains(key))
+            .take(RECENT_LIMIT as usize),
+    );
+    let mut keys = start_keys.clone();
+    keys.extend(tick.timers.iter().map(|t| t.issue_key.clone()));
+    let summaries = load_summaries(conn, &keys).await?;
+    let summary = |key: &str| summaries.iter().find(|(k, _)| k == key).map(|(_, s)| s.clone()).unwrap_or_default();
+
+    Ok(MenuModel {
+        timers: tick
+            .timers
+            .iter()
+            .map(|t| MenuTimer {
+                id: t.id,
+                issue_key: t.issue_key.clone(),
+                summary: summary(&t.issue_key),
+                is_paused: t.is_paused,
+            })
+            .collect(),
+        start_on: start_keys
+            .into_iter()
+            .map(|key| {
+                let summary = summary(&key);
+                (key, summary)
+            })
+            .collect(),
+    })
+}
+
+/// Have the next tick reload the menu, after worklogs or pinned issues changed.
+pub fn invalidate(state: &AppState) {
+    state.tray_menu_stale.store(true, Ordering::SeqCst);
+}
+
+async fn load_summaries(conn: &mut SqliteConnection, keys: &[String]) -> Result<Vec<(String, String)>, String> {
+    if keys.is_empty() {
+        return Ok(Vec::new());
+    }
+    let placeholders: Vec<String> = (1..=keys.len()).map(|i| format!("?{}", i)).collect();
+    let sql = format!(
+        "SELECT issue_key, summary FROM issues WHERE issue_key IN ({})",
+        placeholders.join(", ")
+    );
+    let mut query = sqlx::query_as::<_, (String, String)>(&sql);
+    for key in keys {
+        query = query.bind(key);
+    }
+    query.fetch_all(&mut *conn).await.map_err(|e| e.to_string())
+}
+
+/// Replace the tray menu with one built from `model`. Returns its "Today" item
+/// for [`set_today`], or `None` without a tray.
+pub fn apply(app: &tauri::AppHandle, model: &MenuModel, today_minutes: i64) -> tauri::Result<Option<MenuItem<tauri::Wry>>> {
+    let Some(tray) = app.tray_by_id("main-tray") else {
+        return Ok(None);
+    };
+    let menu = Menu::new(app)?;
+
+    for timer in &model.timers {
+        let state = if timer.is_paused { "⏸" } else { "▶" };
+        let title = issue_label(&format!("{} {}", state, timer.issue_key), &timer.summary);
+        menu.append(&MenuItem::with_id(app, format!("timer-title:{}", timer.id), title, false, None::<&str>)?)?;
+        let (toggle_id, toggle_text) = if timer.is_paused {
+            (format!("timer-resume:{}", timer.id), "Resume")
+        } else {
+            (format!("timer-pause:{}", timer.id), "Pause")
+        };
+        menu.append(&MenuItem::with_id(app, toggle_id, toggle_text, true, None::<&str>)?)?;
+        menu.append(&MenuItem::with_id(app, format!("timer-stop:{}", timer.id), "Stop", true, None::<&str>)?)?;
+        menu.append(&PredefinedMenuItem::separator(app)?)?;
+    }
+
+    let start_on = Submenu::new(app, "Start on…", !model.start_on.is_empty())?;
+    for (key, summary) in &model.start_on {
+        start_on.append(&MenuItem::with_id(app, format!("start:{}", key), issue_label(key, summary), true, None::<&str>)?)?;
+    }
+    menu.append(&start_on)?;
+    let today = MenuItem::with_id(app, "today", today_label(today_minutes), false, None::<&str>)?;
+    menu.append(&today)?;
+    menu.append(&MenuItem::with_id(app, "push-pending", "Push pending now", true, None::<&str>)?)?;
+
+    menu.append(&PredefinedMenuItem::separator(app)?)?;
+    menu.append(&MenuItem::with_id(app, "show-hide", "Show/Hide", true, None::<&str>)?)?;
+    menu.append(&PredefinedMenuItem::separator(app)?)?;
+    menu.append(&MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?)?;
+
+    tray.set_menu(Some(menu))?;
+    Ok(Some(today))
+}
+
+/// Show a new total on the "Today" item returned by [`apply`].
+pub fn set_today(item: &MenuItem<tauri::Wry>, today_minutes: i64) -> tauri::Result<()> {
+    item.set_text(today_label(today_minutes))
+}
+
+fn today_label(minutes: i64) -> String {
+    format!("Today: {}", hours_minutes(minutes))
+}
+
+/// Run a menu action built by [`apply`]. Show/Hide and Quit are handled in `lib.rs`.
+pub fn handle_event(app: &tauri::AppHandle, id: &str) {
+    let app = app.clone();
+    let id = id.to_string();
+    tauri::async_runtime::spawn(async move {
+        let state = app.state::<AppState>();
+        let timer_id = |prefix: &str| id.strip_prefix(prefix).and_then(|v| v.parse::<i64>().ok());
+        let result = if let Some(key) = id.strip_prefix("start:") {
+            crate::commands::timer::timer_start(state, key.to_string(), None, None)
+                .await
+                .map(|_| true)
+        } else if let Some(timer_id) = timer_id("timer-pause:") {
+            crate::commands::timer::timer_pause(state, Some(timer_id)).await.map(|_| true)
+        } else if let Some(timer_id) = timer_id("timer-resume:") {
+            crate::commands::timer::timer_resume(state, Some(timer_id)).await.map(|_| true)
+        } else if let Some(timer_id) = timer_id("timer-stop:") {
+            crate::commands::timer::timer_stop(state, Some(timer_id)).await.map(|_| true)
+        } else if id == "push-pending" {
+            crate::commands::sync::sync_now(state).await.map(|_| false)
+        } else {
+            Ok(false)
+        };
+        match result {
+            Ok(true) => {
+                let _ = app.emit(TIMER_CHANGED_EVENT, ());
+            }
+            Ok(false) => {}
+            Err(e) => eprintln!("Tray action {} failed: {}", id, e),
+        }
+    });
+}
+
+/// `KEY  Summary`, cut to fit a menu.
+fn issue_label(key: &str, summary: &str) -> String {
+    let label = if summary.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}  {}", key, summary)
+    };
+    if label.chars().count() <= LABEL_MAX_CHARS {
+        return label;
+    }
+    let cut: String = label.chars().take(LABEL_MAX_CHARS - 1).collect();
+    format!("{}…", cut.trim_end())
+}
+
+fn hours_minutes(minutes: i64) -> String {
+    if minutes >= 60 {
+        format!("{}h {}m", minutes / 60, minutes % 60)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timer_tick::TickTimer;
+
+    #[test]
+    fn labels_fit_the_menu() {
+        assert_eq!(issue_label("SUP-1", ""), "SUP-1");
+        assert_eq!(issue_label("SUP-1", "Fix login"), "SUP-1  Fix login");
+        let long = issue_label("SUP-1", &"word ".repeat(20));
+        assert!(long.chars().count() <= LABEL_MAX_CHARS);
+        assert!(long.ends_with("word…"));
+        assert_eq!(hours_minutes(45), "45m");
+        assert_eq!(hours_minutes(125), "2h 5m");
+        assert_eq!(today_label(180), "Today: 3h 0m");
+    }
+
+    #[tokio::test]
+    async fn offers_pinned_then_recent_issues() {
+        let db = crate::db::test_pool().await;
+        sqlx::raw_sql(
+            "INSERT INTO settings (key, value) VALUES ('pinned_issues', '[\"OPS-9\"]');
+             INSERT INTO issues (issue_key, summary, project_key) VALUES
+                ('OPS-9', 'On-call', 'OPS'), ('SUP-1', 'Login bug', 'SUP'), ('SUP-2', 'Export', 'SUP');
+             INSERT INTO worklogs (issue_key, started_at, duration_seconds, sync_status) VALUES
+                ('SUP-1', '2024-03-04T09:00:00+00:00', 600, 'synced'),
+                ('SUP-2', '2024-03-05T09:00:00+01:00', 600, 'pending'),
+                ('OPS-9', '2024-03-06T09:00:00+00:00', 600, 'pending'),
+                ('SUP-3', '2024-03-07T09:00:00+00:00', 600, 'pending_delete');",
+        )
+        .execute(&db)
+        .await
+        .unwrap();
+        let tick = TimerTick {
+            timers: vec![TickTimer {
+                id: 4,
+                issue_key: "SUP-2".to_string(),
+                elapsed_secs: 90,
+                is_paused: true,
+            }],
+            today_seconds: 3 * 3600 + 59,
+        };
+
+        let mut conn = db.acquire().await.unwrap();
+        let model = load_model(&mut conn, &tick).await.unwrap();
+
+        let keys: Vec<&str> = model.start_on.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(keys, vec!["OPS-9", "SUP-2", "SUP-1"]);
+        assert_eq!(model.start_on[2].1, "Login bug");
+        assert_eq!(
+            model.timers,
+            vec![MenuTimer {
+                id: 4,
+                issue_key: "SUP-2".to_string(),
+                summary: "Export".to_string(),
+                is_paused: true,
+            }]
+        );
+        assert!(model.shows_timers_of(&tick));
+
+        // Resuming the timer changes what the menu has to offer for it
+        let resumed = TimerTick {
+            timers: vec![TickTimer { is_paused: false, ..tick.timers[0].clone() }],
+            ..tick
+        };
+        assert!(!model.shows_timers_of(&resumed));
+    }
+}
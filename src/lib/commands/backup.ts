@@ -0,0 +1,29 @@
+import { invoke } from "@tauri-apps/api/core";
+
+export interface BackupInfo {
+  file_name: string;
+  created_at: string;
+  size_bytes: number;
+}
+
+export interface IntegrityStatus {
+  problems: string[];
+  backups: BackupInfo[];
+}
+
+export async function listBackups(): Promise<BackupInfo[]> {
+  return invoke("db_list_backups");
+}
+
+export async function createBackup(): Promise<BackupInfo> {
+  return invoke("db_create_backup");
+}
+
+/** Replaces the database with the given backup and restarts the app. */
+export async function restoreBackup(fileName: string): Promise<void> {
+  return invoke("db_restore_backup", { fileName });
+}
+
+export async function getIntegrityStatus(): Promise<IntegrityStatus> {
+  return invoke("db_integrity_status");
+}
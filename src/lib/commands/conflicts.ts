@@ -0,0 +1,13 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { ConflictResolution, WorklogConflict } from "../types/worklog";
+
+export async function listConflicts(): Promise<WorklogConflict[]> {
+  return invoke("conflict_list");
+}
+
+export async function resolveConflict(
+  worklogId: number,
+  resolution: ConflictResolution,
+): Promise<void> {
+  return invoke("conflict_resolve", { worklogId, resolution });
+}
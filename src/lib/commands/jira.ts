@@ -1,16 +1,22 @@
 import { invoke } from "@tauri-apps/api/core";
-import type { JiraIssue, JiraUser } from "../types/jira";
-import type { PushSummary, ImportSummary, ExternalWorklog } from "../types/worklog";
+import type { JiraFlavor, JiraIssue, JiraUser } from "../types/jira";
+import type { PushSummary, PushReport, ImportSummary, ExternalWorklog, UserWorklogs } from "../types/worklog";
+
+export async function detectFlavor(baseUrl: string): Promise<JiraFlavor> {
+  return invoke("jira_detect_flavor", { baseUrl });
+}
 
 export async function testConnection(
   baseUrl: string,
   email: string,
   apiToken: string,
+  flavor?: JiraFlavor,
 ): Promise<JiraUser> {
   return invoke("jira_test_connection", {
     baseUrl,
     email,
     apiToken,
+    flavor,
   });
 }
 
@@ -21,6 +27,24 @@ export async function searchIssues(
   return invoke("jira_search_issues", { jql, maxResults });
 }
 
+export async function oauthConnect(
+  clientId: string,
+  clientSecret: string | null,
+  redirectPort: number,
+  siteUrl?: string,
+): Promise<JiraUser> {
+  return invoke("jira_oauth_connect", {
+    clientId,
+    clientSecret,
+    redirectPort,
+    siteUrl,
+  });
+}
+
+export async function oauthDisconnect(): Promise<void> {
+  return invoke("jira_oauth_disconnect");
+}
+
 export async function pushWorklog(worklogId: number): Promise<void> {
   return invoke("jira_push_worklog", { worklogId });
 }
@@ -29,6 +53,27 @@ export async function pushAllPending(date: string): Promise<PushSummary> {
   return invoke("jira_push_all_pending", { date });
 }
 
+/**
+ * Pushes pending worklogs dated `dateFrom..=dateTo`; leave both out to push
+ * everything pending. With `dryRun` nothing is sent and the report only
+ * previews and validates each worklog.
+ */
+export async function pushPending(
+  dateFrom: string | null,
+  dateTo: string | null,
+  dryRun: boolean,
+): Promise<PushReport> {
+  return invoke("jira_push_pending", { dateFrom, dateTo, dryRun });
+}
+
+/**
+ * Links a pending worklog to the Jira worklog a push preview found for it
+ * (`duplicate_of`), so it is not created a second time.
+ */
+export async function linkWorklog(worklogId: number, jiraWorklogId: string): Promise<void> {
+  return invoke("jira_link_worklog", { worklogId, jiraWorklogId });
+}
+
 export async function updateJiraWorklog(
   worklogId: number,
   durationSeconds?: number,
@@ -47,8 +92,20 @@ export async function deleteJiraWorklog(worklogId: number): Promise<void> {
   return invoke("jira_delete_worklog", { worklogId });
 }
 
-export async function importWorklogs(date: string): Promise<ImportSummary> {
-  return invoke("jira_import_worklogs", { date });
+/** Imports the current user's worklogs for every local date in `dateFrom..=dateTo`. */
+export async function importWorklogs(
+  dateFrom: string,
+  dateTo: string = dateFrom,
+): Promise<ImportSummary> {
+  return invoke("jira_import_worklogs", { dateFrom, dateTo });
+}
+
+/**
+ * Imports only the worklogs changed or deleted in Jira since the previous call.
+ * The first call imports the user's worklogs of the last 30 days instead.
+ */
+export async function importWorklogChanges(): Promise<ImportSummary> {
+  return invoke("jira_import_worklog_changes");
 }
 
 export async function getMyself(): Promise<JiraUser> {
@@ -64,7 +121,7 @@ export async function fetchUserWorklogs(
   authorName: string,
   dateFrom: string,
   dateTo: string,
-): Promise<ExternalWorklog[]> {
+): Promise<UserWorklogs> {
   return invoke("jira_fetch_user_worklogs", {
     accountId,
     authorName,
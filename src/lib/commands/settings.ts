@@ -1,4 +1,6 @@
 import { invoke } from "@tauri-apps/api/core";
+import type { JiraFlavor } from "../types/jira";
+import type { RetryPolicy, RoundingPolicy } from "../types/settings";
 
 export async function settingsGet(
   key: string,
@@ -21,14 +23,32 @@ export async function saveJiraConfig(
   baseUrl: string,
   email: string,
   apiToken: string,
+  flavor?: JiraFlavor,
 ): Promise<void> {
   return invoke("settings_save_jira_config", {
     baseUrl,
     email,
     apiToken,
+    flavor,
   });
 }
 
 export async function loadJiraConfig(): Promise<Record<string, string> | null> {
   return invoke("settings_load_jira_config");
 }
+
+export async function getRoundingPolicy(): Promise<RoundingPolicy> {
+  return invoke("settings_get_rounding");
+}
+
+export async function saveRoundingPolicy(policy: RoundingPolicy): Promise<void> {
+  return invoke("settings_save_rounding", { policy });
+}
+
+export async function getJiraRetryPolicy(): Promise<RetryPolicy> {
+  return invoke("settings_get_jira_retry_policy");
+}
+
+export async function saveJiraRetryPolicy(policy: RetryPolicy): Promise<void> {
+  return invoke("settings_save_jira_retry_policy", { policy });
+}
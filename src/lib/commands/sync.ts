@@ -0,0 +1,43 @@
+import { invoke } from "@tauri-apps/api/core";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
+import type {
+  SyncEvent,
+  SyncEventFilter,
+  SyncItemEvent,
+  SyncSettings,
+  SyncStatus,
+} from "../types/settings";
+
+export async function getSyncSettings(): Promise<SyncSettings> {
+  return invoke("sync_get_settings");
+}
+
+export async function saveSyncSettings(settings: SyncSettings): Promise<void> {
+  return invoke("sync_save_settings", { settings });
+}
+
+export async function getSyncStatus(): Promise<SyncStatus> {
+  return invoke("sync_get_status");
+}
+
+/** Sync history, newest first. */
+export async function getSyncEvents(filter?: SyncEventFilter): Promise<SyncEvent[]> {
+  return invoke("sync_get_events", { filter });
+}
+
+/** Queues every pending worklog and wakes the background worker. */
+export async function syncNow(): Promise<void> {
+  return invoke("sync_now");
+}
+
+export function onSyncStatus(
+  handler: (status: SyncStatus) => void,
+): Promise<UnlistenFn> {
+  return listen<SyncStatus>("sync://status", (e) => handler(e.payload));
+}
+
+export function onSyncItem(
+  handler: (item: SyncItemEvent) => void,
+): Promise<UnlistenFn> {
+  return listen<SyncItemEvent>("sync://item", (e) => handler(e.payload));
+}
@@ -1,35 +1,82 @@
 import { invoke } from "@tauri-apps/api/core";
-import type { TimerState } from "../types/settings";
-import type { StoppedWorklog } from "../types/worklog";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
+import type { TimerState, TimerTick } from "../types/settings";
+import type { StoppedWorklog, TimerSwitch } from "../types/worklog";
 
-export async function timerGetState(): Promise<TimerState | null> {
-  return invoke("timer_get_state");
+// Commands taking an optional `timerId` act on the only timer when it is left
+// out, and fail when several timers are running.
+
+export async function timerGetState(timerId?: number): Promise<TimerState | null> {
+  return invoke("timer_get_state", { timerId });
+}
+
+/** Every timer, oldest first. */
+export async function timerList(): Promise<TimerState[]> {
+  return invoke("timer_list");
+}
+
+/**
+ * Start a timer; in exclusive mode every other timer is stopped first.
+ * `startedAt` (RFC 3339) back-dates the start when the timer was started late.
+ */
+export async function timerStart(
+  issueKey: string,
+  startedAt?: string,
+  name?: string,
+): Promise<TimerState> {
+  return invoke("timer_start", { issueKey, startedAt, name });
+}
+
+/** Move the timer's first start, or add (negative: remove) tracked time before it. */
+export async function timerAdjust(
+  change: { startedAt: string } | { addSeconds: number },
+  timerId?: number,
+): Promise<TimerState> {
+  return invoke("timer_adjust", { ...change, timerId });
+}
+
+/**
+ * Hand the timer over to another issue as of `switchAt` (RFC 3339, default now),
+ * saving the old one up to that moment.
+ */
+export async function timerSwitch(
+  issueKey: string,
+  switchAt: string | undefined,
+  keepDescription: boolean,
+  timerId?: number,
+): Promise<TimerSwitch> {
+  return invoke("timer_switch", { timerId, issueKey, switchAt, keepDescription });
 }
 
-export async function timerStart(issueKey: string): Promise<TimerState> {
-  return invoke("timer_start", { issueKey });
+export async function timerPause(timerId?: number): Promise<TimerState> {
+  return invoke("timer_pause", { timerId });
 }
 
-export async function timerPause(): Promise<TimerState> {
-  return invoke("timer_pause");
+export async function timerResume(timerId?: number): Promise<TimerState> {
+  return invoke("timer_resume", { timerId });
 }
 
-export async function timerResume(): Promise<TimerState> {
-  return invoke("timer_resume");
+/** Stops the timer; time spanning several days is saved as one worklog per day. */
+export async function timerStop(timerId?: number): Promise<StoppedWorklog[]> {
+  return invoke("timer_stop", { timerId });
 }
 
-export async function timerStop(): Promise<StoppedWorklog> {
-  return invoke("timer_stop");
+export async function timerRename(timerId: number, name: string): Promise<TimerState> {
+  return invoke("timer_rename", { timerId, name });
 }
 
-export async function timerUpdateTray(displayText: string): Promise<void> {
-  return invoke("timer_update_tray", { displayText });
+export async function timerUpdateDescription(description: string, timerId?: number): Promise<void> {
+  return invoke("timer_update_description", { description, timerId });
 }
 
-export async function timerUpdateDescription(description: string): Promise<void> {
-  return invoke("timer_update_description", { description });
+/** Sent every second by the backend clock, which also keeps the tray current. */
+export function onTimerTick(
+  handler: (tick: TimerTick) => void,
+): Promise<UnlistenFn> {
+  return listen<TimerTick>("timer://tick", (e) => handler(e.payload));
 }
 
-export async function timerSetTrayIcon(iconName: string): Promise<void> {
-  return invoke("timer_set_tray_icon", { iconName });
+/** A timer was started, paused, resumed or stopped from the tray menu. */
+export function onTimerChanged(handler: () => void): Promise<UnlistenFn> {
+  return listen("timer://changed", () => handler());
 }
@@ -1,6 +1,6 @@
 import { loadJiraConfig, saveJiraConfig, settingsGet, settingsSet } from "../commands/settings";
 import { testConnection } from "../commands/jira";
-import { timerUpdateTray } from "../commands/timer";
+import type { TimerStopMode } from "../types/settings";
 
 const DEFAULT_HIDDEN_STATUSES = ["Done", "Canceled"];
 
@@ -9,17 +9,31 @@ class SettingsStore {
   jiraEmail = $state("");
   jqlFilter = $state("assignee = currentUser() ORDER BY updated DESC");
   isConnected = $state(false);
+  /** Why the saved Jira connection could not be read, e.g. an unavailable secret store */
+  configError = $state("");
   userName = $state("");
   hiddenStatuses = $state<string[]>(DEFAULT_HIDDEN_STATUSES);
   showTrayTitle = $state(false);
+  /** Menu bar title; `{issue}`, `{elapsed}` and `{today}` are filled in */
+  trayTitleFormat = $state("{issue} {elapsed}");
   theme = $state<"system" | "light" | "dark">("system");
+  /** Local `HH:MM` at which a stopped timer's time rolls over to the next day */
+  dayBoundary = $state("00:00");
+  timerStopMode = $state<TimerStopMode>("per_day");
+  /** Starting a timer stops the others; off allows parallel timers */
+  timerExclusive = $state(true);
 
   async init() {
-    const config = await loadJiraConfig();
-    if (config) {
-      this.jiraBaseUrl = config["jira_base_url"] ?? "";
-      this.jiraEmail = config["jira_email"] ?? "";
-      this.isConnected = true;
+    try {
+      const config = await loadJiraConfig();
+      if (config) {
+        this.jiraBaseUrl = config["jira_base_url"] ?? "";
+        this.jiraEmail = config["jira_email"] ?? "";
+        this.isConnected = true;
+      }
+      this.configError = "";
+    } catch (e) {
+      this.configError = String(e);
     }
     const jql = await settingsGet("jql_filter");
     if (jql) this.jqlFilter = jql;
@@ -35,6 +49,14 @@ class SettingsStore {
 
     const trayTitle = await settingsGet("show_tray_title");
     if (trayTitle !== null) this.showTrayTitle = trayTitle !== "false";
+    const trayTitleFormat = await settingsGet("tray_title_format");
+    if (trayTitleFormat) this.trayTitleFormat = trayTitleFormat;
+
+    const dayBoundary = await settingsGet("timer_day_boundary");
+    if (dayBoundary) this.dayBoundary = dayBoundary;
+    const stopMode = await settingsGet("timer_stop_mode");
+    if (stopMode === "per_day" || stopMode === "per_segment") this.timerStopMode = stopMode;
+    this.timerExclusive = (await settingsGet("timer_exclusive")) !== "false";
 
     const theme = await settingsGet("theme");
     if (theme === "light" || theme === "dark") this.theme = theme;
@@ -61,6 +83,7 @@ class SettingsStore {
     this.jiraBaseUrl = baseUrl;
     this.jiraEmail = email;
     this.isConnected = true;
+    this.configError = "";
     this.userName = user.displayName;
     return user.displayName;
   }
@@ -75,10 +98,29 @@ class SettingsStore {
     await settingsSet("hidden_statuses", JSON.stringify(statuses));
   }
 
+  async saveDayBoundary(value: string) {
+    this.dayBoundary = value;
+    await settingsSet("timer_day_boundary", value);
+  }
+
+  async saveTimerStopMode(value: TimerStopMode) {
+    this.timerStopMode = value;
+    await settingsSet("timer_stop_mode", value);
+  }
+
+  async toggleTimerExclusive(enabled: boolean) {
+    this.timerExclusive = enabled;
+    await settingsSet("timer_exclusive", String(enabled));
+  }
+
   async toggleTrayTitle(enabled: boolean) {
     this.showTrayTitle = enabled;
     await settingsSet("show_tray_title", String(enabled));
-    if (!enabled) await timerUpdateTray("");
+  }
+
+  async saveTrayTitleFormat(value: string) {
+    this.trayTitleFormat = value.trim() || "{issue} {elapsed}";
+    await settingsSet("tray_title_format", this.trayTitleFormat);
   }
 }
 
@@ -1,14 +1,19 @@
 import * as cmd from "../commands/timer";
 import { formatDuration } from "../utils/format";
-import type { TimerState } from "../types/settings";
-import { settingsStore } from "./settings.svelte";
+import type { TimerState, TimerTick } from "../types/settings";
 
 class TimerStore {
+  /** Every timer; more than one only when parallel timers are allowed */
+  timers = $state<TimerState[]>([]);
+  /** The timer shown and controlled by the UI */
   current = $state<TimerState | null>(null);
   issueSummary = $state("");
   elapsedSeconds = $state(0);
+  /** Worklogs and timer time on the current tracking day, from the last tick */
+  todaySeconds = $state(0);
   description = $state("");
-  private intervalId: number | null = null;
+  private unlistenTick: (() => void) | null = null;
+  private unlistenChanged: (() => void) | null = null;
   private descriptionTimerId: number | null = null;
 
   get isRunning(): boolean {
@@ -25,57 +30,85 @@ class TimerStore {
   }
 
   async init() {
-    const state = await cmd.timerGetState();
+    // The backend counts and keeps the tray current; the UI only follows its ticks
+    this.unlistenTick ??= await cmd.onTimerTick((tick) => this.applyTick(tick));
+    this.unlistenChanged ??= await cmd.onTimerChanged(() => this.reload());
+    await this.reload();
+  }
+
+  /** Catch up with timers changed outside the window, e.g. from the tray menu. */
+  async reload() {
+    this.flushPendingDescription();
+    this.timers = await cmd.timerList();
+    const same = this.timers.find((t) => t.id === this.current?.id);
+    const running = this.timers.filter((t) => !t.is_paused);
+    const state = same ?? running.at(-1) ?? this.timers.at(-1);
     if (state) {
-      this.current = state;
-      this.description = state.description;
-      this.recalcElapsed();
-      if (state.is_paused) {
-        cmd.timerSetTrayIcon("paused");
-      } else {
-        cmd.timerSetTrayIcon("working");
-        this.startTicking();
-      }
+      if (state.id !== this.current?.id) this.issueSummary = "";
+      this.select(state);
+    } else {
+      this.current = null;
+      this.issueSummary = "";
+      this.elapsedSeconds = 0;
+      this.description = "";
     }
   }
 
-  async start(issueKey: string, summary: string) {
-    if (this.current) {
-      await this.stop();
-    }
-    this.current = await cmd.timerStart(issueKey);
+  /** Show and control `state`, one of `timers`. */
+  select(state: TimerState) {
+    this.flushPendingDescription();
+    this.current = state;
+    this.description = state.description;
+    this.recalcElapsed();
+  }
+
+  async start(issueKey: string, summary: string, startedAt?: string) {
+    // In exclusive mode the backend saves the running timer up to the new start
+    this.flushPendingDescription();
+    this.current = await cmd.timerStart(issueKey, startedAt);
     this.issueSummary = summary;
-    this.elapsedSeconds = 0;
     this.description = "";
-    this.startTicking();
-    cmd.timerSetTrayIcon("working");
+    this.recalcElapsed();
+    this.timers = await cmd.timerList();
   }
 
   async pause() {
-    this.current = await cmd.timerPause();
-    this.stopTicking();
+    this.current = await cmd.timerPause(this.current?.id);
     this.recalcElapsed();
-    if (settingsStore.showTrayTitle) {
-      await cmd.timerUpdateTray(`${this.current.issue_key} ${formatDuration(this.elapsedSeconds)} ⏸`);
-    }
-    cmd.timerSetTrayIcon("paused");
   }
 
   async resume() {
-    this.current = await cmd.timerResume();
-    this.startTicking();
-    cmd.timerSetTrayIcon("working");
+    this.current = await cmd.timerResume(this.current?.id);
+    this.recalcElapsed();
+  }
+
+  async switchTo(issueKey: string, summary: string, switchAt?: string, keepDescription = false) {
+    this.flushPendingDescription();
+    const result = await cmd.timerSwitch(issueKey, switchAt, keepDescription, this.current?.id);
+    this.current = result.timer;
+    this.issueSummary = summary;
+    this.description = result.timer.description;
+    this.recalcElapsed();
+    this.timers = await cmd.timerList();
+    return result.saved;
+  }
+
+  async adjust(change: { startedAt: string } | { addSeconds: number }) {
+    this.current = await cmd.timerAdjust(change, this.current?.id);
+    this.recalcElapsed();
   }
 
   async stop() {
-    const result = await cmd.timerStop();
+    this.flushPendingDescription();
+    const result = await cmd.timerStop(this.current?.id);
     this.current = null;
     this.issueSummary = "";
     this.elapsedSeconds = 0;
     this.description = "";
-    this.stopTicking();
-    await cmd.timerUpdateTray("");
-    cmd.timerSetTrayIcon("idle");
+    this.timers = await cmd.timerList();
+    // A parallel timer still running takes over the display
+    const next = this.timers.at(-1);
+    if (next) this.select(next);
     return result;
   }
 
@@ -84,8 +117,9 @@ class TimerStore {
     if (this.descriptionTimerId) {
       clearTimeout(this.descriptionTimerId);
     }
+    const timerId = this.current?.id;
     this.descriptionTimerId = window.setTimeout(() => {
-      cmd.timerUpdateDescription(text);
+      cmd.timerUpdateDescription(text, timerId);
       this.descriptionTimerId = null;
     }, 500);
   }
@@ -94,25 +128,14 @@ class TimerStore {
     if (this.descriptionTimerId) {
       clearTimeout(this.descriptionTimerId);
       this.descriptionTimerId = null;
-      cmd.timerUpdateDescription(this.description);
+      cmd.timerUpdateDescription(this.description, this.current?.id);
     }
   }
 
-  private startTicking() {
-    this.stopTicking();
-    this.intervalId = window.setInterval(() => {
-      this.recalcElapsed();
-      if (this.current && settingsStore.showTrayTitle) {
-        cmd.timerUpdateTray(this.displayText);
-      }
-    }, 1000);
-  }
-
-  private stopTicking() {
-    if (this.intervalId) {
-      clearInterval(this.intervalId);
-      this.intervalId = null;
-    }
+  private applyTick(tick: TimerTick) {
+    this.todaySeconds = tick.today_seconds;
+    const shown = tick.timers.find((t) => t.id === this.current?.id);
+    if (shown) this.elapsedSeconds = shown.elapsed_secs;
   }
 
   private recalcElapsed() {
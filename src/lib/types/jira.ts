@@ -1,3 +1,5 @@
+export type JiraFlavor = "cloud" | "data_center";
+
 export interface JiraUser {
   accountId: string;
   displayName: string;
@@ -1,8 +1,103 @@
 export interface TimerState {
+  id: number;
+  /** Label to tell parallel timers apart; empty unless the user named it */
+  name: string;
   issue_key: string;
   started_at: string;
   accumulated_secs: number;
   is_paused: boolean;
   paused_at: string | null;
   description: string;
+  /** Every stretch the timer ran, oldest first; the last is open while running */
+  segments: TimerSegment[];
+}
+
+export interface TimerSegment {
+  started_at: string;
+  ended_at: string | null;
+}
+
+/** Payload of the backend's once-a-second `timer://tick` event. */
+export interface TimerTick {
+  timers: { id: number; issue_key: string; elapsed_secs: number; is_paused: boolean }[];
+  /** Worklogs and timer time on the current tracking day */
+  today_seconds: number;
+}
+
+/** How a stopped timer becomes worklogs: one per day, or one per run between pauses. */
+export type TimerStopMode = "per_day" | "per_segment";
+
+export type RoundingMode = "none" | "up" | "nearest" | "down";
+
+export interface RoundingPolicy {
+  mode: RoundingMode;
+  increment_seconds: number;
+  /** Anything tracked is billed at least this long; 0 disables it */
+  minimum_seconds: number;
+  /** Also round pending worklogs right before they are pushed */
+  at_push: boolean;
+}
+
+export interface RetryPolicy {
+  max_retries: number;
+  base_delay_ms: number;
+  max_delay_ms: number;
+  requests_per_second: number;
+}
+
+export interface SyncSettings {
+  auto_push: boolean;
+  grace_minutes: number;
+}
+
+export type SyncState = "idle" | "syncing" | "offline" | "not_configured";
+
+export interface SyncStatus {
+  state: SyncState;
+  pending: number;
+  last_error: string | null;
+  last_run_at: string | null;
+}
+
+export interface SyncItemEvent {
+  worklog_id: number;
+  operation: "create" | "update" | "delete";
+  outcome: "done" | "retry" | "failed";
+  error: string | null;
+}
+
+export type SyncEventOperation =
+  | "create"
+  | "update"
+  | "delete"
+  | "link"
+  | "import_create"
+  | "import_update"
+  | "import_delete";
+
+export type SyncEventOutcome = "success" | "error" | "conflict" | "skipped";
+
+/** One row of the sync history: a Jira mutation or an import decision. */
+export interface SyncEvent {
+  id: number;
+  /** UTC, `YYYY-MM-DD HH:MM:SS` */
+  occurred_at: string;
+  worklog_id: number | null;
+  jira_worklog_id: string | null;
+  issue_key: string | null;
+  operation: SyncEventOperation;
+  request: string;
+  outcome: SyncEventOutcome;
+  error: string | null;
+}
+
+export interface SyncEventFilter {
+  worklog_id?: number;
+  jira_worklog_id?: string;
+  issue_key?: string;
+  operation?: SyncEventOperation;
+  outcome?: SyncEventOutcome;
+  since?: string;
+  until?: string;
+  limit?: number;
 }
@@ -1,10 +1,14 @@
+import type { TimerState } from "./settings";
+
 export interface Worklog {
   id: number;
   issue_key: string;
   started_at: string;
   duration_seconds: number;
+  /** Tracked time before rounding; null for worklogs that came from Jira */
+  raw_duration_seconds: number | null;
   description: string;
-  sync_status: "pending" | "synced" | "error";
+  sync_status: "pending" | "synced" | "error" | "modified" | "pending_delete" | "conflict";
   jira_worklog_id: string | null;
   sync_error: string | null;
   created_at: string;
@@ -22,6 +26,12 @@ export interface ExternalWorklog {
   author_name: string;
 }
 
+export interface UserWorklogs {
+  worklogs: ExternalWorklog[];
+  /** The issue search hit its limit, so worklogs on the remaining issues are missing */
+  truncated: boolean;
+}
+
 export interface WorklogFilter {
   issue_key?: string;
   sync_status?: string;
@@ -32,9 +42,16 @@ export interface WorklogFilter {
 export interface StoppedWorklog {
   id: number;
   issue_key: string;
+  started_at: string;
   duration_seconds: number;
 }
 
+export interface TimerSwitch {
+  /** Worklogs saved for the timer that was switched away from */
+  saved: StoppedWorklog[];
+  timer: TimerState;
+}
+
 export interface PushSummary {
   total: number;
   success: number;
@@ -42,11 +59,64 @@ export interface PushSummary {
   errors: string[];
 }
 
+/** A pending worklog exactly as it would be sent to Jira. */
+export interface PushPreviewItem {
+  worklog_id: number;
+  issue_key: string;
+  /** Start time in Jira's format */
+  started: string;
+  duration_seconds: number;
+  /** ADF on Cloud, plain text on Data Center */
+  comment: unknown | null;
+  problems: string[];
+  duplicate_of: string | null;
+  from_earlier_push: boolean;
+}
+
+export interface PushReport {
+  dry_run: boolean;
+  items: PushPreviewItem[];
+  /** Null for a dry run */
+  summary: PushSummary | null;
+}
+
 export interface ImportSummary {
   imported: number;
   updated: number;
   deleted: number;
   skipped: number;
+  conflicts: number;
   issues_checked: number;
   warnings: string[];
+  days: DayImportSummary[];
+}
+
+export interface DayImportSummary {
+  date: string;
+  imported: number;
+  updated: number;
+  deleted: number;
+  skipped: number;
+  conflicts: number;
+}
+
+export interface WorklogFields {
+  started_at: string;
+  duration_seconds: number;
+  description: string;
 }
+
+export interface WorklogConflict {
+  worklog_id: number;
+  issue_key: string;
+  jira_worklog_id: string | null;
+  reason: string | null;
+  detected_at: string;
+  /** null when the worklog was deleted here */
+  local: WorklogFields | null;
+  base: WorklogFields | null;
+  /** null when the worklog was deleted in Jira */
+  remote: WorklogFields | null;
+}
+
+export type ConflictResolution = "keep_mine" | "take_theirs" | "merge_description";